@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use flate2::write::GzEncoder;
 use flate2::Compression;
@@ -11,12 +11,13 @@ use tar::Builder;
 
 use std::collections::BTreeMap;
 
+use crate::spec_parser::parser::parse_spec_in;
 use crate::spec_parser::{
-    parse_spec_file, ApiSpec, DispatchConfig, Message, MiddlewareConfig, Parameter, RequestBody,
-    ResponseContent, SpecFormat,
+    ApiSpec, DispatchConfig, Message, MiddlewareConfig, Operation, Parameter, ParseError,
+    RequestBody, ResponseContent, SpecFormat,
 };
 
-use crate::error::{CompileError, CompileWarning};
+use crate::error::{diagnostics_to_json, CompileError, CompileWarning, Diagnostic, DiagnosticCode};
 use crate::manifest::ProjectManifest;
 
 /// Current artifact format version.
@@ -43,6 +44,19 @@ pub struct CompileOptions {
     pub provenance_source: Option<String>,
     /// Bypass the plugin download cache entirely (no read, no write).
     pub no_cache: bool,
+    /// Fail compilation with [`CompileError::WarningsDenied`] if any warning
+    /// is emitted (e.g. for CI builds).
+    pub deny_warnings: bool,
+    /// Compile only the operations whose `operationId` is listed, e.g. for a
+    /// canary artifact. Other operations (including ones without an id) are
+    /// dropped before their `$ref`s are resolved, and only plugins the kept
+    /// operations use are bundled. Unknown ids fail with E1057.
+    pub only_operations: Option<Vec<String>>,
+    /// Values for `${env:VAR}` placeholders in plugin config strings,
+    /// substituted at compile time. The result is stored in the artifact, so
+    /// secrets are better left as runtime `env://` references. A placeholder
+    /// naming a variable missing here fails with E1025.
+    pub env: BTreeMap<String, String>,
 }
 
 impl Default for CompileOptions {
@@ -54,6 +68,9 @@ impl Default for CompileOptions {
             provenance_commit: None,
             provenance_source: None,
             no_cache: false,
+            deny_warnings: false,
+            only_operations: None,
+            env: BTreeMap::new(),
         }
     }
 }
@@ -71,6 +88,8 @@ const KNOWN_EXTENSIONS: &[&str] = &[
     "x-barbacane-dispatch",    // Operation level - dispatcher config (required)
     "x-barbacane-middlewares", // Root or operation level - middleware chain
     "x-barbacane-mcp",         // Root or operation level - MCP server config
+    "x-barbacane-timeout",     // Operation level - upstream timeout (ms)
+    "x-barbacane-plugins",     // Operation level - inline dispatcher + middlewares
 ];
 
 /// Result of compilation including the manifest and any warnings.
@@ -80,6 +99,115 @@ pub struct CompileResult {
     pub manifest: Manifest,
     /// Warnings produced during compilation (non-fatal issues).
     pub warnings: Vec<CompileWarning>,
+    /// SHA-256 (hex) of the written .bca bytes.
+    #[serde(default)]
+    archive_sha256: String,
+    /// Parsed specs, reused by [`compile_with_manifest_incremental`].
+    #[serde(skip)]
+    parse_cache: Option<ParseCache>,
+    /// Spec files this compile parsed (the rest were reused).
+    #[serde(skip)]
+    reparsed_specs: Vec<PathBuf>,
+}
+
+/// A parsed spec with its source content and SHA-256 (hex).
+type ParsedSpec = (ApiSpec, String, String);
+
+/// Specs parsed by a compile, keyed by the path they were read from, so a
+/// later incremental compile can skip re-parsing the unchanged ones.
+#[derive(Debug, Clone, Default)]
+struct ParseCache {
+    /// The `only_operations` filter the specs were parsed with.
+    only_operations: Option<Vec<String>>,
+    /// Spec path -> (spec, content, sha256).
+    specs: HashMap<PathBuf, ParsedSpec>,
+}
+
+impl CompileResult {
+    /// SHA-256 of the serialized .bca archive (`sha256:<hex>`).
+    ///
+    /// Compilation is deterministic, so the same specs, plugins, and options
+    /// yield the same hash — usable as a content-addressable cache key. Unlike
+    /// [`Manifest::artifact_hash`], this covers every byte of the archive,
+    /// including provenance and the signature.
+    pub fn artifact_hash(&self) -> String {
+        format!("sha256:{}", self.archive_sha256)
+    }
+
+    /// Warnings as a JSON array of `{severity, code, message, location?}`
+    /// objects, for tooling. Errors are reported by
+    /// [`CompileError::diagnostics_json`].
+    pub fn diagnostics_json(&self) -> String {
+        let diagnostics: Vec<Diagnostic> = self
+            .warnings
+            .iter()
+            .map(CompileWarning::diagnostic)
+            .collect();
+        diagnostics_to_json(&diagnostics)
+    }
+
+    /// Spec files a [`compile_with_manifest`] run parsed, in input order:
+    /// every spec for a full compile, only the re-parsed ones for
+    /// [`compile_with_manifest_incremental`]. Empty for other entry points.
+    pub fn reparsed_specs(&self) -> &[PathBuf] {
+        &self.reparsed_specs
+    }
+}
+
+/// Summary of a [`compile_to_writer`] run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileReport {
+    /// Number of source specs compiled.
+    pub specs_count: usize,
+    /// Number of compiled routes.
+    pub routes_count: usize,
+    /// Number of bundled plugins.
+    pub plugins_count: usize,
+    /// SHA-256 of the written archive (see [`CompileResult::artifact_hash`]).
+    pub artifact_hash: String,
+    /// Warnings produced during compilation (non-fatal issues).
+    pub warnings: Vec<CompileWarning>,
+}
+
+/// Where a warning was raised: the human-readable location plus the spec
+/// file and JSON Pointer for structured diagnostics.
+#[derive(Clone)]
+struct WarningSite {
+    location: String,
+    spec: String,
+    pointer: Option<String>,
+}
+
+impl WarningSite {
+    fn warning(&self, code: DiagnosticCode, message: String) -> CompileWarning {
+        CompileWarning {
+            code,
+            message,
+            location: Some(self.location.clone()),
+            spec: Some(self.spec.clone()),
+            pointer: self.pointer.clone(),
+        }
+    }
+}
+
+/// Escape a JSON Pointer reference token (RFC 6901).
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// JSON Pointer to an operation in its source spec.
+fn operation_pointer(spec: &ApiSpec, op: &Operation) -> Option<String> {
+    match spec.format {
+        SpecFormat::OpenApi => Some(format!(
+            "/paths/{}/{}",
+            escape_pointer_token(&op.path),
+            op.method.to_ascii_lowercase()
+        )),
+        SpecFormat::AsyncApi => op
+            .operation_id
+            .as_ref()
+            .map(|id| format!("/operations/{}", escape_pointer_token(id))),
+    }
 }
 
 /// The manifest.json embedded in a .bca artifact.
@@ -202,7 +330,12 @@ pub struct CompiledOperation {
     pub path: String,
     /// HTTP method (OpenAPI: "GET", AsyncAPI: "SEND"/"RECEIVE").
     pub method: String,
+    /// The spec's `operationId`, or one synthesized from method and path
+    /// when the spec declares none (e.g. `get_users_id`).
     pub operation_id: Option<String>,
+    /// Whether `operation_id` was synthesized rather than declared.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub operation_id_synthesized: bool,
     /// Operation summary (short description).
     #[serde(default)]
     pub summary: Option<String>,
@@ -225,6 +358,9 @@ pub struct CompiledOperation {
     /// Sunset date for deprecated operations (HTTP-date format per RFC 9110).
     #[serde(default)]
     pub sunset: Option<String>,
+    /// Upstream timeout in milliseconds (from `x-barbacane-timeout`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
     /// AsyncAPI messages (for async operations only).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub messages: Vec<Message>,
@@ -240,6 +376,73 @@ pub struct CompiledOperation {
     /// MCP-specific tool description override.
     #[serde(default)]
     pub mcp_description: Option<String>,
+    /// Request content-type allowlist from `x-barbacane-accept` (lowercased
+    /// media types, `type/*` wildcards allowed). Enforced before the body
+    /// schema lookup; empty means no allowlist.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accepted_content_types: Vec<String>,
+    /// SHA-256 over the resolved dispatcher and middleware chain (plugin names
+    /// and configs). Stable across compiles, so a hot-reloading data plane can
+    /// tell which routes' plugin configuration actually changed. Empty for
+    /// artifacts compiled before it was introduced.
+    #[serde(default)]
+    pub config_fingerprint: String,
+}
+
+impl CompiledRoutes {
+    /// Iterate over the compiled operations in artifact order.
+    pub fn iter_operations(&self) -> impl Iterator<Item = &CompiledOperation> {
+        self.operations.iter()
+    }
+}
+
+impl CompiledOperation {
+    /// The operation's route and plugin chain, for route tables and diffs.
+    pub fn route_summary(&self) -> RouteSummary {
+        RouteSummary {
+            method: self.method.clone(),
+            path: self.path.clone(),
+            operation_id: self.operation_id.clone(),
+            plugins: self
+                .middlewares
+                .iter()
+                .map(|mw| mw.name.clone())
+                .chain(std::iter::once(self.dispatch.name.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Summary of a compiled operation (see [`CompiledOperation::route_summary`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RouteSummary {
+    /// HTTP method (or AsyncAPI action).
+    pub method: String,
+    /// Path template (or AsyncAPI channel address).
+    pub path: String,
+    pub operation_id: Option<String>,
+    /// Plugin chain in execution order: the resolved middlewares, then the
+    /// dispatcher.
+    pub plugins: Vec<String>,
+}
+
+/// Route-level differences between two compiled route sets. Routes are keyed
+/// as `"METHOD path"` and each list is sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutesDiff {
+    /// Routes only present in the new set.
+    pub added: Vec<String>,
+    /// Routes only present in the old set.
+    pub removed: Vec<String>,
+    /// Routes present in both whose `config_fingerprint` differs.
+    pub config_changed: Vec<String>,
+}
+
+impl RoutesDiff {
+    /// Whether the two route sets are equivalent at the route/plugin-config level.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.config_changed.is_empty()
+    }
 }
 
 /// Compile one or more spec files into a .bca artifact.
@@ -258,12 +461,47 @@ pub fn compile(
     output: &Path,
     options: &CompileOptions,
 ) -> Result<CompileResult, CompileError> {
-    let specs = parse_specs(spec_paths)?;
+    let specs = parse_specs(spec_paths, options)?;
     // Plain `compile` receives caller-built bundles whose declared capabilities
     // may not have been read from plugin.toml (e.g. the control plane builds
     // bundles from the registry, which does not yet persist capabilities), so
     // the resulting artifact is not marked capability-authoritative.
-    compile_inner(&specs, plugins, output, options, false)
+    compile_inner(
+        &specs,
+        plugins,
+        Vec::new(),
+        || File::create(output),
+        options,
+        false,
+    )
+}
+
+/// Compile spec files into a .bca archive written to `writer` (a file, an
+/// upload stream, a `Vec<u8>`...), returning a [`CompileReport`] rather
+/// than the full manifest.
+///
+/// Archive entries are written to `writer` one at a time as they are
+/// produced. Nothing is written if validation fails. If writing fails
+/// part-way, the gzip stream is never finished, so the partial output is
+/// not a loadable archive.
+///
+/// Plugins are bundled as with [`compile`].
+pub fn compile_to_writer<W: Write>(
+    spec_paths: &[&Path],
+    plugins: &[PluginBundle],
+    options: &CompileOptions,
+    writer: W,
+) -> Result<CompileReport, CompileError> {
+    let specs = parse_specs(spec_paths, options)?;
+    // Same capability semantics as `compile`.
+    let result = compile_inner(&specs, plugins, Vec::new(), || Ok(writer), options, false)?;
+    Ok(CompileReport {
+        specs_count: result.manifest.source_specs.len(),
+        routes_count: result.manifest.routes_count,
+        plugins_count: result.manifest.plugins.len(),
+        artifact_hash: result.artifact_hash(),
+        warnings: result.warnings,
+    })
 }
 
 /// Compile specs with a project manifest into a .bca artifact.
@@ -285,7 +523,41 @@ pub fn compile_with_manifest(
     output: &Path,
     options: &CompileOptions,
 ) -> Result<CompileResult, CompileError> {
-    let specs = parse_specs(spec_paths)?;
+    compile_with_manifest_incremental(
+        spec_paths,
+        project_manifest,
+        manifest_base_path,
+        output,
+        options,
+        None,
+        &[],
+    )
+}
+
+/// [`compile_with_manifest`], reusing the parsed specs of a `previous`
+/// result (e.g. in a watch loop).
+///
+/// A spec is re-parsed when it is listed in `changed`, when its content
+/// hash differs from the one `previous` parsed, or when it is new. Everything
+/// after parsing (validation, plugin resolution, archive writing) runs as in
+/// a full compile, so the artifact is byte-identical to one.
+///
+/// `changed` must list every modified file, including files a spec pulls in
+/// through a `$ref`: a changed path that is not one of `spec_paths` makes
+/// every spec re-parse, since any of them may reference it. A `previous`
+/// result compiled with different `only_operations` is not reused.
+pub fn compile_with_manifest_incremental(
+    spec_paths: &[&Path],
+    project_manifest: &ProjectManifest,
+    manifest_base_path: &Path,
+    output: &Path,
+    options: &CompileOptions,
+    previous: Option<&CompileResult>,
+    changed: &[&Path],
+) -> Result<CompileResult, CompileError> {
+    let previous = previous.and_then(|result| result.parse_cache.as_ref());
+    let (specs, reparsed_specs) = parse_specs_reusing(spec_paths, options, previous, changed)?;
+    let project_manifest = project_manifest.expand_plugin_globs(manifest_base_path)?;
 
     // Extract just the ApiSpec for validation
     let api_specs: Vec<ApiSpec> = specs.iter().map(|(spec, _, _)| spec.clone()).collect();
@@ -311,9 +583,71 @@ pub fn compile_with_manifest(
         })
         .collect();
 
+    // Plugins must fill the slot their plugin.toml type declares (E1024)
+    validate_plugin_roles(&api_specs, &plugin_bundles)?;
+
+    // URL plugins downloaded without a checksum pin (E1044 - warning)
+    let plugin_warnings =
+        project_manifest.unverified_plugin_warnings(plugin_bundles.iter().map(|p| p.name.as_str()));
+
     // Bundles were resolved from plugin.toml, so their declared capabilities are
     // authoritative and the artifact is eligible for load-time enforcement.
-    compile_inner(&specs, &plugin_bundles, output, options, true)
+    let mut result = compile_inner(
+        &specs,
+        &plugin_bundles,
+        plugin_warnings,
+        || File::create(output),
+        options,
+        true,
+    )?;
+    result.parse_cache = Some(ParseCache {
+        only_operations: options.only_operations.clone(),
+        specs: spec_paths
+            .iter()
+            .map(|path| path.to_path_buf())
+            .zip(specs)
+            .collect(),
+    });
+    result.reparsed_specs = reparsed_specs;
+    Ok(result)
+}
+
+/// Check that every plugin is used in the slot its declared type fills (E1024):
+/// `x-barbacane-dispatch` takes a dispatcher and the middleware chain only
+/// middlewares, so an operation never ends up with a second dispatcher.
+/// Plugins whose type is unknown (no plugin.toml) are not checked.
+fn validate_plugin_roles(specs: &[ApiSpec], plugins: &[PluginBundle]) -> Result<(), CompileError> {
+    let roles: HashMap<&str, &str> = plugins
+        .iter()
+        .filter(|p| p.plugin_type == "dispatcher" || p.plugin_type == "middleware")
+        .map(|p| (p.name.as_str(), p.plugin_type.as_str()))
+        .collect();
+    let check = |plugin: &str, expected: &str, operation: &str| {
+        let name = crate::manifest::normalize_plugin_name(plugin);
+        match roles.get(name.as_str()) {
+            Some(&found) if found != expected => Err(CompileError::InvalidPluginRole {
+                operation: operation.to_string(),
+                plugin: plugin.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    };
+
+    for spec in specs {
+        let spec_file = spec.filename.as_deref().unwrap_or("unknown");
+        for op in &spec.operations {
+            let operation = format!("{} {} in '{}'", op.method, op.path, spec_file);
+            if let Some(dispatch) = &op.dispatch {
+                check(&dispatch.name, "dispatcher", &operation)?;
+            }
+            for mw in resolve_middlewares(&spec.global_middlewares, &op.middlewares) {
+                check(&mw.name, "middleware", &operation)?;
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Load a manifest from a .bca artifact.
@@ -364,6 +698,175 @@ pub fn load_routes(artifact_path: &Path) -> Result<CompiledRoutes, CompileError>
     )))
 }
 
+/// Stream compiled operations from a .bca artifact one at a time.
+///
+/// Unlike [`load_routes`], which materializes every operation, this decodes
+/// `routes.json` incrementally and hands each operation to `on_operation` as
+/// soon as it is deserialized, so memory stays flat for tools that just scan a
+/// large artifact. Locating the entry costs one extra pass over the gzip
+/// stream.
+pub fn stream_routes<F>(artifact_path: &Path, on_operation: F) -> Result<(), CompileError>
+where
+    F: FnMut(CompiledOperation),
+{
+    let (offset, size) = {
+        let file = File::open(artifact_path)?;
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        let mut found = None;
+        for entry in archive.entries()? {
+            let entry = entry?;
+            if entry.path()?.to_str() == Some("routes.json") {
+                found = Some((entry.raw_file_position(), entry.size()));
+                break;
+            }
+        }
+        found.ok_or_else(|| {
+            CompileError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "routes.json not found in artifact",
+            ))
+        })?
+    };
+
+    let mut decoder = flate2::read::GzDecoder::new(File::open(artifact_path)?);
+    std::io::copy(
+        &mut Read::by_ref(&mut decoder).take(offset),
+        &mut std::io::sink(),
+    )?;
+    stream_routes_from_reader(decoder.take(size), on_operation)
+}
+
+/// Stream the operations of a `routes.json` document read from `reader`,
+/// calling `on_operation` for each element of `operations` in order. Other
+/// top-level members are skipped without being materialized.
+pub fn stream_routes_from_reader<R, F>(reader: R, on_operation: F) -> Result<(), CompileError>
+where
+    R: Read,
+    F: FnMut(CompiledOperation),
+{
+    use serde::Deserializer as _;
+
+    let mut de = serde_json::Deserializer::from_reader(std::io::BufReader::new(reader));
+    de.deserialize_map(RoutesVisitor(on_operation))?;
+    de.end()?;
+    Ok(())
+}
+
+/// Visits the top-level `routes.json` object, streaming `operations`.
+struct RoutesVisitor<F>(F);
+
+impl<'de, F> serde::de::Visitor<'de> for RoutesVisitor<F>
+where
+    F: FnMut(CompiledOperation),
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a routes.json object")
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<(), A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut seen_operations = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "operations" {
+                map.next_value_seed(OperationsSeed(&mut self.0))?;
+                seen_operations = true;
+            } else {
+                map.next_value::<serde::de::IgnoredAny>()?;
+            }
+        }
+        if !seen_operations {
+            return Err(serde::de::Error::missing_field("operations"));
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes the `operations` array, handing each element to the callback
+/// instead of collecting it.
+struct OperationsSeed<'a, F>(&'a mut F);
+
+impl<'de, F> serde::de::DeserializeSeed<'de> for OperationsSeed<'_, F>
+where
+    F: FnMut(CompiledOperation),
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F> serde::de::Visitor<'de> for OperationsSeed<'_, F>
+where
+    F: FnMut(CompiledOperation),
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an array of compiled operations")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        while let Some(op) = seq.next_element::<CompiledOperation>()? {
+            (self.0)(op);
+        }
+        Ok(())
+    }
+}
+
+/// Compare the routes of two compiled route sets.
+///
+/// A route counts as `config_changed` when its dispatcher/middleware
+/// fingerprint differs. Routes whose fingerprint is missing on either side
+/// (artifacts predating fingerprints) are always reported as changed.
+pub fn diff_routes(old: &CompiledRoutes, new: &CompiledRoutes) -> RoutesDiff {
+    let key = |op: &CompiledOperation| format!("{} {}", op.method, op.path);
+    let old_ops: BTreeMap<String, &CompiledOperation> =
+        old.operations.iter().map(|op| (key(op), op)).collect();
+    let new_ops: BTreeMap<String, &CompiledOperation> =
+        new.operations.iter().map(|op| (key(op), op)).collect();
+
+    let mut diff = RoutesDiff::default();
+    for (route, new_op) in &new_ops {
+        match old_ops.get(route) {
+            None => diff.added.push(route.clone()),
+            Some(old_op) => {
+                if old_op.config_fingerprint.is_empty()
+                    || old_op.config_fingerprint != new_op.config_fingerprint
+                {
+                    diff.config_changed.push(route.clone());
+                }
+            }
+        }
+    }
+    diff.removed = old_ops
+        .keys()
+        .filter(|route| !new_ops.contains_key(*route))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// Compare the routes of two .bca artifacts (see [`diff_routes`]).
+pub fn diff_artifacts(
+    old_artifact: &Path,
+    new_artifact: &Path,
+) -> Result<RoutesDiff, CompileError> {
+    let old = load_routes(old_artifact)?;
+    let new = load_routes(new_artifact)?;
+    Ok(diff_routes(&old, &new))
+}
+
 /// Load all source specs from a .bca artifact.
 /// Returns a map of filename -> content.
 pub fn load_specs(artifact_path: &Path) -> Result<HashMap<String, String>, CompileError> {
@@ -448,15 +951,124 @@ pub struct PluginBundle {
 }
 
 /// Parse spec files into (ApiSpec, content, sha256) tuples.
-fn parse_specs(spec_paths: &[&Path]) -> Result<Vec<(ApiSpec, String, String)>, CompileError> {
+/// Parse in-memory spec buffers (e.g. fetched over the network or generated).
+///
+/// Each entry is `(filename, bytes, format)`: the filename labels the spec in
+/// diagnostics, and the buffer must be a spec of the given format.
+pub fn load_specs_from_bytes(
+    specs: &[(String, Vec<u8>, SpecFormat)],
+) -> Result<Vec<ApiSpec>, CompileError> {
+    specs
+        .iter()
+        .map(|(filename, bytes, format)| {
+            parse_spec_bytes(Some(filename), bytes, Some(*format), None, None).map(|(spec, _)| spec)
+        })
+        .collect()
+}
+
+/// Parse one spec buffer, returning the spec and its UTF-8 content. With
+/// `expected` set, a buffer of the other format is rejected. With `base_dir`
+/// set, `$ref`s to other files resolve relative to it. With `only` set, other
+/// operations are dropped.
+fn parse_spec_bytes(
+    filename: Option<&str>,
+    bytes: &[u8],
+    expected: Option<SpecFormat>,
+    base_dir: Option<&Path>,
+    only: Option<&[String]>,
+) -> Result<(ApiSpec, String), CompileError> {
+    let label = filename.unwrap_or("unknown");
+    let content = String::from_utf8(bytes.to_vec())
+        .map_err(|e| ParseError::ParseError(format!("'{}' is not valid UTF-8: {}", label, e)))?;
+
+    let mut spec = parse_spec_in(&content, base_dir, only)?;
+    if let Some(expected) = expected {
+        if spec.format != expected {
+            return Err(ParseError::SchemaError(format!(
+                "'{}' is {:?}, expected {:?}",
+                label, spec.format, expected
+            ))
+            .into());
+        }
+    }
+    spec.filename = filename.map(str::to_string);
+    Ok((spec, content))
+}
+
+fn parse_specs(
+    spec_paths: &[&Path],
+    options: &CompileOptions,
+) -> Result<Vec<(ApiSpec, String, String)>, CompileError> {
+    parse_specs_reusing(spec_paths, options, None, &[]).map(|(specs, _)| specs)
+}
+
+/// [`parse_specs`], taking unchanged specs from `previous` instead of parsing
+/// them again. Also returns the paths that were actually parsed.
+fn parse_specs_reusing(
+    spec_paths: &[&Path],
+    options: &CompileOptions,
+    previous: Option<&ParseCache>,
+    changed: &[&Path],
+) -> Result<(Vec<ParsedSpec>, Vec<PathBuf>), CompileError> {
+    let only = options.only_operations.as_deref();
+    // A changed file outside the spec list (e.g. a `$ref` target) may affect
+    // any spec, so nothing is reused.
+    let previous = previous.filter(|cache| {
+        cache.only_operations == options.only_operations
+            && changed.iter().all(|path| spec_paths.contains(path))
+    });
     let mut specs = Vec::new();
+    let mut reparsed = Vec::new();
     for path in spec_paths {
-        let content = std::fs::read_to_string(path)?;
-        let sha256 = compute_sha256(content.as_bytes());
-        let spec = parse_spec_file(path)?;
+        let bytes = std::fs::read(path)?;
+        let sha256 = compute_sha256(&bytes);
+        let cached = previous
+            .filter(|_| !changed.contains(path))
+            .and_then(|cache| cache.specs.get(*path))
+            .filter(|(_, _, cached_sha)| *cached_sha == sha256);
+        if let Some(entry) = cached {
+            specs.push(entry.clone());
+            continue;
+        }
+        let filename = path.file_name().and_then(|s| s.to_str());
+        let (spec, content) = parse_spec_bytes(filename, &bytes, None, path.parent(), only)?;
         specs.push((spec, content, sha256));
+        reparsed.push(path.to_path_buf());
     }
-    Ok(specs)
+
+    // Every requested operation must have survived the filter (E1057).
+    if let Some(only) = only {
+        let found: HashSet<&str> = specs
+            .iter()
+            .flat_map(|(spec, _, _)| &spec.operations)
+            .filter_map(|op| op.operation_id.as_deref())
+            .collect();
+        let unknown: Vec<&str> = only
+            .iter()
+            .map(String::as_str)
+            .filter(|id| !found.contains(id))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(CompileError::UnknownOperationId(unknown.join(", ")));
+        }
+    }
+    Ok((specs, reparsed))
+}
+
+/// Normalized names of the dispatchers and middlewares the specs' operations use.
+fn used_plugin_names(specs: &[(ApiSpec, String, String)]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for (spec, _, _) in specs {
+        for op in &spec.operations {
+            if let Some(dispatch) = &op.dispatch {
+                names.insert(crate::manifest::normalize_plugin_name(&dispatch.name));
+            }
+            for mw in resolve_middlewares(&spec.global_middlewares, &op.middlewares) {
+                names.insert(crate::manifest::normalize_plugin_name(&mw.name));
+            }
+        }
+    }
+    names
 }
 
 /// Resolve middleware chain for an operation:
@@ -484,16 +1096,31 @@ fn resolve_middlewares(
 }
 
 /// Shared compilation core: validates specs, builds operations, and writes the .bca archive.
-fn compile_inner(
+///
+/// `open_output` is only called once validation has passed, so a failed
+/// compilation never creates or touches the output.
+fn compile_inner<W: Write>(
     specs: &[(ApiSpec, String, String)],
     plugins: &[PluginBundle],
-    output: &Path,
+    plugin_warnings: Vec<CompileWarning>,
+    open_output: impl FnOnce() -> std::io::Result<W>,
     options: &CompileOptions,
     capabilities_authoritative: bool,
 ) -> Result<CompileResult, CompileError> {
-    let mut warnings: Vec<CompileWarning> = Vec::new();
+    // Seeded with warnings raised while resolving plugins, so they count
+    // towards `deny_warnings` like any other.
+    let mut warnings: Vec<CompileWarning> = plugin_warnings;
     let mut operations: Vec<CompiledOperation> = Vec::new();
 
+    // A partial build bundles only the plugins its operations use.
+    let plugins: Vec<&PluginBundle> = match options.only_operations {
+        Some(_) => {
+            let used = used_plugin_names(specs);
+            plugins.iter().filter(|p| used.contains(&p.name)).collect()
+        }
+        None => plugins.iter().collect(),
+    };
+
     // Per-plugin set of secret (writeOnly) config fields, from each plugin's
     // config-schema.json, used to warn on plaintext secrets baked into configs.
     let plugin_secret_fields: HashMap<&str, std::collections::BTreeSet<String>> = plugins
@@ -504,7 +1131,18 @@ fn compile_inner(
 
     let mut seen_routes: HashMap<(String, String), String> = HashMap::new();
     let mut seen_structural: HashMap<(String, String), (String, String)> = HashMap::new();
-    let mut seen_operation_ids: HashMap<String, String> = HashMap::new();
+    // (method, path, site) of every routed operation, for the overlap pass (E1017).
+    let mut route_sites: Vec<(String, String, WarningSite)> = Vec::new();
+
+    // Every operationId must be unique across all specs (E1055)
+    check_duplicate_operation_ids(specs)?;
+    // Synthesized ids must not collide with declared ones, wherever declared.
+    let declared_ids: HashSet<&str> = specs
+        .iter()
+        .flat_map(|(spec, _, _)| &spec.operations)
+        .filter_map(|op| op.operation_id.as_deref())
+        .collect();
+    let mut synthesized_ids: HashSet<String> = HashSet::new();
 
     // Extract root-level MCP config from first spec that has it
     let root_mcp_config = extract_root_mcp_config(specs);
@@ -526,42 +1164,59 @@ fn compile_inner(
         // Check for unknown extensions at spec level (E1015 - warning)
         for key in spec.extensions.keys() {
             if key.starts_with("x-barbacane-") && !KNOWN_EXTENSIONS.contains(&key.as_str()) {
-                warnings.push(CompileWarning {
-                    code: "E1015".to_string(),
-                    message: format!("unknown extension: {}", key),
-                    location: Some(spec_file.to_string()),
-                });
+                let site = WarningSite {
+                    location: spec_file.to_string(),
+                    spec: spec_file.to_string(),
+                    pointer: Some(format!("/{}", escape_pointer_token(key))),
+                };
+                warnings.push(
+                    site.warning(DiagnosticCode::E1015, format!("unknown extension: {}", key)),
+                );
             }
         }
 
         for op in &spec.operations {
             let location = format!("{} {} in '{}'", op.method, op.path, spec_file);
+            let site = WarningSite {
+                location: location.clone(),
+                spec: spec_file.to_string(),
+                pointer: operation_pointer(spec, op),
+            };
 
             // Check for unknown extensions at operation level (E1015 - warning)
             for key in op.extensions.keys() {
                 if key.starts_with("x-barbacane-") && !KNOWN_EXTENSIONS.contains(&key.as_str()) {
-                    warnings.push(CompileWarning {
-                        code: "E1015".to_string(),
-                        message: format!("unknown extension: {}", key),
-                        location: Some(location.clone()),
-                    });
+                    warnings.push(
+                        site.warning(DiagnosticCode::E1015, format!("unknown extension: {}", key)),
+                    );
                 }
             }
 
-            // Validate path template syntax (E1054)
-            validate_path_template(&op.path, &location)?;
-
-            // Check for duplicate operationId (E1055)
-            if let Some(ref op_id) = op.operation_id {
-                if let Some(first_location) = seen_operation_ids.get(op_id) {
-                    return Err(CompileError::DuplicateOperationId(
-                        op_id.clone(),
-                        format!("first at {}, duplicate at {}", first_location, location),
-                    ));
+            // Inline plugin slots shadowed by the dedicated extensions (E1018 - warning)
+            if let Some(inline) = op
+                .extensions
+                .get("x-barbacane-plugins")
+                .and_then(|v| v.as_object())
+            {
+                for (slot, extension) in [
+                    ("dispatch", "x-barbacane-dispatch"),
+                    ("middlewares", "x-barbacane-middlewares"),
+                ] {
+                    if inline.contains_key(slot) && op.extensions.contains_key(extension) {
+                        warnings.push(site.warning(
+                            DiagnosticCode::E1018,
+                            format!(
+                                "x-barbacane-plugins.{} is ignored: {} is also set",
+                                slot, extension
+                            ),
+                        ));
+                    }
                 }
-                seen_operation_ids.insert(op_id.clone(), location.clone());
             }
 
+            // Validate path template syntax (E1054)
+            validate_path_template(&op.path, &location)?;
+
             // Check for routing conflicts (E1010)
             let key = (op.path.clone(), op.method.clone());
             if let Some(other_spec) = seen_routes.get(&key) {
@@ -584,43 +1239,21 @@ fn compile_inner(
                 }
             }
             seen_structural.insert(structural_key, (op.path.clone(), spec_file.to_string()));
+            route_sites.push((op.method.clone(), op.path.clone(), site.clone()));
 
             // Check for missing dispatcher (E1020)
-            let dispatch = op.dispatch.clone().ok_or_else(|| {
+            let mut dispatch = op.dispatch.clone().ok_or_else(|| {
                 CompileError::MissingDispatch(format!(
                     "{} {} in '{}'",
                     op.method, op.path, spec_file
                 ))
             })?;
-
-            // Check for plaintext HTTP upstream URLs (E1031)
-            if !options.allow_plaintext {
-                if let Some(url) = extract_upstream_url(&dispatch.config) {
-                    if url.starts_with("http://") {
-                        return Err(CompileError::PlaintextUpstream(format!(
-                            "{} {} in '{}' - upstream URL: {}",
-                            op.method, op.path, spec_file, url
-                        )));
-                    }
-                }
-            }
-
-            let middlewares = resolve_middlewares(&spec.global_middlewares, &op.middlewares);
-
-            // Validate middleware names (E1011)
-            for (idx, mw) in middlewares.iter().enumerate() {
-                if mw.name.is_empty() {
-                    return Err(CompileError::MissingMiddlewareName(format!(
-                        "middleware #{} in {}",
-                        idx + 1,
-                        location
-                    )));
-                }
-            }
+            let mut middlewares = resolve_middlewares(&spec.global_middlewares, &op.middlewares);
 
             // Warn on plaintext secrets in config (E1070): a field the plugin's
             // schema marks `writeOnly` but that holds a literal gets baked into
-            // the artifact. Recommend env:// / file:// references.
+            // the artifact. Recommend env:// / file:// references. Runs before
+            // `${env:VAR}` interpolation so placeholders are not flagged.
             //
             // `plugin_secret_fields` is keyed by the normalized plugin name, but
             // a spec may reference a plugin with a version suffix
@@ -636,14 +1269,48 @@ fn compile_inner(
                     &dispatch.config,
                     &dispatch.name,
                     fields,
-                    &location,
+                    &site,
                     &mut warnings,
                 );
             }
             for mw in &middlewares {
                 let mw_key = crate::manifest::normalize_plugin_name(&mw.name);
                 if let Some(fields) = plugin_secret_fields.get(mw_key.as_str()) {
-                    scan_plaintext_secrets(&mw.config, &mw.name, fields, &location, &mut warnings);
+                    scan_plaintext_secrets(&mw.config, &mw.name, fields, &site, &mut warnings);
+                }
+            }
+
+            // Substitute `${env:VAR}` placeholders in plugin configs (E1025)
+            interpolate_env(
+                &mut dispatch.config,
+                &options.env,
+                &dispatch.name,
+                &location,
+            )?;
+            for mw in &mut middlewares {
+                interpolate_env(&mut mw.config, &options.env, &mw.name, &location)?;
+            }
+
+            // Check for plaintext HTTP upstream URLs (E1031)
+            if !options.allow_plaintext {
+                if let Some(url) = extract_upstream_url(&dispatch.config) {
+                    if url.starts_with("http://") {
+                        return Err(CompileError::PlaintextUpstream(format!(
+                            "{} {} in '{}' - upstream URL: {}",
+                            op.method, op.path, spec_file, url
+                        )));
+                    }
+                }
+            }
+
+            // Validate middleware names (E1011)
+            for (idx, mw) in middlewares.iter().enumerate() {
+                if mw.name.is_empty() {
+                    return Err(CompileError::MissingMiddlewareName(format!(
+                        "middleware #{} in {}",
+                        idx + 1,
+                        location
+                    )));
                 }
             }
 
@@ -676,6 +1343,17 @@ fn compile_inner(
                 }
             }
 
+            // Deprecated operations that are still routed (E1016 - warning)
+            if op.deprecated {
+                warnings.push(site.warning(
+                    DiagnosticCode::E1016,
+                    format!(
+                        "deprecated operation {} {} is still routed",
+                        op.method, op.path
+                    ),
+                ));
+            }
+
             // Resolve MCP enabled state for this operation
             let (mcp_enabled, mcp_description) =
                 resolve_mcp_config(&root_mcp_config, op.extensions.get("x-barbacane-mcp"));
@@ -683,29 +1361,54 @@ fn compile_inner(
             // MCP warnings
             if mcp_enabled == Some(true) {
                 if op.operation_id.is_none() {
-                    warnings.push(CompileWarning {
-                        code: "E1060".to_string(),
-                        message: "operation without operationId cannot be exposed as MCP tool"
-                            .to_string(),
-                        location: Some(location.clone()),
-                    });
+                    warnings.push(site.warning(
+                        DiagnosticCode::E1060,
+                        "operation without operationId cannot be exposed as MCP tool".to_string(),
+                    ));
                 }
                 if op.summary.is_none() && op.description.is_none() {
-                    warnings.push(CompileWarning {
-                        code: "E1061".to_string(),
-                        message:
+                    warnings.push(
+                        site.warning(
+                            DiagnosticCode::E1061,
                             "MCP-enabled operation has no summary or description for tool metadata"
                                 .to_string(),
-                        location: Some(location.clone()),
-                    });
+                        ),
+                    );
                 }
             }
 
+            let accepted_content_types =
+                resolve_accept_list(op.extensions.get("x-barbacane-accept"), &location)?;
+
+            let config_fingerprint = compute_config_fingerprint(&dispatch, &middlewares)?;
+
+            // Operations without an operationId get one derived from method
+            // and path, suffixed on collision (E1019 - warning)
+            let operation_id = match &op.operation_id {
+                Some(id) => id.clone(),
+                None => {
+                    let base = synthesize_operation_id(&op.method, &op.path);
+                    let mut id = base.clone();
+                    let mut suffix = 2;
+                    while declared_ids.contains(id.as_str()) || synthesized_ids.contains(&id) {
+                        id = format!("{}_{}", base, suffix);
+                        suffix += 1;
+                    }
+                    synthesized_ids.insert(id.clone());
+                    warnings.push(site.warning(
+                        DiagnosticCode::E1019,
+                        format!("operation has no operationId; using synthesized '{}'", id),
+                    ));
+                    id
+                }
+            };
+
             operations.push(CompiledOperation {
                 index: operations.len(),
                 path: op.path.clone(),
                 method: op.method.clone(),
-                operation_id: op.operation_id.clone(),
+                operation_id: Some(operation_id),
+                operation_id_synthesized: op.operation_id.is_none(),
                 summary: op.summary.clone(),
                 description: op.description.clone(),
                 parameters: op.parameters.clone(),
@@ -714,24 +1417,69 @@ fn compile_inner(
                 middlewares,
                 deprecated: op.deprecated,
                 sunset: op.sunset.clone(),
+                timeout_ms: op.timeout_ms,
                 messages: op.messages.clone(),
                 bindings: op.bindings.clone(),
                 responses: op.responses.clone(),
                 mcp_enabled,
                 mcp_description,
+                accepted_content_types,
+                config_fingerprint,
             });
         }
     }
 
-    // Sort operations by (path, method) for deterministic output, then reassign indices
-    operations.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
-    for (i, op) in operations.iter_mut().enumerate() {
-        op.index = i;
+    // Overlapping path templates the router resolves by precedence (E1017 - warning)
+    warnings.extend(route_overlap_warnings(&route_sites));
+
+    // Bundled plugins no operation's dispatcher or middleware chain uses (E1041 - warning)
+    let referenced: HashSet<String> = operations
+        .iter()
+        .flat_map(|op| {
+            op.middlewares
+                .iter()
+                .map(|mw| &mw.name)
+                .chain(std::iter::once(&op.dispatch.name))
+        })
+        .map(|name| crate::manifest::normalize_plugin_name(name))
+        .collect();
+    for plugin in &plugins {
+        if !referenced.contains(&plugin.name) {
+            warnings.push(CompileWarning {
+                code: DiagnosticCode::E1041,
+                message: format!(
+                    "plugin '{}' is bundled but no operation uses it",
+                    plugin.name
+                ),
+                location: Some(format!("plugins/{}.wasm", plugin.name)),
+                spec: None,
+                pointer: None,
+            });
+        }
     }
 
-    // Build routes.json
-    let routes = CompiledRoutes { operations };
-    let routes_json = serde_json::to_string_pretty(&routes)?;
+    // Sort warnings for deterministic output
+    warnings.sort_by(|a, b| {
+        (&a.location, &a.code, &a.message).cmp(&(&b.location, &b.code, &b.message))
+    });
+
+    // Every warning is known by now; fail before writing the artifact.
+    if options.deny_warnings && !warnings.is_empty() {
+        return Err(CompileError::WarningsDenied {
+            count: warnings.len(),
+            warnings,
+        });
+    }
+
+    // Sort operations by (path, method) for deterministic output, then reassign indices
+    operations.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+    for (i, op) in operations.iter_mut().enumerate() {
+        op.index = i;
+    }
+
+    // Build routes.json
+    let routes = CompiledRoutes { operations };
+    let routes_json = serde_json::to_string_pretty(&routes)?;
     let routes_sha256 = compute_sha256(routes_json.as_bytes());
 
     // Build plugin metadata
@@ -742,7 +1490,7 @@ fn compile_inner(
         format!("sha256:{}", routes_sha256),
     );
 
-    for plugin in plugins {
+    for plugin in &plugins {
         let wasm_path = format!("plugins/{}.wasm", plugin.name);
         let sha256 = compute_sha256(&plugin.wasm_bytes);
 
@@ -815,7 +1563,7 @@ fn compile_inner(
 
     let mut manifest = Manifest {
         barbacane_artifact_version: ARTIFACT_VERSION,
-        compiled_at: now_utc_iso8601(),
+        compiled_at: build_timestamp(),
         compiler_version: COMPILER_VERSION.to_string(),
         source_specs,
         routes_count: routes.operations.len(),
@@ -838,8 +1586,8 @@ fn compile_inner(
 
     let manifest_json = serde_json::to_string_pretty(&manifest)?;
 
-    // Create the .bca archive (tar.gz)
-    let file = File::create(output)?;
+    // Create the .bca archive (tar.gz), hashing the bytes as they are written
+    let file = HashingWriter::new(open_output()?);
     let encoder = GzEncoder::new(file, Compression::default());
     let mut archive = Builder::new(encoder);
 
@@ -847,34 +1595,45 @@ fn compile_inner(
     add_file_to_tar(&mut archive, "manifest.json", manifest_json.as_bytes())?;
     add_file_to_tar(&mut archive, "routes.json", routes_json.as_bytes())?;
 
-    // Add source specs under specs/ directory
-    for (spec, content, _) in specs {
-        let filename = spec
-            .filename
-            .as_deref()
-            .and_then(|p| Path::new(p).file_name())
-            .and_then(|n| n.to_str())
-            .unwrap_or("spec.yaml");
-        let archive_path = format!("specs/{}", filename);
+    // Add source specs under specs/ directory, in path order so the archive
+    // does not depend on the order specs were passed in
+    let mut spec_entries: Vec<(String, &str)> = specs
+        .iter()
+        .map(|(spec, content, _)| {
+            let filename = spec
+                .filename
+                .as_deref()
+                .and_then(|p| Path::new(p).file_name())
+                .and_then(|n| n.to_str())
+                .unwrap_or("spec.yaml");
+            (format!("specs/{}", filename), content.as_str())
+        })
+        .collect();
+    spec_entries.sort_by(|a, b| a.0.cmp(&b.0));
+    for (archive_path, content) in spec_entries {
         add_file_to_tar(&mut archive, &archive_path, content.as_bytes())?;
     }
 
-    // Add plugins
-    for plugin in plugins {
+    // Add plugins, sorted by name like the manifest's plugin list
+    let mut sorted_plugins = plugins.clone();
+    sorted_plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    for plugin in sorted_plugins {
         let wasm_path = format!("plugins/{}.wasm", plugin.name);
         add_file_to_tar(&mut archive, &wasm_path, &plugin.wasm_bytes)?;
     }
 
     // Finish the archive
     let encoder = archive.into_inner()?;
-    encoder.finish()?;
-
-    // Sort warnings for deterministic output
-    warnings.sort_by(|a, b| {
-        (&a.location, &a.code, &a.message).cmp(&(&b.location, &b.code, &b.message))
-    });
-
-    Ok(CompileResult { manifest, warnings })
+    let file = encoder.finish()?;
+    let archive_sha256 = file.finish()?;
+
+    Ok(CompileResult {
+        manifest,
+        warnings,
+        archive_sha256,
+        parse_cache: None,
+        reparsed_specs: Vec::new(),
+    })
 }
 
 /// Compute SHA-256 hash of bytes.
@@ -882,6 +1641,17 @@ fn compute_sha256(content: &[u8]) -> String {
     hex::encode(Sha256::new().chain_update(content).finalize())
 }
 
+/// Fingerprint an operation's resolved plugin chain: SHA-256 over the JSON of
+/// the dispatcher and middlewares (names and configs, in chain order). Config
+/// objects serialize with sorted keys, so key order in the spec doesn't matter.
+fn compute_config_fingerprint(
+    dispatch: &DispatchConfig,
+    middlewares: &[MiddlewareConfig],
+) -> Result<String, CompileError> {
+    let chain = serde_json::to_vec(&(dispatch, middlewares))?;
+    Ok(compute_sha256(&chain))
+}
+
 /// Compute a combined artifact hash from all individual input checksums.
 ///
 /// Produces a single SHA-256 that represents the entire artifact content by
@@ -1083,9 +1853,54 @@ fn add_file_to_tar<W: Write>(
     archive.append_data(&mut header, name, content)
 }
 
-/// Get current UTC timestamp in ISO 8601 format.
-fn now_utc_iso8601() -> String {
-    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+/// Build timestamp for the manifest's `compiled_at`, in ISO 8601 format.
+///
+/// The wall clock would make every build byte-different, so this is taken
+/// from `SOURCE_DATE_EPOCH` (the reproducible-builds convention) when set,
+/// and is otherwise the Unix epoch. Artifacts are identified by their
+/// content hashes, not by this field.
+fn build_timestamp() -> String {
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(0);
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+/// Writer that computes the SHA-256 of everything written through it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Flush the inner writer and return the hex digest.
+    fn finish(mut self) -> std::io::Result<String> {
+        self.inner.flush()?;
+        Ok(hex::encode(self.hasher.finalize()))
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
 }
 
 /// Collect the names of config fields a plugin's `config-schema.json` marks as
@@ -1145,16 +1960,17 @@ fn scan_plaintext_secrets(
     config: &serde_json::Value,
     plugin: &str,
     secret_fields: &std::collections::BTreeSet<String>,
-    location: &str,
+    site: &WarningSite,
     warnings: &mut Vec<CompileWarning>,
 ) {
     if secret_fields.is_empty() {
         return;
     }
 
-    /// A value that is resolved at runtime rather than stored in the artifact.
+    /// A value that does not come from the spec itself: a reference resolved
+    /// at runtime, or a `${env:VAR}` placeholder filled in at compile time.
     fn is_secret_ref(value: &str) -> bool {
-        value.starts_with("env://") || value.starts_with("file://")
+        value.starts_with("env://") || value.starts_with("file://") || value.contains("${env:")
     }
 
     match config {
@@ -1162,27 +1978,75 @@ fn scan_plaintext_secrets(
             for (key, value) in map {
                 if let serde_json::Value::String(s) = value {
                     if secret_fields.contains(key) && !s.is_empty() && !is_secret_ref(s) {
-                        warnings.push(CompileWarning {
-                            code: "E1070".to_string(),
-                            message: format!(
+                        warnings.push(site.warning(
+                            DiagnosticCode::E1070,
+                            format!(
                                 "plugin '{plugin}' config field '{key}' is a secret but is set to \
                                  a plaintext literal; use an env:// or file:// reference so it is \
                                  resolved at runtime instead of baked into the artifact"
                             ),
-                            location: Some(location.to_string()),
-                        });
+                        ));
                     }
                 }
-                scan_plaintext_secrets(value, plugin, secret_fields, location, warnings);
+                scan_plaintext_secrets(value, plugin, secret_fields, site, warnings);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                scan_plaintext_secrets(item, plugin, secret_fields, site, warnings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace `${env:VAR}` placeholders in every string of a plugin config with
+/// the value from `env`. Keys and non-string values are left alone; a
+/// placeholder without a closing `}` is kept literally.
+fn interpolate_env(
+    config: &mut serde_json::Value,
+    env: &BTreeMap<String, String>,
+    plugin: &str,
+    operation: &str,
+) -> Result<(), CompileError> {
+    match config {
+        serde_json::Value::String(s) => {
+            if !s.contains("${env:") {
+                return Ok(());
+            }
+            let mut out = String::with_capacity(s.len());
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("${env:") {
+                let after = &rest[start + "${env:".len()..];
+                let Some(end) = after.find('}') else {
+                    break;
+                };
+                let var = &after[..end];
+                let value = env.get(var).ok_or_else(|| CompileError::MissingEnvVar {
+                    var: var.to_string(),
+                    plugin: plugin.to_string(),
+                    operation: operation.to_string(),
+                })?;
+                out.push_str(&rest[..start]);
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            out.push_str(rest);
+            *s = out;
+        }
+        serde_json::Value::Object(map) => {
+            for value in map.values_mut() {
+                interpolate_env(value, env, plugin, operation)?;
             }
         }
         serde_json::Value::Array(items) => {
             for item in items {
-                scan_plaintext_secrets(item, plugin, secret_fields, location, warnings);
+                interpolate_env(item, env, plugin, operation)?;
             }
         }
         _ => {}
     }
+    Ok(())
 }
 
 /// Extract upstream URL from dispatch config, if present.
@@ -1207,6 +2071,56 @@ fn extract_upstream_url(config: &serde_json::Value) -> Option<String> {
     None
 }
 
+/// Reject an `operationId` declared by more than one operation (E1055),
+/// naming every operation that declares it. Operations without an id are
+/// not checked.
+fn check_duplicate_operation_ids(specs: &[(ApiSpec, String, String)]) -> Result<(), CompileError> {
+    let mut locations: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut order: Vec<&str> = Vec::new();
+    for (spec, _, _) in specs {
+        let spec_file = spec.filename.as_deref().unwrap_or("unknown");
+        for op in &spec.operations {
+            let Some(id) = op.operation_id.as_deref() else {
+                continue;
+            };
+            let seen = locations.entry(id).or_default();
+            if seen.is_empty() {
+                order.push(id);
+            }
+            seen.push(format!("{} {} in '{}'", op.method, op.path, spec_file));
+        }
+    }
+
+    // Report the first duplicated id in declaration order.
+    match order.into_iter().find(|id| locations[id].len() > 1) {
+        Some(id) => Err(CompileError::DuplicateOperationId {
+            id: id.to_string(),
+            locations: locations.remove(id).unwrap_or_default(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Derive an operationId from method and path: lowercase, with every run of
+/// characters outside `[a-z0-9]` (slashes, braces, dashes, ...) collapsed to
+/// one `_`. `GET /users/{id}` becomes `get_users_id`.
+fn synthesize_operation_id(method: &str, path: &str) -> String {
+    let mut id = String::with_capacity(method.len() + path.len());
+    for c in method
+        .chars()
+        .chain(std::iter::once('/'))
+        .chain(path.chars())
+    {
+        let c = c.to_ascii_lowercase();
+        if c.is_ascii_alphanumeric() {
+            id.push(c);
+        } else if !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    id.trim_end_matches('_').to_string()
+}
+
 /// Validate path template syntax (E1054).
 ///
 /// Checks for:
@@ -1325,6 +2239,101 @@ fn validate_path_template(path: &str, location: &str) -> Result<(), CompileError
     Ok(())
 }
 
+/// A path-template segment as the router matches it (E1017).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouteSegment<'a> {
+    Static(&'a str),
+    Param,
+    Wildcard,
+}
+
+impl RouteSegment<'_> {
+    /// Match precedence in the routing trie: static, then parameter, then wildcard.
+    fn rank(self) -> u8 {
+        match self {
+            RouteSegment::Static(_) => 0,
+            RouteSegment::Param => 1,
+            RouteSegment::Wildcard => 2,
+        }
+    }
+}
+
+/// Split a path template into segments the way the data-plane router does.
+fn route_segments(path: &str) -> Vec<RouteSegment<'_>> {
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            if s.starts_with('{') && s.ends_with('}') {
+                if s.ends_with("+}") {
+                    RouteSegment::Wildcard
+                } else {
+                    RouteSegment::Param
+                }
+            } else {
+                RouteSegment::Static(s)
+            }
+        })
+        .collect()
+}
+
+/// Whether some request path matches both templates.
+fn routes_overlap(a: &[RouteSegment], b: &[RouteSegment]) -> bool {
+    match (a.split_first(), b.split_first()) {
+        (None, None) => true,
+        // A wildcard takes every remaining segment (at least one).
+        (Some((RouteSegment::Wildcard, _)), Some(_))
+        | (Some(_), Some((RouteSegment::Wildcard, _))) => true,
+        (Some((x, a_rest)), Some((y, b_rest))) => {
+            let compatible = match (x, y) {
+                (RouteSegment::Static(x), RouteSegment::Static(y)) => x == y,
+                _ => true,
+            };
+            compatible && routes_overlap(a_rest, b_rest)
+        }
+        _ => false,
+    }
+}
+
+/// Warn for each pair of same-method operations whose path templates overlap
+/// (E1017), e.g. `/users/me` and `/users/{id}`. The router sends a request
+/// matching both to the template with the more specific segment at the first
+/// position where they differ, so the other one never sees those requests.
+/// Same-shape templates are already rejected by E1010/E1050.
+fn route_overlap_warnings(routes: &[(String, String, WarningSite)]) -> Vec<CompileWarning> {
+    let mut warnings = Vec::new();
+    for (i, (method, path, site)) in routes.iter().enumerate() {
+        let segments = route_segments(path);
+        for (other_method, other_path, other_site) in &routes[i + 1..] {
+            if method != other_method {
+                continue;
+            }
+            let other_segments = route_segments(other_path);
+            if !routes_overlap(&segments, &other_segments) {
+                continue;
+            }
+            let precedence = segments
+                .iter()
+                .zip(&other_segments)
+                .map(|(a, b)| a.rank().cmp(&b.rank()))
+                .find(|ordering| ordering.is_ne());
+            let (winner, loser, loser_site) = match precedence {
+                Some(std::cmp::Ordering::Less) => (path, other_path, other_site),
+                Some(_) => (other_path, path, site),
+                None => continue,
+            };
+            warnings.push(loser_site.warning(
+                DiagnosticCode::E1017,
+                format!(
+                    "ambiguous route: {} {} shadows {} for requests matching both; \
+                     static segments take precedence over parameters",
+                    method, winner, loser
+                ),
+            ));
+        }
+    }
+    warnings
+}
+
 /// Normalize a path template for structural comparison (E1050).
 ///
 /// Replaces parameter names with a placeholder while preserving the wildcard `+` modifier:
@@ -1492,6 +2501,53 @@ fn extract_root_mcp_config(specs: &[(ApiSpec, String, String)]) -> McpConfig {
     McpConfig::default()
 }
 
+/// Parse an operation's `x-barbacane-accept` allowlist (E1056). Entries must
+/// be `type/subtype` media types (`type/*` allowed); parameters are not.
+fn resolve_accept_list(
+    value: Option<&serde_json::Value>,
+    location: &str,
+) -> Result<Vec<String>, CompileError> {
+    let Some(value) = value else {
+        return Ok(Vec::new());
+    };
+    let invalid =
+        |reason: &str| CompileError::InvalidAcceptList(format!("{} at {}", reason, location));
+
+    let entries = value
+        .as_array()
+        .ok_or_else(|| invalid("expected a list of media types"))?;
+    if entries.is_empty() {
+        return Err(invalid(
+            "list is empty (every request body would be rejected)",
+        ));
+    }
+
+    entries
+        .iter()
+        .map(|entry| {
+            let media_type = entry
+                .as_str()
+                .ok_or_else(|| invalid("media types must be strings"))?
+                .trim()
+                .to_ascii_lowercase();
+            match media_type.split_once('/') {
+                Some((ty, subtype))
+                    if !ty.is_empty()
+                        && ty != "*"
+                        && !subtype.is_empty()
+                        && !subtype.contains(['/', ';']) =>
+                {
+                    Ok(media_type)
+                }
+                _ => Err(invalid(&format!(
+                    "'{}' is not a type/subtype media type",
+                    media_type
+                ))),
+            }
+        })
+        .collect()
+}
+
 /// Resolve MCP enabled/description for a single operation from root + operation-level config.
 fn resolve_mcp_config(
     root: &McpConfig,
@@ -1577,14 +2633,22 @@ mod tests {
             &config,
             "ai-proxy",
             &secret_fields,
-            "GET /v1 in 'api.yaml'",
+            &test_site(),
             &mut warnings,
         );
 
         // Two plaintext secrets: routes[0].api_key and targets.azure.client_secret.
         // routes[1].base_url is not a secret field; the env:// api_key is ignored.
         assert_eq!(warnings.len(), 2, "got: {warnings:?}");
-        assert!(warnings.iter().all(|w| w.code == "E1070"));
+        assert!(warnings.iter().all(|w| w.code == DiagnosticCode::E1070));
+    }
+
+    fn test_site() -> WarningSite {
+        WarningSite {
+            location: "GET /v1 in 'api.yaml'".to_string(),
+            spec: "api.yaml".to_string(),
+            pointer: Some("/paths/~1v1/get".to_string()),
+        }
     }
 
     #[test]
@@ -1597,7 +2661,13 @@ mod tests {
             "api_key": "",
         });
         let mut warnings = Vec::new();
-        scan_plaintext_secrets(&config, "ai-proxy", &secret_fields, "loc", &mut warnings);
+        scan_plaintext_secrets(
+            &config,
+            "ai-proxy",
+            &secret_fields,
+            &test_site(),
+            &mut warnings,
+        );
         assert!(warnings.is_empty(), "got: {warnings:?}");
     }
 
@@ -1608,6 +2678,30 @@ mod tests {
         path
     }
 
+    /// Minimal valid WASM module: magic + version.
+    const TEST_WASM: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+    /// Write each `(name, plugin_type)` plugin as `<name>/<name>.wasm` with
+    /// its `plugin.toml` under `dir`, and return a manifest declaring them.
+    fn write_test_plugins(dir: &Path, plugins: &[(&str, &str)]) -> ProjectManifest {
+        let mut manifest = String::from("plugins:\n");
+        for (name, plugin_type) in plugins {
+            let plugin_dir = dir.join(name);
+            std::fs::create_dir_all(&plugin_dir).unwrap();
+            std::fs::write(plugin_dir.join(format!("{}.wasm", name)), TEST_WASM).unwrap();
+            std::fs::write(
+                plugin_dir.join("plugin.toml"),
+                format!(
+                    "[plugin]\nname = \"{}\"\nversion = \"1.0.0\"\ntype = \"{}\"\n",
+                    name, plugin_type
+                ),
+            )
+            .unwrap();
+            manifest.push_str(&format!("  {0}:\n    path: ./{0}/{0}.wasm\n", name));
+        }
+        ProjectManifest::parse(&manifest, Path::new("barbacane.yaml")).unwrap()
+    }
+
     #[test]
     fn compile_minimal_spec() {
         let temp = TempDir::new().unwrap();
@@ -1645,6 +2739,193 @@ paths:
         assert!(output_path.exists());
     }
 
+    const ENV_SPEC: &str = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      x-barbacane-middlewares:
+        - name: oauth2-auth
+          config:
+            client_id: gateway
+            client_secret: "${env:CLIENT_SECRET}"
+            scopes: ["read", "${env:EXTRA_SCOPE}"]
+            timeout: 5
+      x-barbacane-dispatch:
+        name: http-upstream
+        config:
+          url: "https://${env:UPSTREAM_HOST}/v1"
+"#;
+
+    fn compile_with_env(env: &[(&str, &str)]) -> Result<CompiledRoutes, CompileError> {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(temp.path(), "api.yaml", ENV_SPEC);
+        let output_path = temp.path().join("artifact.bca");
+        let options = CompileOptions {
+            env: env
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..CompileOptions::default()
+        };
+        compile(&[spec_path.as_path()], &[], &output_path, &options)?;
+        Ok(load_routes(&output_path).unwrap())
+    }
+
+    #[test]
+    fn compile_interpolates_env_placeholders() {
+        let routes = compile_with_env(&[
+            ("CLIENT_SECRET", "s3cret"),
+            ("EXTRA_SCOPE", "write"),
+            ("UPSTREAM_HOST", "users.internal"),
+        ])
+        .unwrap();
+        let op = &routes.operations[0];
+        assert_eq!(op.dispatch.config["url"], "https://users.internal/v1");
+        let config = &op.middlewares[0].config;
+        assert_eq!(config["client_secret"], "s3cret");
+        assert_eq!(config["scopes"], serde_json::json!(["read", "write"]));
+        // Values without a placeholder pass through untouched.
+        assert_eq!(config["client_id"], "gateway");
+        assert_eq!(config["timeout"], 5);
+    }
+
+    #[test]
+    fn compile_rejects_missing_env_var() {
+        let err =
+            compile_with_env(&[("CLIENT_SECRET", "s3cret"), ("EXTRA_SCOPE", "write")]).unwrap_err();
+        assert_eq!(err.code(), DiagnosticCode::E1025);
+        assert_eq!(
+            err.to_string(),
+            "E1025: plugin 'http-upstream' config on GET /users in 'api.yaml' references unset environment variable 'UPSTREAM_HOST'"
+        );
+    }
+
+    #[test]
+    fn compile_does_not_flag_env_placeholders_as_plaintext_secrets() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write_test_plugins(
+            temp.path(),
+            &[
+                ("oauth2-auth", "middleware"),
+                ("http-upstream", "dispatcher"),
+            ],
+        );
+        std::fs::write(
+            temp.path().join("oauth2-auth/config-schema.json"),
+            r#"{"type": "object", "properties": {"client_secret": {"type": "string", "writeOnly": true}}}"#,
+        )
+        .unwrap();
+        let spec_path = create_test_spec(temp.path(), "api.yaml", ENV_SPEC);
+        let options = CompileOptions {
+            env: BTreeMap::from([
+                ("CLIENT_SECRET".to_string(), "s3cret".to_string()),
+                ("EXTRA_SCOPE".to_string(), "write".to_string()),
+                ("UPSTREAM_HOST".to_string(), "users.internal".to_string()),
+            ]),
+            ..CompileOptions::default()
+        };
+
+        let result = compile_with_manifest(
+            &[spec_path.as_path()],
+            &manifest,
+            temp.path(),
+            &temp.path().join("artifact.bca"),
+            &options,
+        )
+        .unwrap();
+        assert!(
+            result
+                .warnings
+                .iter()
+                .all(|w| w.code != DiagnosticCode::E1070),
+            "got: {:?}",
+            result.warnings
+        );
+    }
+
+    #[test]
+    fn compile_flags_inline_jwt_hmac_secret() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write_test_plugins(
+            temp.path(),
+            &[("jwt-auth", "middleware"), ("mock", "dispatcher")],
+        );
+        let schema =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../plugins/jwt-auth/config-schema.json");
+        std::fs::copy(schema, temp.path().join("jwt-auth/config-schema.json")).unwrap();
+        let spec = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /inline:
+    get:
+      operationId: inline
+      x-barbacane-middlewares:
+        - name: jwt-auth
+          config:
+            hmac_secret: "not-a-reference"
+      x-barbacane-dispatch:
+        name: mock
+  /referenced:
+    get:
+      operationId: referenced
+      x-barbacane-middlewares:
+        - name: jwt-auth
+          config:
+            hmac_secret: "env://JWT_HMAC_SECRET"
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec_path = create_test_spec(temp.path(), "api.yaml", spec);
+
+        let result = compile_with_manifest(
+            &[spec_path.as_path()],
+            &manifest,
+            temp.path(),
+            &temp.path().join("artifact.bca"),
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        let secrets: Vec<_> = result
+            .warnings
+            .iter()
+            .filter(|w| w.code == DiagnosticCode::E1070)
+            .collect();
+        assert_eq!(secrets.len(), 1, "got: {secrets:?}");
+        assert!(secrets[0].message.contains("hmac_secret"));
+        assert_eq!(
+            secrets[0].location.as_deref(),
+            Some("GET /inline in 'api.yaml'")
+        );
+    }
+
+    #[test]
+    fn interpolate_env_keeps_literals_and_unterminated_placeholders() {
+        let env = BTreeMap::from([("A".to_string(), "1".to_string())]);
+        let mut config = serde_json::json!({
+            "plain": "no placeholder",
+            "env_ref": "env://A",
+            "open": "${env:A}-${env:A",
+            "${env:A}": true,
+        });
+        interpolate_env(&mut config, &env, "p", "GET /").unwrap();
+        assert_eq!(
+            config,
+            serde_json::json!({
+                "plain": "no placeholder",
+                "env_ref": "env://A",
+                "open": "1-${env:A",
+                "${env:A}": true,
+            })
+        );
+    }
+
     #[test]
     fn compile_detects_missing_dispatch() {
         let temp = TempDir::new().unwrap();
@@ -1779,50 +3060,656 @@ paths:
         assert_eq!(routes.operations.len(), 2);
     }
 
-    #[test]
-    fn compile_rejects_plaintext_http_url() {
+    fn compile_spec_warnings(paths: &str) -> Vec<CompileWarning> {
         let temp = TempDir::new().unwrap();
-
-        let spec_content = r#"
-openapi: "3.1.0"
-info:
-  title: Test API
-  version: "1.0.0"
-paths:
-  /proxy:
-    get:
-      x-barbacane-dispatch:
-        name: http-upstream
-        config:
-          url: "http://backend.internal:8080/api"
-"#;
-        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
-        let output_path = temp.path().join("artifact.bca");
-
-        // Default options reject plaintext HTTP
-        let result = compile(
-            &[spec_path.as_path()],
-            &[],
-            &output_path,
-            &CompileOptions::default(),
+        let spec_content = format!(
+            "openapi: \"3.1.0\"\ninfo:\n  title: Test API\n  version: \"1.0.0\"\npaths:\n{}",
+            paths
         );
-        assert!(matches!(result, Err(CompileError::PlaintextUpstream(_))));
-
-        // With allow_plaintext, it should succeed
-        let result = compile(
+        let spec_path = create_test_spec(temp.path(), "test.yaml", &spec_content);
+        compile(
             &[spec_path.as_path()],
             &[],
-            &output_path,
-            &CompileOptions {
-                allow_plaintext: true,
-                ..Default::default()
-            },
-        );
-        assert!(result.is_ok());
+            &temp.path().join("artifact.bca"),
+            &CompileOptions::default(),
+        )
+        .unwrap()
+        .warnings
     }
 
     #[test]
-    fn compile_allows_https_url() {
+    fn static_segment_shadowing_param_warns() {
+        let warnings = compile_spec_warnings(
+            r#"
+  /users/{id}:
+    get:
+      operationId: getUsersId
+      x-barbacane-dispatch:
+        name: mock
+  /users/me:
+    get:
+      operationId: getUsersMe
+      x-barbacane-dispatch:
+        name: mock
+    delete:
+      operationId: deleteUsersMe
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].code, DiagnosticCode::E1017);
+        assert_eq!(
+            warnings[0].message,
+            "ambiguous route: GET /users/me shadows /users/{id} for requests matching both; \
+             static segments take precedence over parameters"
+        );
+        assert_eq!(
+            warnings[0].location.as_deref(),
+            Some("GET /users/{id} in 'test.yaml'")
+        );
+    }
+
+    #[test]
+    fn param_segment_shadowing_wildcard_warns() {
+        let warnings = compile_spec_warnings(
+            r#"
+  /files/{key+}:
+    get:
+      operationId: getFilesKey
+      x-barbacane-dispatch:
+        name: mock
+  /files/{bucket}/meta:
+    get:
+      operationId: getFilesBucketMeta
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].code, DiagnosticCode::E1017);
+        assert!(warnings[0]
+            .message
+            .contains("GET /files/{bucket}/meta shadows /files/{key+}"));
+    }
+
+    #[test]
+    fn non_overlapping_routes_do_not_warn() {
+        let warnings = compile_spec_warnings(
+            r#"
+  /users/{id}:
+    get:
+      operationId: getUsersId
+      x-barbacane-dispatch:
+        name: mock
+  /users/{id}/orders:
+    get:
+      operationId: getUsersIdOrders
+      x-barbacane-dispatch:
+        name: mock
+  /orders/latest:
+    get:
+      operationId: getOrdersLatest
+      x-barbacane-dispatch:
+        name: mock
+  /users/me:
+    put:
+      operationId: putUsersMe
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    #[test]
+    fn iter_operations_yields_route_summaries() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+x-barbacane-middlewares:
+  - name: cors
+paths:
+  /health:
+    get:
+      operationId: health
+      x-barbacane-dispatch:
+        name: mock
+  /users/{id}:
+    delete:
+      x-barbacane-middlewares:
+        - name: jwt-auth
+        - name: rate-limit
+      x-barbacane-dispatch:
+        name: http-upstream
+        config:
+          url: "https://backend.internal"
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+        compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        let routes = load_routes(&output_path).unwrap();
+        let mut summaries: Vec<RouteSummary> = routes
+            .iter_operations()
+            .map(CompiledOperation::route_summary)
+            .collect();
+        summaries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert_eq!(
+            summaries,
+            vec![
+                RouteSummary {
+                    method: "GET".to_string(),
+                    path: "/health".to_string(),
+                    operation_id: Some("health".to_string()),
+                    plugins: vec!["cors".to_string(), "mock".to_string()],
+                },
+                RouteSummary {
+                    method: "DELETE".to_string(),
+                    path: "/users/{id}".to_string(),
+                    operation_id: Some("delete_users_id".to_string()),
+                    plugins: vec![
+                        "cors".to_string(),
+                        "jwt-auth".to_string(),
+                        "rate-limit".to_string(),
+                        "http-upstream".to_string(),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn stream_routes_yields_same_operations_as_load_routes() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /health:
+    get:
+      description: "quotes \" and brackets ] } in a string"
+      x-barbacane-dispatch:
+        name: mock
+        config:
+          status: 200
+          body: '{"ok":[true]}'
+  /users/{id}:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+    delete:
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+        compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        let eager = load_routes(&output_path).unwrap();
+        let mut streamed = Vec::new();
+        stream_routes(&output_path, |op| streamed.push(op)).unwrap();
+
+        assert_eq!(streamed.len(), 3);
+        assert_eq!(
+            serde_json::to_value(&streamed).unwrap(),
+            serde_json::to_value(&eager.operations).unwrap()
+        );
+    }
+
+    #[test]
+    fn routes_stream_reads_incrementally() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        struct CountingReader<R> {
+            inner: R,
+            read: Rc<Cell<usize>>,
+        }
+        impl<R: Read> Read for CountingReader<R> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.inner.read(buf)?;
+                self.read.set(self.read.get() + n);
+                Ok(n)
+            }
+        }
+
+        let op = serde_json::json!({
+            "index": 0,
+            "path": "/items/{id}",
+            "method": "GET",
+            "operation_id": null,
+            "parameters": [],
+            "request_body": null,
+            "dispatch": {"name": "mock", "config": {"body": "x".repeat(200)}},
+        });
+        let operations: Vec<serde_json::Value> = (0..1000)
+            .map(|i| {
+                let mut op = op.clone();
+                op["index"] = i.into();
+                op
+            })
+            .collect();
+        let json = serde_json::to_vec(&serde_json::json!({ "operations": operations })).unwrap();
+
+        let read = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: std::io::Cursor::new(json.clone()),
+            read: Rc::clone(&read),
+        };
+        let mut seen = 0;
+        stream_routes_from_reader(reader, |op| {
+            assert_eq!(op.index, seen);
+            if seen == 0 {
+                // Only the first buffered chunk has been pulled, not the document.
+                assert!(read.get() < json.len() / 10, "read {} bytes", read.get());
+            }
+            seen += 1;
+        })
+        .unwrap();
+
+        assert_eq!(seen, 1000);
+        assert_eq!(read.get(), json.len());
+    }
+
+    #[test]
+    fn routes_stream_skips_other_members_and_rejects_missing_operations() {
+        let json = br#"{"version": {"a": [1, "]"]}, "operations": []}"#;
+        let mut seen = 0;
+        stream_routes_from_reader(&json[..], |_| seen += 1).unwrap();
+        assert_eq!(seen, 0);
+
+        let err = stream_routes_from_reader(&br#"{"version": 1}"#[..], |_| {}).unwrap_err();
+        assert!(err.to_string().contains("operations"), "{err}");
+    }
+
+    #[test]
+    fn compile_rejects_plaintext_http_url() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /proxy:
+    get:
+      x-barbacane-dispatch:
+        name: http-upstream
+        config:
+          url: "http://backend.internal:8080/api"
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        // Default options reject plaintext HTTP
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        );
+        assert!(matches!(result, Err(CompileError::PlaintextUpstream(_))));
+
+        // With allow_plaintext, it should succeed
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions {
+                allow_plaintext: true,
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn compile_warns_when_inline_plugins_are_shadowed() {
+        let temp = TempDir::new().unwrap();
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /health:
+    get:
+      operationId: getHealth
+      x-barbacane-dispatch:
+        name: mock
+      x-barbacane-plugins:
+        dispatch:
+          name: http-upstream
+        middlewares:
+          - name: cors
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        // Only the dispatch slot is shadowed; the inline chain is used.
+        assert_eq!(result.warnings.len(), 1, "got: {:?}", result.warnings);
+        assert_eq!(result.warnings[0].code, DiagnosticCode::E1018);
+        assert_eq!(
+            result.warnings[0].message,
+            "x-barbacane-plugins.dispatch is ignored: x-barbacane-dispatch is also set"
+        );
+        let routes = load_routes(&output_path).unwrap();
+        assert_eq!(routes.operations[0].dispatch.name, "mock");
+        assert_eq!(routes.operations[0].middlewares[0].name, "cors");
+    }
+
+    #[test]
+    fn compile_deny_warnings() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+x-barbacane-unknown: true
+paths:
+  /health:
+    get:
+      operationId: getHealth
+      x-barbacane-dispatch:
+        name: mock
+        config:
+          status: 200
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        // Warnings are non-fatal by default
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].code, DiagnosticCode::E1015);
+
+        // With deny_warnings, the same warning fails the build
+        let denied_output = temp.path().join("denied.bca");
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &denied_output,
+            &CompileOptions {
+                deny_warnings: true,
+                ..Default::default()
+            },
+        );
+        match result {
+            Err(CompileError::WarningsDenied { count, warnings }) => {
+                assert_eq!(count, 1);
+                assert_eq!(warnings[0].code, DiagnosticCode::E1015);
+            }
+            other => panic!("expected WarningsDenied, got {other:?}"),
+        }
+        assert!(!denied_output.exists());
+    }
+
+    #[test]
+    fn compile_deny_warnings_without_warnings_succeeds() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /health:
+    get:
+      operationId: getHealth
+      x-barbacane-dispatch:
+        name: mock
+        config:
+          status: 200
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions {
+                deny_warnings: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(result.warnings.is_empty());
+        assert!(output_path.exists());
+    }
+
+    #[test]
+    fn diagnostics_json_shape() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /users/{id}:
+    get:
+      operationId: getUsersId
+      x-barbacane-unknown: true
+      x-barbacane-dispatch:
+        name: mock
+        config:
+          status: 200
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        // Warning
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        let spec_file = result.warnings[0].spec.clone().unwrap();
+        assert!(spec_file.ends_with("test.yaml"));
+        let json: serde_json::Value = serde_json::from_str(&result.diagnostics_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "severity": "warning",
+                "code": "E1015",
+                "message": "unknown extension: x-barbacane-unknown",
+                "location": {
+                    "spec": spec_file,
+                    "pointer": "/paths/~1users~1{id}/get",
+                },
+            }])
+        );
+
+        // Error
+        let err = CompileError::RoutingConflict("GET /users".to_string());
+        let json: serde_json::Value = serde_json::from_str(&err.diagnostics_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "severity": "error",
+                "code": "E1010",
+                "message": "routing conflict: GET /users",
+            }])
+        );
+
+        // Round-trips through the public types
+        let parsed: Vec<Diagnostic> = serde_json::from_str(&err.diagnostics_json()).unwrap();
+        assert_eq!(parsed, err.diagnostics());
+        assert_eq!(err.to_string(), "E1010: routing conflict: GET /users");
+    }
+
+    #[test]
+    fn compile_carries_operation_timeout() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /reports:
+    get:
+      operationId: getReports
+      x-barbacane-timeout: 120000
+      x-barbacane-dispatch:
+        name: mock
+  /health:
+    get:
+      operationId: getHealth
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        // A known extension: no E1015
+        assert!(result.warnings.is_empty(), "got: {:?}", result.warnings);
+
+        let routes = load_routes(&output_path).unwrap();
+        let find = |path: &str| routes.operations.iter().find(|op| op.path == path).unwrap();
+        assert_eq!(find("/reports").timeout_ms, Some(120000));
+        assert_eq!(find("/health").timeout_ms, None);
+    }
+
+    #[test]
+    fn compile_warns_on_deprecated_operations() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /v1/users:
+    get:
+      operationId: getV1Users
+      deprecated: true
+      x-barbacane-dispatch:
+        name: mock
+    post:
+      operationId: postV1Users
+      deprecated: true
+      x-sunset: "Sat, 31 Dec 2025 23:59:59 GMT"
+      x-barbacane-dispatch:
+        name: mock
+  /v2/users:
+    get:
+      operationId: getV2Users
+      deprecated: false
+      x-barbacane-dispatch:
+        name: mock
+    post:
+      operationId: postV2Users
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.warnings.len(), 2, "got: {:?}", result.warnings);
+        assert!(result
+            .warnings
+            .iter()
+            .all(|w| w.code == DiagnosticCode::E1016));
+        let messages: Vec<&str> = result.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert!(messages.contains(&"deprecated operation GET /v1/users is still routed"));
+        assert!(messages.contains(&"deprecated operation POST /v1/users is still routed"));
+    }
+
+    #[test]
+    fn compile_allows_https_url() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /proxy:
+    get:
+      x-barbacane-dispatch:
+        name: http-upstream
+        config:
+          url: "https://backend.internal:8080/api"
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        // HTTPS should be allowed by default
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn compile_with_bundled_plugins() {
         let temp = TempDir::new().unwrap();
 
         let spec_content = r#"
@@ -1831,83 +3718,318 @@ info:
   title: Test API
   version: "1.0.0"
 paths:
-  /proxy:
+  /health:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+
+        // Create a fake plugin (minimal valid WASM)
+        let fake_wasm = vec![
+            0x00, 0x61, 0x73, 0x6d, // magic
+            0x01, 0x00, 0x00, 0x00, // version
+        ];
+
+        let plugins = vec![PluginBundle {
+            name: "test-plugin".to_string(),
+            version: "1.0.0".to_string(),
+            plugin_type: "middleware".to_string(),
+            wasm_bytes: fake_wasm.clone(),
+            body_access: false,
+            host_functions: vec![],
+            secret_fields: vec![],
+        }];
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &plugins,
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.manifest.plugins.len(), 1);
+        assert_eq!(result.manifest.plugins[0].name, "test-plugin");
+        assert_eq!(result.manifest.plugins[0].version, "1.0.0");
+        assert_eq!(result.manifest.plugins[0].plugin_type, "middleware");
+        assert_eq!(
+            result.manifest.plugins[0].wasm_path,
+            "plugins/test-plugin.wasm"
+        );
+
+        // Load plugins back
+        let loaded = load_plugins(&output_path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        let plugin = loaded.get("test-plugin").unwrap();
+        assert_eq!(plugin.version, "1.0.0");
+        assert_eq!(plugin.wasm_bytes, fake_wasm);
+    }
+
+    const FIVE_OPERATION_SPEC: &str = r##"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /health:
+    get:
+      operationId: health
+      x-barbacane-dispatch:
+        name: mock
+  /users:
+    get:
+      operationId: listUsers
+      x-barbacane-middlewares:
+        - name: jwt-auth
+      x-barbacane-dispatch:
+        name: http-upstream
+        config:
+          url: "https://users.internal"
+    post:
+      operationId: createUser
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Missing"
+      x-barbacane-dispatch:
+        name: http-upstream
+        config:
+          url: "https://users.internal"
+  /users/{id}:
+    get:
+      operationId: getUser
+      x-barbacane-dispatch:
+        name: mock
+  /reports:
+    get:
+      operationId: reports
+      x-barbacane-middlewares:
+        - name: rate-limit
+      x-barbacane-dispatch:
+        name: lambda
+"##;
+
+    fn fake_bundle(name: &str) -> PluginBundle {
+        PluginBundle {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            plugin_type: "middleware".to_string(),
+            wasm_bytes: TEST_WASM.to_vec(),
+            body_access: false,
+            host_functions: vec![],
+            secret_fields: vec![],
+        }
+    }
+
+    #[test]
+    fn only_operations_compiles_listed_subset() {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(temp.path(), "test.yaml", FIVE_OPERATION_SPEC);
+        let output_path = temp.path().join("artifact.bca");
+        let plugins: Vec<PluginBundle> =
+            ["mock", "http-upstream", "jwt-auth", "rate-limit", "lambda"]
+                .into_iter()
+                .map(fake_bundle)
+                .collect();
+
+        // createUser's unresolvable $ref is never touched.
+        let result = compile(
+            &[spec_path.as_path()],
+            &plugins,
+            &output_path,
+            &CompileOptions {
+                only_operations: Some(vec!["listUsers".to_string(), "getUser".to_string()]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let routes = load_routes(&output_path).unwrap();
+        let ids: Vec<_> = routes
+            .iter_operations()
+            .map(|op| op.operation_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["listUsers", "getUser"]);
+        assert_eq!(result.manifest.routes_count, 2);
+
+        let mut bundled: Vec<_> = load_plugins(&output_path).unwrap().into_keys().collect();
+        bundled.sort();
+        assert_eq!(bundled, vec!["http-upstream", "jwt-auth", "mock"]);
+    }
+
+    #[test]
+    fn only_operations_unknown_id_errors() {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(temp.path(), "test.yaml", FIVE_OPERATION_SPEC);
+
+        let err = compile(
+            &[spec_path.as_path()],
+            &[],
+            &temp.path().join("artifact.bca"),
+            &CompileOptions {
+                only_operations: Some(vec!["health".to_string(), "deleteUser".to_string()]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(err.code(), DiagnosticCode::E1057);
+        assert_eq!(
+            err.to_string(),
+            "E1057: unknown operationId in only_operations: deleteUser"
+        );
+    }
+
+    #[test]
+    fn unused_bundled_plugin_warns() {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(
+            temp.path(),
+            "test.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /health:
+    get:
+      operationId: getHealth
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &[fake_bundle("mock"), fake_bundle("cors")],
+            &temp.path().join("artifact.bca"),
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+        assert_eq!(result.warnings[0].code, DiagnosticCode::E1041);
+        assert_eq!(
+            result.warnings[0].message,
+            "plugin 'cors' is bundled but no operation uses it"
+        );
+    }
+
+    #[test]
+    fn referenced_bundled_plugins_do_not_warn() {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(
+            temp.path(),
+            "test.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+x-barbacane-middlewares:
+  - name: cors
+paths:
+  /health:
     get:
+      operationId: getHealth
       x-barbacane-dispatch:
-        name: http-upstream
-        config:
-          url: "https://backend.internal:8080/api"
-"#;
-        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
-        let output_path = temp.path().join("artifact.bca");
+        name: mock@1.0.0
+"#,
+        );
 
-        // HTTPS should be allowed by default
         let result = compile(
             &[spec_path.as_path()],
-            &[],
-            &output_path,
+            &[fake_bundle("mock"), fake_bundle("cors")],
+            &temp.path().join("artifact.bca"),
             &CompileOptions::default(),
-        );
-        assert!(result.is_ok());
+        )
+        .unwrap();
+
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
     }
 
     #[test]
-    fn compile_with_bundled_plugins() {
-        let temp = TempDir::new().unwrap();
-
-        let spec_content = r#"
+    fn load_specs_from_bytes_parses_openapi_and_asyncapi() {
+        let openapi = br#"
 openapi: "3.1.0"
 info:
-  title: Test API
+  title: Users API
   version: "1.0.0"
 paths:
-  /health:
+  /users:
     get:
+      operationId: listUsers
       x-barbacane-dispatch:
         name: mock
 "#;
-        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
-        let output_path = temp.path().join("artifact.bca");
-
-        // Create a fake plugin (minimal valid WASM)
-        let fake_wasm = vec![
-            0x00, 0x61, 0x73, 0x6d, // magic
-            0x01, 0x00, 0x00, 0x00, // version
-        ];
-
-        let plugins = vec![PluginBundle {
-            name: "test-plugin".to_string(),
-            version: "1.0.0".to_string(),
-            plugin_type: "middleware".to_string(),
-            wasm_bytes: fake_wasm.clone(),
-            body_access: false,
-            host_functions: vec![],
-            secret_fields: vec![],
-        }];
+        let asyncapi = br#"
+asyncapi: "3.0.0"
+info:
+  title: User Events API
+  version: "1.0.0"
+channels:
+  userSignedUp:
+    address: user/signedup
+operations:
+  processUserSignup:
+    action: receive
+    channel:
+      $ref: '#/channels/userSignedUp'
+    x-barbacane-dispatch:
+      name: kafka
+"#;
 
-        let result = compile(
-            &[spec_path.as_path()],
-            &plugins,
-            &output_path,
-            &CompileOptions::default(),
-        )
+        let specs = load_specs_from_bytes(&[
+            (
+                "users.yaml".to_string(),
+                openapi.to_vec(),
+                SpecFormat::OpenApi,
+            ),
+            (
+                "events.yaml".to_string(),
+                asyncapi.to_vec(),
+                SpecFormat::AsyncApi,
+            ),
+        ])
         .unwrap();
 
-        assert_eq!(result.manifest.plugins.len(), 1);
-        assert_eq!(result.manifest.plugins[0].name, "test-plugin");
-        assert_eq!(result.manifest.plugins[0].version, "1.0.0");
-        assert_eq!(result.manifest.plugins[0].plugin_type, "middleware");
-        assert_eq!(
-            result.manifest.plugins[0].wasm_path,
-            "plugins/test-plugin.wasm"
-        );
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].format, SpecFormat::OpenApi);
+        assert_eq!(specs[0].filename.as_deref(), Some("users.yaml"));
+        assert_eq!(specs[0].operations[0].path, "/users");
+        assert_eq!(specs[1].format, SpecFormat::AsyncApi);
+        assert_eq!(specs[1].filename.as_deref(), Some("events.yaml"));
+        assert_eq!(specs[1].operations[0].path, "user/signedup");
+    }
 
-        // Load plugins back
-        let loaded = load_plugins(&output_path).unwrap();
-        assert_eq!(loaded.len(), 1);
-        let plugin = loaded.get("test-plugin").unwrap();
-        assert_eq!(plugin.version, "1.0.0");
-        assert_eq!(plugin.wasm_bytes, fake_wasm);
+    #[test]
+    fn load_specs_from_bytes_rejects_malformed_buffer() {
+        let result = load_specs_from_bytes(&[(
+            "broken.yaml".to_string(),
+            b"openapi: [unclosed".to_vec(),
+            SpecFormat::OpenApi,
+        )]);
+        assert!(matches!(
+            result,
+            Err(CompileError::Parse(ParseError::ParseError(_)))
+        ));
+
+        // A buffer of the other format is rejected too
+        let result = load_specs_from_bytes(&[(
+            "users.yaml".to_string(),
+            b"openapi: \"3.1.0\"\ninfo:\n  title: T\n  version: \"1\"\npaths: {}\n".to_vec(),
+            SpecFormat::AsyncApi,
+        )]);
+        match result {
+            Err(CompileError::Parse(ParseError::SchemaError(msg))) => {
+                assert!(msg.contains("users.yaml"), "got: {msg}");
+            }
+            other => panic!("expected SchemaError, got {other:?}"),
+        }
     }
 
     #[test]
@@ -2159,18 +4281,328 @@ paths:
 "#;
         let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
         let output_path = temp.path().join("artifact.bca");
-
-        let result = compile(
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(CompileError::DuplicateOperationId { .. })
+        ));
+    }
+
+    #[test]
+    fn duplicate_operation_id_lists_every_location() {
+        let temp = TempDir::new().unwrap();
+        let users = create_test_spec(
+            temp.path(),
+            "users.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Users
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      operationId: list
+      x-barbacane-dispatch:
+        name: mock
+    post:
+      operationId: createUser
+      x-barbacane-dispatch:
+        name: mock
+  /users/{id}:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+        let orders = create_test_spec(
+            temp.path(),
+            "orders.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Orders
+  version: "1.0.0"
+paths:
+  /orders:
+    get:
+      operationId: list
+      x-barbacane-dispatch:
+        name: mock
+  /invoices:
+    get:
+      operationId: list
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+
+        let err = compile(
+            &[users.as_path(), orders.as_path()],
+            &[],
+            &temp.path().join("artifact.bca"),
+            &CompileOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), DiagnosticCode::E1055);
+        match &err {
+            CompileError::DuplicateOperationId { id, locations } => {
+                assert_eq!(id, "list");
+                assert_eq!(
+                    locations,
+                    &[
+                        "GET /users in 'users.yaml'",
+                        "GET /invoices in 'orders.yaml'",
+                        "GET /orders in 'orders.yaml'",
+                    ]
+                );
+            }
+            other => panic!("expected DuplicateOperationId, got {other:?}"),
+        }
+        assert!(err.to_string().starts_with(
+            "E1055: duplicate operationId 'list': declared by GET /users in 'users.yaml', "
+        ));
+    }
+
+    #[test]
+    fn synthesize_operation_id_from_method_and_path() {
+        assert_eq!(
+            synthesize_operation_id("GET", "/users/{id}"),
+            "get_users_id"
+        );
+        assert_eq!(
+            synthesize_operation_id("POST", "/v1/user-groups/{groupId}/members"),
+            "post_v1_user_groups_groupid_members"
+        );
+        assert_eq!(
+            synthesize_operation_id("GET", "/files/{key+}"),
+            "get_files_key"
+        );
+        assert_eq!(synthesize_operation_id("GET", "/"), "get");
+        assert_eq!(
+            synthesize_operation_id("SEND", "orders.{orderId}"),
+            "send_orders_orderid"
+        );
+    }
+
+    #[test]
+    fn compile_synthesizes_missing_operation_ids() {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(
+            temp.path(),
+            "test.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      operationId: get_users_id
+      x-barbacane-dispatch:
+        name: mock
+  /users/{id}:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+  /users-id:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+  /health:
+    get:
+      operationId: health
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+        let output_path = temp.path().join("artifact.bca");
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &output_path,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        let routes = load_routes(&output_path).unwrap();
+        let id_of = |path: &str| {
+            let op = routes.operations.iter().find(|op| op.path == path).unwrap();
+            (
+                op.operation_id.clone().unwrap(),
+                op.operation_id_synthesized,
+            )
+        };
+        // Declared ids are left untouched, even one shaped like a synthesized id.
+        assert_eq!(id_of("/users"), ("get_users_id".to_string(), false));
+        assert_eq!(id_of("/health"), ("health".to_string(), false));
+        // Both /users-id and /users/{id} derive get_users_id, which is declared:
+        // they get suffixes in path order.
+        assert_eq!(id_of("/users-id"), ("get_users_id_2".to_string(), true));
+        assert_eq!(id_of("/users/{id}"), ("get_users_id_3".to_string(), true));
+
+        let synthesized: Vec<&str> = result
+            .warnings
+            .iter()
+            .filter(|w| w.code == DiagnosticCode::E1019)
+            .map(|w| w.message.as_str())
+            .collect();
+        assert_eq!(
+            synthesized,
+            [
+                "operation has no operationId; using synthesized 'get_users_id_2'",
+                "operation has no operationId; using synthesized 'get_users_id_3'",
+            ]
+        );
+        assert_eq!(result.warnings.len(), 2, "got: {:?}", result.warnings);
+
+        // Stable across runs: recompiling yields byte-identical output.
+        let again = temp.path().join("again.bca");
+        compile(
+            &[spec_path.as_path()],
+            &[],
+            &again,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read(&output_path).unwrap(),
+            std::fs::read(&again).unwrap()
+        );
+    }
+
+    #[test]
+    fn unique_operation_ids_compile() {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(
+            temp.path(),
+            "test.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      operationId: listUsers
+      x-barbacane-dispatch:
+        name: mock
+    post:
+      operationId: createUser
+      x-barbacane-dispatch:
+        name: mock
+  /health:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+  /ready:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+
+        let result = compile(
+            &[spec_path.as_path()],
+            &[],
+            &temp.path().join("artifact.bca"),
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result.manifest.routes_count, 4);
+    }
+
+    #[test]
+    fn compile_records_accept_allowlist() {
+        let temp = TempDir::new().unwrap();
+
+        let spec_content = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /upload:
+    post:
+      x-barbacane-accept: ["Application/JSON", "text/*"]
+      x-barbacane-dispatch:
+        name: mock
+  /open:
+    post:
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec_path = create_test_spec(temp.path(), "test.yaml", spec_content);
+        let output_path = temp.path().join("artifact.bca");
+        compile(
             &[spec_path.as_path()],
             &[],
             &output_path,
             &CompileOptions::default(),
-        );
+        )
+        .unwrap();
 
-        assert!(matches!(
-            result,
-            Err(CompileError::DuplicateOperationId(_, _))
-        ));
+        let routes = load_routes(&output_path).unwrap();
+        let accepted = |path: &str| {
+            routes
+                .operations
+                .iter()
+                .find(|op| op.path == path)
+                .unwrap()
+                .accepted_content_types
+                .clone()
+        };
+        assert_eq!(accepted("/upload"), vec!["application/json", "text/*"]);
+        assert!(accepted("/open").is_empty());
+    }
+
+    #[test]
+    fn compile_rejects_invalid_accept_allowlist() {
+        for accept in [
+            r#"[]"#,
+            r#""application/json""#,
+            r#"["json"]"#,
+            r#"["*/*"]"#,
+        ] {
+            let temp = TempDir::new().unwrap();
+            let spec_content = format!(
+                r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /upload:
+    post:
+      x-barbacane-accept: {}
+      x-barbacane-dispatch:
+        name: mock
+"#,
+                accept
+            );
+            let spec_path = create_test_spec(temp.path(), "test.yaml", &spec_content);
+            let output_path = temp.path().join("artifact.bca");
+            let result = compile(
+                &[spec_path.as_path()],
+                &[],
+                &output_path,
+                &CompileOptions::default(),
+            );
+            assert!(
+                matches!(result, Err(CompileError::InvalidAcceptList(_))),
+                "{} should be rejected",
+                accept
+            );
+        }
     }
 
     #[test]
@@ -2745,6 +5177,344 @@ paths:
         );
     }
 
+    #[test]
+    fn compile_output_is_reproducible() {
+        let temp = TempDir::new().unwrap();
+
+        let users = create_test_spec(
+            temp.path(),
+            "users.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Users API
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+        let orders = create_test_spec(
+            temp.path(),
+            "orders.yaml",
+            r#"
+openapi: "3.1.0"
+info:
+  title: Orders API
+  version: "1.0.0"
+paths:
+  /orders:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+"#,
+        );
+        let bundle = |name: &str| PluginBundle {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            plugin_type: "middleware".to_string(),
+            wasm_bytes: [&TEST_WASM[..], &[name.len() as u8]].concat(),
+            body_access: false,
+            host_functions: vec![],
+            secret_fields: vec![],
+        };
+
+        let out1 = temp.path().join("first.bca");
+        let out2 = temp.path().join("second.bca");
+        let r1 = compile(
+            &[users.as_path(), orders.as_path()],
+            &[bundle("mock"), bundle("cors")],
+            &out1,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+        // Same inputs, passed in a different order
+        let r2 = compile(
+            &[orders.as_path(), users.as_path()],
+            &[bundle("cors"), bundle("mock")],
+            &out2,
+            &CompileOptions::default(),
+        )
+        .unwrap();
+
+        assert!(r1.artifact_hash().starts_with("sha256:"));
+        assert_eq!(r1.artifact_hash(), r2.artifact_hash());
+        assert_eq!(std::fs::read(&out1).unwrap(), std::fs::read(&out2).unwrap());
+        assert_eq!(
+            r1.artifact_hash(),
+            format!("sha256:{}", compute_sha256(&std::fs::read(&out1).unwrap()))
+        );
+    }
+
+    #[test]
+    fn incremental_compile_reuses_unchanged_specs() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write_test_plugins(temp.path(), &[("mock", "dispatcher")]);
+        let spec = |path: &str| {
+            format!(
+                "openapi: \"3.1.0\"\ninfo:\n  title: API\n  version: \"1.0.0\"\npaths:\n  {}:\n    get:\n      x-barbacane-dispatch:\n        name: mock\n",
+                path
+            )
+        };
+        let users = create_test_spec(temp.path(), "users.yaml", &spec("/users"));
+        let orders = create_test_spec(temp.path(), "orders.yaml", &spec("/orders"));
+        let paths = [users.as_path(), orders.as_path()];
+        let options = CompileOptions::default();
+        let compile_to = |name: &str, previous: Option<&CompileResult>, changed: &[&Path]| {
+            let out = temp.path().join(name);
+            let result = compile_with_manifest_incremental(
+                &paths,
+                &manifest,
+                temp.path(),
+                &out,
+                &options,
+                previous,
+                changed,
+            )
+            .unwrap();
+            (result, std::fs::read(&out).unwrap())
+        };
+
+        let (full, full_bytes) = compile_to("full.bca", None, &[]);
+        assert_eq!(full.reparsed_specs(), &[users.clone(), orders.clone()]);
+
+        // No-op incremental: nothing re-parsed, identical artifact.
+        let (noop, noop_bytes) = compile_to("noop.bca", Some(&full), &[]);
+        assert!(noop.reparsed_specs().is_empty());
+        assert_eq!(noop_bytes, full_bytes);
+
+        // One spec edited: only that one is re-parsed, and the result matches
+        // a full compile of the new tree.
+        std::fs::write(&orders, spec("/invoices")).unwrap();
+        let (incremental, incremental_bytes) =
+            compile_to("incremental.bca", Some(&noop), &[orders.as_path()]);
+        assert_eq!(incremental.reparsed_specs(), std::slice::from_ref(&orders));
+        let (_, rebuilt_bytes) = compile_to("rebuilt.bca", None, &[]);
+        assert_eq!(incremental_bytes, rebuilt_bytes);
+        assert_ne!(incremental_bytes, full_bytes);
+
+        // A hash mismatch re-parses even when the caller didn't report it.
+        std::fs::write(&users, spec("/people")).unwrap();
+        let (unreported, _) = compile_to("unreported.bca", Some(&incremental), &[]);
+        assert_eq!(unreported.reparsed_specs(), std::slice::from_ref(&users));
+
+        // A changed non-spec file (e.g. a $ref target) re-parses everything.
+        let shared = temp.path().join("schemas.yaml");
+        let (all, _) = compile_to("all.bca", Some(&unreported), &[shared.as_path()]);
+        assert_eq!(all.reparsed_specs().len(), 2);
+    }
+
+    /// A project with a `mock` dispatcher and a `cors` middleware, each with
+    /// its plugin.toml, compiling `spec`.
+    fn compile_with_roles(spec: &str) -> Result<CompileResult, CompileError> {
+        let temp = TempDir::new().unwrap();
+        let manifest = write_test_plugins(
+            temp.path(),
+            &[("mock", "dispatcher"), ("cors", "middleware")],
+        );
+        let spec_path = create_test_spec(temp.path(), "api.yaml", spec);
+        compile_with_manifest(
+            &[spec_path.as_path()],
+            &manifest,
+            temp.path(),
+            &temp.path().join("artifact.bca"),
+            &CompileOptions::default(),
+        )
+    }
+
+    #[test]
+    fn plugin_roles_matching_slots_compile() {
+        let spec = r#"
+openapi: "3.1.0"
+info:
+  title: API
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      x-barbacane-middlewares:
+        - name: cors
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        compile_with_roles(spec).unwrap();
+    }
+
+    #[test]
+    fn second_dispatcher_in_middleware_chain_is_rejected() {
+        let spec = r#"
+openapi: "3.1.0"
+info:
+  title: API
+  version: "1.0.0"
+x-barbacane-middlewares:
+  - name: mock@1.0.0
+paths:
+  /users:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let err = compile_with_roles(spec).unwrap_err();
+        assert_eq!(err.code(), DiagnosticCode::E1024);
+        match err {
+            CompileError::InvalidPluginRole {
+                operation,
+                plugin,
+                expected,
+                found,
+            } => {
+                assert_eq!(operation, "GET /users in 'api.yaml'");
+                assert_eq!(plugin, "mock@1.0.0");
+                assert_eq!(expected, "middleware");
+                assert_eq!(found, "dispatcher");
+            }
+            other => panic!("expected InvalidPluginRole, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn middleware_as_dispatcher_is_rejected() {
+        let spec = r#"
+openapi: "3.1.0"
+info:
+  title: API
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      x-barbacane-dispatch:
+        name: cors
+"#;
+        let err = compile_with_roles(spec).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "E1024: plugin 'cors' on GET /users in 'api.yaml' is a middleware, but is used as a dispatcher"
+        );
+    }
+
+    const STREAMING_SPEC: &str = r#"
+openapi: "3.1.0"
+info:
+  title: Streaming API
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      operationId: listUsers
+      x-barbacane-dispatch:
+        name: mock
+  /users/{id}:
+    get:
+      operationId: getUser
+      x-barbacane-dispatch:
+        name: mock
+"#;
+
+    #[test]
+    fn compile_to_writer_produces_loadable_archive() {
+        let temp = TempDir::new().unwrap();
+        let spec_path = create_test_spec(temp.path(), "api.yaml", STREAMING_SPEC);
+        let plugins = vec![PluginBundle {
+            name: "mock".to_string(),
+            version: "1.0.0".to_string(),
+            plugin_type: "dispatcher".to_string(),
+            wasm_bytes: TEST_WASM.to_vec(),
+            body_access: false,
+            host_functions: vec![],
+            secret_fields: vec![],
+        }];
+
+        let mut buf: Vec<u8> = Vec::new();
+        let report = compile_to_writer(
+            &[spec_path.as_path()],
+            &plugins,
+            &CompileOptions::default(),
+            &mut buf,
+        )
+        .unwrap();
+
+        assert_eq!(report.specs_count, 1);
+        assert_eq!(report.routes_count, 2);
+        assert_eq!(report.plugins_count, 1);
+        assert!(report.warnings.is_empty());
+        assert_eq!(
+            report.artifact_hash,
+            format!("sha256:{}", compute_sha256(&buf))
+        );
+
+        let artifact_path = temp.path().join("streamed.bca");
+        std::fs::write(&artifact_path, &buf).unwrap();
+        let routes = load_routes(&artifact_path).unwrap();
+        assert_eq!(routes.operations.len(), 2);
+        assert_eq!(routes.operations[0].path, "/users");
+        assert_eq!(load_plugins(&artifact_path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn compile_to_writer_failures_leave_no_loadable_archive() {
+        /// Accepts `limit` bytes, then fails every write.
+        struct FailingWriter {
+            written: Vec<u8>,
+            limit: usize,
+        }
+
+        impl Write for FailingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.written.len() >= self.limit {
+                    return Err(std::io::Error::other("disk full"));
+                }
+                let n = buf.len().min(self.limit - self.written.len());
+                self.written.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let temp = TempDir::new().unwrap();
+
+        // Validation errors write nothing
+        let bad_spec = create_test_spec(
+            temp.path(),
+            "bad.yaml",
+            "openapi: \"3.1.0\"\ninfo:\n  title: T\n  version: \"1\"\npaths:\n  /x:\n    get: {}\n",
+        );
+        let mut buf: Vec<u8> = Vec::new();
+        let result = compile_to_writer(
+            &[bad_spec.as_path()],
+            &[],
+            &CompileOptions::default(),
+            &mut buf,
+        );
+        assert!(matches!(result, Err(CompileError::MissingDispatch(_))));
+        assert!(buf.is_empty());
+
+        // A write failure mid-stream surfaces as an error, and the bytes that
+        // made it out are not a loadable archive
+        let spec_path = create_test_spec(temp.path(), "api.yaml", STREAMING_SPEC);
+        let mut sink = FailingWriter {
+            written: Vec::new(),
+            limit: 256,
+        };
+        let result = compile_to_writer(
+            &[spec_path.as_path()],
+            &[],
+            &CompileOptions::default(),
+            &mut sink,
+        );
+        assert!(matches!(result, Err(CompileError::Io(_))));
+        assert!(!sink.written.is_empty());
+
+        let partial_path = temp.path().join("partial.bca");
+        std::fs::write(&partial_path, &sink.written).unwrap();
+        assert!(load_routes(&partial_path).is_err());
+    }
+
     #[test]
     fn artifact_hash_differs_with_different_specs() {
         let specs_a = vec![SourceSpec {
@@ -2945,6 +5715,148 @@ paths:
         );
     }
 
+    #[test]
+    fn config_fingerprint_changes_only_for_the_edited_route() {
+        let temp = TempDir::new().unwrap();
+
+        let spec = |limit: u32| {
+            format!(
+                r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+x-barbacane-middlewares:
+  - name: cors
+    config:
+      allowed_origins: ["*"]
+paths:
+  /health:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+        config:
+          status: 200
+  /users:
+    get:
+      x-barbacane-middlewares:
+        - name: rate-limit
+          config:
+            quota: {limit}
+            window: 60
+      x-barbacane-dispatch:
+        name: mock
+        config:
+          status: 200
+"#
+            )
+        };
+        let dir_a = temp.path().join("a");
+        let dir_b = temp.path().join("b");
+        std::fs::create_dir_all(&dir_a).unwrap();
+        std::fs::create_dir_all(&dir_b).unwrap();
+        let spec_a = create_test_spec(&dir_a, "api.yaml", &spec(100));
+        let spec_b = create_test_spec(&dir_b, "api.yaml", &spec(200));
+        let out_a = temp.path().join("a.bca");
+        let out_b = temp.path().join("b.bca");
+        compile(&[spec_a.as_path()], &[], &out_a, &CompileOptions::default()).unwrap();
+        compile(&[spec_b.as_path()], &[], &out_b, &CompileOptions::default()).unwrap();
+
+        let routes_a = load_routes(&out_a).unwrap();
+        let routes_b = load_routes(&out_b).unwrap();
+        let fingerprint = |routes: &CompiledRoutes, path: &str| {
+            routes
+                .operations
+                .iter()
+                .find(|op| op.path == path)
+                .map(|op| op.config_fingerprint.clone())
+                .unwrap()
+        };
+
+        assert_eq!(fingerprint(&routes_a, "/health").len(), 64);
+        assert_eq!(
+            fingerprint(&routes_a, "/health"),
+            fingerprint(&routes_b, "/health")
+        );
+        assert_ne!(
+            fingerprint(&routes_a, "/users"),
+            fingerprint(&routes_b, "/users")
+        );
+
+        let diff = diff_artifacts(&out_a, &out_b).unwrap();
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.config_changed, vec!["GET /users".to_string()]);
+
+        assert!(diff_artifacts(&out_a, &out_a).unwrap().is_empty());
+    }
+
+    #[test]
+    fn config_fingerprint_ignores_config_key_order() {
+        let dispatch = |config: serde_json::Value| DispatchConfig {
+            name: "http-upstream".to_string(),
+            config,
+        };
+        let a: serde_json::Value =
+            serde_json::from_str(r#"{"url": "https://a", "timeout": 5}"#).unwrap();
+        let b: serde_json::Value =
+            serde_json::from_str(r#"{"timeout": 5, "url": "https://a"}"#).unwrap();
+        assert_eq!(
+            compute_config_fingerprint(&dispatch(a), &[]).unwrap(),
+            compute_config_fingerprint(&dispatch(b), &[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn diff_routes_reports_added_and_removed_routes() {
+        let op = |method: &str, path: &str| CompiledOperation {
+            index: 0,
+            path: path.to_string(),
+            method: method.to_string(),
+            operation_id: None,
+            operation_id_synthesized: false,
+            summary: None,
+            description: None,
+            parameters: Vec::new(),
+            request_body: None,
+            dispatch: DispatchConfig {
+                name: "mock".to_string(),
+                config: serde_json::Value::Null,
+            },
+            middlewares: Vec::new(),
+            deprecated: false,
+            sunset: None,
+            timeout_ms: None,
+            messages: Vec::new(),
+            bindings: BTreeMap::new(),
+            responses: BTreeMap::new(),
+            mcp_enabled: None,
+            mcp_description: None,
+            accepted_content_types: Vec::new(),
+            config_fingerprint: "f".repeat(64),
+        };
+        let old = CompiledRoutes {
+            operations: vec![op("GET", "/a"), op("GET", "/b")],
+        };
+        let new = CompiledRoutes {
+            operations: vec![op("GET", "/b"), op("POST", "/c")],
+        };
+
+        let diff = diff_routes(&old, &new);
+        assert_eq!(diff.added, vec!["POST /c".to_string()]);
+        assert_eq!(diff.removed, vec!["GET /a".to_string()]);
+        assert!(diff.config_changed.is_empty());
+
+        // A route without a fingerprint (pre-fingerprint artifact) is always
+        // treated as changed.
+        let mut legacy = old.clone();
+        legacy.operations[1].config_fingerprint = String::new();
+        assert_eq!(
+            diff_routes(&legacy, &new).config_changed,
+            vec!["GET /b".to_string()]
+        );
+    }
+
     #[test]
     fn different_specs_produce_different_artifact_hashes() {
         let temp = TempDir::new().unwrap();
@@ -1,15 +1,170 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Machine-readable code carried by every compile error and warning.
+///
+/// Serializes as the code string (e.g. `"E1015"`, `"manifest"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DiagnosticCode {
+    E1001,
+    E1002,
+    E1003,
+    E1004,
+    E1010,
+    E1011,
+    E1015,
+    E1016,
+    E1017,
+    E1018,
+    E1019,
+    E1020,
+    E1024,
+    E1025,
+    E1031,
+    E1040,
+    E1041,
+    E1042,
+    E1043,
+    E1044,
+    E1050,
+    E1051,
+    E1052,
+    E1054,
+    E1055,
+    E1056,
+    E1057,
+    E1060,
+    E1061,
+    E1070,
+    #[serde(rename = "manifest")]
+    Manifest,
+    #[serde(rename = "plugin_resolution")]
+    PluginResolution,
+    #[serde(rename = "io")]
+    Io,
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "signing")]
+    Signing,
+    #[serde(rename = "warnings_denied")]
+    WarningsDenied,
+}
+
+impl DiagnosticCode {
+    /// The code as it appears in messages and JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::E1001 => "E1001",
+            DiagnosticCode::E1002 => "E1002",
+            DiagnosticCode::E1003 => "E1003",
+            DiagnosticCode::E1004 => "E1004",
+            DiagnosticCode::E1010 => "E1010",
+            DiagnosticCode::E1011 => "E1011",
+            DiagnosticCode::E1015 => "E1015",
+            DiagnosticCode::E1016 => "E1016",
+            DiagnosticCode::E1017 => "E1017",
+            DiagnosticCode::E1018 => "E1018",
+            DiagnosticCode::E1019 => "E1019",
+            DiagnosticCode::E1020 => "E1020",
+            DiagnosticCode::E1024 => "E1024",
+            DiagnosticCode::E1025 => "E1025",
+            DiagnosticCode::E1031 => "E1031",
+            DiagnosticCode::E1040 => "E1040",
+            DiagnosticCode::E1041 => "E1041",
+            DiagnosticCode::E1042 => "E1042",
+            DiagnosticCode::E1043 => "E1043",
+            DiagnosticCode::E1044 => "E1044",
+            DiagnosticCode::E1050 => "E1050",
+            DiagnosticCode::E1051 => "E1051",
+            DiagnosticCode::E1052 => "E1052",
+            DiagnosticCode::E1054 => "E1054",
+            DiagnosticCode::E1055 => "E1055",
+            DiagnosticCode::E1056 => "E1056",
+            DiagnosticCode::E1057 => "E1057",
+            DiagnosticCode::E1060 => "E1060",
+            DiagnosticCode::E1061 => "E1061",
+            DiagnosticCode::E1070 => "E1070",
+            DiagnosticCode::Manifest => "manifest",
+            DiagnosticCode::PluginResolution => "plugin_resolution",
+            DiagnosticCode::Io => "io",
+            DiagnosticCode::Json => "json",
+            DiagnosticCode::Signing => "signing",
+            DiagnosticCode::WarningsDenied => "warnings_denied",
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// A warning produced during compilation (non-blocking).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompileWarning {
-    /// Warning code (e.g., "E1015").
-    pub code: String,
+    /// Warning code (e.g., `E1015`).
+    pub code: DiagnosticCode,
     /// Warning message.
     pub message: String,
     /// Location in the spec (e.g., "GET /users in 'api.yaml'").
     pub location: Option<String>,
+    /// Spec file the warning refers to, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    /// JSON Pointer into `spec`, when known (e.g., "/paths/~1users/get").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+}
+
+impl CompileWarning {
+    /// This warning as a structured diagnostic.
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            code: self.code,
+            message: self.message.clone(),
+            location: self.spec.as_ref().map(|spec| DiagnosticLocation {
+                spec: spec.clone(),
+                pointer: self.pointer.clone(),
+            }),
+        }
+    }
+}
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Where in the sources a [`Diagnostic`] applies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticLocation {
+    /// Spec file path.
+    pub spec: String,
+    /// JSON Pointer into the spec, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+}
+
+/// A compile error or warning in a stable, machine-readable shape, for
+/// tooling that embeds the compiler.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<DiagnosticLocation>,
+}
+
+/// Serialize diagnostics as a JSON array.
+pub(crate) fn diagnostics_to_json(diagnostics: &[Diagnostic]) -> String {
+    // A Vec of plain structs with string keys cannot fail to serialize.
+    serde_json::to_string(diagnostics).unwrap_or_else(|_| "[]".to_string())
 }
 
 /// Errors produced during compilation.
@@ -27,6 +182,24 @@ pub enum CompileError {
     #[error("E1020: operation has no x-barbacane-dispatch: {0}")]
     MissingDispatch(String),
 
+    /// E1024: Plugin used in a slot its declared type does not fill.
+    #[error("E1024: plugin '{plugin}' on {operation} is a {found}, but is used as a {expected}")]
+    InvalidPluginRole {
+        operation: String,
+        plugin: String,
+        expected: String,
+        found: String,
+    },
+
+    /// E1025: Plugin config references an environment variable the caller
+    /// did not supply in `CompileOptions::env`.
+    #[error("E1025: plugin '{plugin}' config on {operation} references unset environment variable '{var}'")]
+    MissingEnvVar {
+        var: String,
+        plugin: String,
+        operation: String,
+    },
+
     /// E1031: Plaintext HTTP upstream URL in production mode.
     #[error("E1031: plaintext HTTP upstream URL not allowed in production: {0}")]
     PlaintextUpstream(String),
@@ -35,6 +208,24 @@ pub enum CompileError {
     #[error("E1040: plugin '{0}' used in spec but not declared in barbacane.yaml")]
     UndeclaredPlugin(String),
 
+    /// E1042: Plugin does not report the version pinned in the manifest.
+    #[error(
+        "E1042: plugin '{name}' version mismatch: barbacane.yaml requests {requested}, plugin reports {found}"
+    )]
+    PluginVersionMismatch {
+        name: String,
+        requested: String,
+        found: String,
+    },
+
+    /// E1043: Downloaded plugin does not match the `sha256` pinned in the manifest.
+    #[error("E1043: plugin '{name}' checksum mismatch: expected sha256 {expected}, got {actual}")]
+    PluginChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+
     /// E1011: Middleware entry missing required 'name' field.
     #[error("E1011: middleware missing 'name': {0}")]
     MissingMiddlewareName(String),
@@ -55,9 +246,18 @@ pub enum CompileError {
     #[error("E1054: invalid path template: {0}")]
     InvalidPathTemplate(String),
 
-    /// E1055: Duplicate operationId across specs.
-    #[error("E1055: duplicate operationId '{0}': {1}")]
-    DuplicateOperationId(String, String),
+    /// E1055: Duplicate operationId across specs. `locations` lists every
+    /// operation declaring `id`, in spec order.
+    #[error("E1055: duplicate operationId '{id}': declared by {}", .locations.join(", "))]
+    DuplicateOperationId { id: String, locations: Vec<String> },
+
+    /// E1056: `x-barbacane-accept` is not a non-empty list of media types.
+    #[error("E1056: invalid x-barbacane-accept: {0}")]
+    InvalidAcceptList(String),
+
+    /// E1057: `only_operations` names an operationId no spec declares.
+    #[error("E1057: unknown operationId in only_operations: {0}")]
+    UnknownOperationId(String),
 
     /// Manifest parsing or loading error.
     #[error("manifest error: {0}")]
@@ -78,4 +278,77 @@ pub enum CompileError {
     /// Artifact signing failed (bad/missing signing key).
     #[error("artifact signing error: {0}")]
     Signing(String),
+
+    /// Warnings were emitted while `deny_warnings` is set.
+    #[error("{count} warning(s) denied by deny_warnings")]
+    WarningsDenied {
+        count: usize,
+        warnings: Vec<CompileWarning>,
+    },
+}
+
+impl CompileError {
+    /// Machine-readable code for this error.
+    pub fn code(&self) -> DiagnosticCode {
+        use crate::spec_parser::ParseError;
+        match self {
+            CompileError::Parse(ParseError::UnknownFormat) => DiagnosticCode::E1001,
+            CompileError::Parse(ParseError::ParseError(_)) => DiagnosticCode::E1002,
+            CompileError::Parse(ParseError::UnresolvedRef(_)) => DiagnosticCode::E1003,
+            CompileError::Parse(ParseError::SchemaError(_)) => DiagnosticCode::E1004,
+            CompileError::Parse(ParseError::Io(_)) => DiagnosticCode::Io,
+            CompileError::RoutingConflict(_) => DiagnosticCode::E1010,
+            CompileError::MissingDispatch(_) => DiagnosticCode::E1020,
+            CompileError::InvalidPluginRole { .. } => DiagnosticCode::E1024,
+            CompileError::MissingEnvVar { .. } => DiagnosticCode::E1025,
+            CompileError::PlaintextUpstream(_) => DiagnosticCode::E1031,
+            CompileError::UndeclaredPlugin(_) => DiagnosticCode::E1040,
+            CompileError::PluginVersionMismatch { .. } => DiagnosticCode::E1042,
+            CompileError::PluginChecksumMismatch { .. } => DiagnosticCode::E1043,
+            CompileError::MissingMiddlewareName(_) => DiagnosticCode::E1011,
+            CompileError::AmbiguousRoute(_) => DiagnosticCode::E1050,
+            CompileError::SchemaTooDeep(_) => DiagnosticCode::E1051,
+            CompileError::SchemaTooComplex(_) => DiagnosticCode::E1052,
+            CompileError::InvalidPathTemplate(_) => DiagnosticCode::E1054,
+            CompileError::DuplicateOperationId { .. } => DiagnosticCode::E1055,
+            CompileError::InvalidAcceptList(_) => DiagnosticCode::E1056,
+            CompileError::UnknownOperationId(_) => DiagnosticCode::E1057,
+            CompileError::ManifestError(_) => DiagnosticCode::Manifest,
+            CompileError::PluginResolution(_) => DiagnosticCode::PluginResolution,
+            CompileError::Io(_) => DiagnosticCode::Io,
+            CompileError::Json(_) => DiagnosticCode::Json,
+            CompileError::Signing(_) => DiagnosticCode::Signing,
+            CompileError::WarningsDenied { .. } => DiagnosticCode::WarningsDenied,
+        }
+    }
+
+    /// This error as structured diagnostics. [`CompileError::WarningsDenied`]
+    /// is followed by the denied warnings.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let code = self.code();
+        let text = self.to_string();
+        // Display prefixes the code ("E1010: ..."); the JSON has it separately.
+        let message = text
+            .strip_prefix(code.as_str())
+            .and_then(|rest| rest.strip_prefix(": "))
+            .unwrap_or(&text)
+            .to_string();
+
+        let mut diagnostics = vec![Diagnostic {
+            severity: Severity::Error,
+            code,
+            message,
+            location: None,
+        }];
+        if let CompileError::WarningsDenied { warnings, .. } = self {
+            diagnostics.extend(warnings.iter().map(CompileWarning::diagnostic));
+        }
+        diagnostics
+    }
+
+    /// [`diagnostics`](Self::diagnostics) as a JSON array of
+    /// `{severity, code, message, location?}` objects.
+    pub fn diagnostics_json(&self) -> String {
+        diagnostics_to_json(&self.diagnostics())
+    }
 }
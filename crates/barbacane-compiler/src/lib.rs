@@ -11,19 +11,23 @@ pub mod manifest;
 pub mod spec_parser;
 
 pub use artifact::{
-    compile, compile_with_manifest, load_manifest, load_plugins, load_routes, load_specs,
-    recompute_artifact_hash, verify_artifact_hash, verify_artifact_signature,
-    verify_plugin_checksum, BundledPlugin, CompileOptions, CompileResult, CompiledOperation,
-    CompiledRoutes, IntegrityError, LoadedPlugin, Manifest, McpConfig, PluginBundle,
-    PluginCapabilities, Provenance, SourceSpec, ARTIFACT_VERSION, COMPILER_VERSION,
+    compile, compile_to_writer, compile_with_manifest, diff_artifacts, diff_routes, load_manifest,
+    load_plugins, load_routes, load_specs, load_specs_from_bytes, recompute_artifact_hash,
+    stream_routes, stream_routes_from_reader, verify_artifact_hash, verify_artifact_signature,
+    verify_plugin_checksum, BundledPlugin, CompileOptions, CompileReport, CompileResult,
+    CompiledOperation, CompiledRoutes, IntegrityError, LoadedPlugin, Manifest, McpConfig,
+    PluginBundle, PluginCapabilities, Provenance, RouteSummary, RoutesDiff, SourceSpec,
+    ARTIFACT_VERSION, COMPILER_VERSION,
+};
+pub use error::{
+    CompileError, CompileWarning, Diagnostic, DiagnosticCode, DiagnosticLocation, Severity,
 };
-pub use error::{CompileError, CompileWarning};
 pub use manifest::{
     extract_plugin_names, PathSource, PluginSource, ProjectManifest, ResolvedPlugin, UrlSource,
 };
 // Re-export spec-parser types for convenience
 pub use spec_parser::{
-    parse_spec, parse_spec_file, ApiSpec, AsyncAction, Channel, ContentSchema, DispatchConfig,
-    Message, MiddlewareConfig, Operation, Parameter, ParseError, RequestBody, ResponseContent,
-    SpecFormat,
+    parse_spec, parse_spec_file, parse_spec_with_base_dir, ApiSpec, AsyncAction, Channel,
+    ContentSchema, DispatchConfig, Message, MiddlewareConfig, Operation, Parameter, ParseError,
+    RequestBody, ResponseContent, SpecFormat,
 };
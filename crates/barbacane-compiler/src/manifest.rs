@@ -10,7 +10,7 @@ use sha2::{Digest, Sha256};
 
 use crate::cache::PluginCache;
 use crate::download;
-use crate::error::CompileError;
+use crate::error::{CompileError, CompileWarning, DiagnosticCode};
 use crate::spec_parser::ApiSpec;
 use serde::{Deserialize, Serialize};
 
@@ -144,6 +144,31 @@ fn resolve_wasm_path(path_source: &PathSource, base_path: &Path) -> std::path::P
     }
 }
 
+/// Whether a `path:` source is a glob pattern rather than a single file.
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?'])
+}
+
+/// Expand a glob `path:` source (relative to `base_path` unless absolute) to
+/// the sorted list of matching files.
+fn expand_glob(pattern: &str, base_path: &Path) -> Vec<std::path::PathBuf> {
+    let pattern = if Path::new(pattern).is_absolute() {
+        pattern.to_string()
+    } else {
+        let base = glob::Pattern::escape(&base_path.to_string_lossy());
+        format!("{}/{}", base.trim_end_matches('/'), pattern)
+    };
+    let Ok(paths) = glob::glob(&pattern) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<_> = paths
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .collect();
+    matches.sort();
+    matches
+}
+
 /// Resolve a single plugin: read WASM bytes, validate, and extract metadata.
 fn resolve_plugin(
     name: &str,
@@ -208,6 +233,18 @@ fn resolve_plugin(
         );
     }
 
+    // A pinned version must match what the plugin reports about itself.
+    if let Some(requested) = source.requested_version() {
+        let found = metadata.as_ref().map(|m| m.version.as_str());
+        if found != Some(requested) {
+            return Err(CompileError::PluginVersionMismatch {
+                name: name.to_string(),
+                requested: requested.to_string(),
+                found: found.unwrap_or("unknown").to_string(),
+            });
+        }
+    }
+
     // Secret (writeOnly) config fields from config-schema.json (path plugins
     // only; URL plugins do not fetch the schema).
     let secret_fields = match source {
@@ -260,12 +297,7 @@ fn resolve_url_plugin(
 
     // Verify checksum if provided
     if let Some(expected) = &url_source.sha256 {
-        let actual = hex::encode(Sha256::digest(&downloaded.wasm_bytes));
-        if actual != *expected {
-            return Err(CompileError::PluginResolution(format!(
-                "plugin '{name}' checksum mismatch: expected {expected}, got {actual}"
-            )));
-        }
+        verify_checksum(name, expected, &downloaded.wasm_bytes)?;
     }
 
     // Store in cache (unless --no-cache)
@@ -282,6 +314,25 @@ fn resolve_url_plugin(
     Ok((downloaded.wasm_bytes, downloaded.plugin_toml))
 }
 
+/// Check downloaded plugin bytes against the manifest's `sha256` pin (E1043).
+fn verify_checksum(name: &str, expected: &str, wasm_bytes: &[u8]) -> Result<(), CompileError> {
+    let actual = hex::encode(Sha256::digest(wasm_bytes));
+    if actual != expected {
+        return Err(CompileError::PluginChecksumMismatch {
+            name: name.to_string(),
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Schema version of `barbacane.yaml` this compiler reads. A manifest without
+/// a top-level `version` is taken to be this version; when the schema changes,
+/// older versions are upgraded in [`ProjectManifest::parse`] and newer ones
+/// are rejected.
+pub const PROJECT_MANIFEST_VERSION: u64 = 1;
+
 /// A project manifest (`barbacane.yaml`).
 ///
 /// Declares the plugins available for use in OpenAPI specs.
@@ -313,6 +364,9 @@ pub enum PluginSource {
 pub struct PathSource {
     /// Path to the .wasm file (relative to manifest or absolute).
     pub path: String,
+    /// Version the plugin must report (see [`PluginSource::requested_version`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 /// Plugin sourced from a remote URL.
@@ -323,6 +377,9 @@ pub struct UrlSource {
     /// Optional SHA-256 checksum for integrity verification.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sha256: Option<String>,
+    /// Version the plugin must report (see [`PluginSource::requested_version`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 impl PluginSource {
@@ -333,6 +390,16 @@ impl PluginSource {
             PluginSource::Url(u) => format!("url: {}", u.url),
         }
     }
+
+    /// The pinned version, checked against the version in the plugin's
+    /// `plugin.toml` at resolution. `None` for an absent or `"*"` version.
+    pub fn requested_version(&self) -> Option<&str> {
+        let version = match self {
+            PluginSource::Path(p) => p.version.as_deref(),
+            PluginSource::Url(u) => u.version.as_deref(),
+        };
+        version.filter(|v| *v != "*")
+    }
 }
 
 /// A resolved plugin ready for bundling.
@@ -367,10 +434,36 @@ impl ProjectManifest {
     }
 
     /// Parse a manifest from YAML content.
+    ///
+    /// The top-level `version` is checked first, so a manifest written for a
+    /// newer compiler fails with a clear error rather than a shape mismatch.
     pub fn parse(content: &str, path: &Path) -> Result<Self, CompileError> {
-        serde_yaml::from_str(content).map_err(|e| {
+        let parse_error = |e: serde_yaml::Error| {
             CompileError::ManifestError(format!("failed to parse {}: {}", path.display(), e))
-        })
+        };
+        let document: serde_yaml::Value = serde_yaml::from_str(content).map_err(parse_error)?;
+        if let Some(version) = document.get("version") {
+            match version.as_u64() {
+                Some(v) if (1..=PROJECT_MANIFEST_VERSION).contains(&v) => {}
+                Some(v) if v > PROJECT_MANIFEST_VERSION => {
+                    return Err(CompileError::ManifestError(format!(
+                        "{} declares manifest version {}, but this compiler reads up to version {}; upgrade barbacane",
+                        path.display(),
+                        v,
+                        PROJECT_MANIFEST_VERSION
+                    )));
+                }
+                _ => {
+                    return Err(CompileError::ManifestError(format!(
+                        "{}: unsupported manifest version {:?}; expected an integer from 1 to {}",
+                        path.display(),
+                        version,
+                        PROJECT_MANIFEST_VERSION
+                    )));
+                }
+            }
+        }
+        serde_yaml::from_str(content).map_err(parse_error)
     }
 
     /// Discover spec files from the `specs` folder.
@@ -445,19 +538,99 @@ impl ProjectManifest {
         self.plugins.keys().map(|s| s.as_str()).collect()
     }
 
-    /// Get resolved paths for all local (`path:`) plugin WASM files.
+    /// Get resolved paths for all local (`path:`) plugin WASM files, with
+    /// glob sources expanded to their current matches.
     ///
     /// Useful for file watching in dev mode — only local plugins can change.
     pub fn local_plugin_paths(&self, base_path: &Path) -> Vec<std::path::PathBuf> {
         self.plugins
             .values()
-            .filter_map(|source| match source {
-                PluginSource::Path(p) => Some(resolve_wasm_path(p, base_path)),
-                PluginSource::Url(_) => None,
+            .flat_map(|source| match source {
+                PluginSource::Path(p) if is_glob(&p.path) => expand_glob(&p.path, base_path),
+                PluginSource::Path(p) => vec![resolve_wasm_path(p, base_path)],
+                PluginSource::Url(_) => Vec::new(),
             })
             .collect()
     }
 
+    /// Warnings (E1044) for the named URL plugins that have no `sha256` pin,
+    /// so their downloads are bundled unverified.
+    pub fn unverified_plugin_warnings<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<CompileWarning> {
+        let mut warnings: Vec<CompileWarning> = names
+            .into_iter()
+            .filter_map(|name| match self.plugins.get(name) {
+                Some(PluginSource::Url(u)) if u.sha256.is_none() => Some(CompileWarning {
+                    code: DiagnosticCode::E1044,
+                    message: format!(
+                        "plugin '{}' is downloaded from {} without a sha256 checksum",
+                        name, u.url
+                    ),
+                    location: Some(format!("plugins/{}.wasm", name)),
+                    spec: None,
+                    pointer: None,
+                }),
+                _ => None,
+            })
+            .collect();
+        warnings.sort_by(|a, b| a.location.cmp(&b.location));
+        warnings
+    }
+
+    /// Replace each glob `path:` source (e.g. `plugins/*/*.wasm`) with one
+    /// source per matching file, named after the file stem
+    /// (`plugins/cors/cors.wasm` -> `cors`). The glob entry's own name is only
+    /// a label. Fails if a glob matches nothing or a derived name is already
+    /// declared.
+    pub fn expand_plugin_globs(&self, base_path: &Path) -> Result<Self, CompileError> {
+        let mut plugins = HashMap::with_capacity(self.plugins.len());
+        let mut globs = Vec::new();
+        for (name, source) in &self.plugins {
+            match source {
+                PluginSource::Path(p) if is_glob(&p.path) => globs.push((name, p)),
+                _ => {
+                    plugins.insert(name.clone(), source.clone());
+                }
+            }
+        }
+        // Deterministic conflict reporting regardless of map order.
+        globs.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (label, glob) in globs {
+            let matches = expand_glob(&glob.path, base_path);
+            if matches.is_empty() {
+                return Err(CompileError::ManifestError(format!(
+                    "plugin glob '{}' ({}) matched no files",
+                    label, glob.path
+                )));
+            }
+            for path in matches {
+                let name = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                let source = PluginSource::Path(PathSource {
+                    path: path.to_string_lossy().into_owned(),
+                    version: glob.version.clone(),
+                });
+                if plugins.insert(name.clone(), source).is_some() {
+                    return Err(CompileError::ManifestError(format!(
+                        "plugin '{}' from glob '{}' ({}) is declared more than once",
+                        name, label, glob.path
+                    )));
+                }
+            }
+        }
+
+        Ok(Self {
+            plugins,
+            specs: self.specs.clone(),
+        })
+    }
+
     /// Resolve all plugins: load WASM bytes from their sources.
     ///
     /// The `base_path` is used to resolve relative paths in `path:` sources.
@@ -467,7 +640,8 @@ impl ProjectManifest {
         base_path: &Path,
         no_cache: bool,
     ) -> Result<Vec<ResolvedPlugin>, CompileError> {
-        self.plugins
+        self.expand_plugin_globs(base_path)?
+            .plugins
             .iter()
             .map(|(name, source)| resolve_plugin(name, source, base_path, no_cache))
             .collect()
@@ -502,13 +676,14 @@ impl ProjectManifest {
         base_path: &Path,
         no_cache: bool,
     ) -> Result<Vec<ResolvedPlugin>, CompileError> {
-        self.validate_specs(specs)?;
+        let manifest = self.expand_plugin_globs(base_path)?;
+        manifest.validate_specs(specs)?;
 
         let used = extract_plugin_names(specs);
         let mut resolved = Vec::new();
 
         for name in used {
-            let source = match self.plugins.get(&name) {
+            let source = match manifest.plugins.get(&name) {
                 Some(s) => s,
                 None => continue, // Already validated, shouldn't happen
             };
@@ -562,6 +737,100 @@ pub(crate) fn normalize_plugin_name(name: &str) -> String {
     }
 }
 
+/// Check a manifest without compiling anything: every plugin source can be
+/// located (local files exist, globs match, URLs are well-formed HTTPS),
+/// names are present and unique, and checksums are well-formed.
+///
+/// Nothing is downloaded or read beyond file existence. Unlike resolution,
+/// every problem is collected instead of stopping at the first.
+pub fn validate(manifest: &ProjectManifest, base_path: &Path) -> Result<(), Vec<CompileError>> {
+    let mut errors = Vec::new();
+    // Normalized plugin name -> manifest entry that declared it.
+    let mut seen: HashMap<String, String> = HashMap::new();
+    let mut claim = |name: String, entry: &str, errors: &mut Vec<CompileError>| {
+        if let Some(previous) = seen.insert(name.clone(), entry.to_string()) {
+            errors.push(CompileError::ManifestError(format!(
+                "plugin '{}' is declared by both '{}' and '{}'",
+                name, previous, entry
+            )));
+        }
+    };
+
+    let mut entries: Vec<_> = manifest.plugins.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, source) in entries {
+        if name.trim().is_empty() {
+            errors.push(CompileError::ManifestError(
+                "plugin name must not be empty".to_string(),
+            ));
+        }
+        match source {
+            PluginSource::Path(p) if p.path.trim().is_empty() => {
+                errors.push(CompileError::ManifestError(format!(
+                    "plugin '{}' has an empty path",
+                    name
+                )));
+            }
+            PluginSource::Path(p) if is_glob(&p.path) => {
+                let matches = expand_glob(&p.path, base_path);
+                if matches.is_empty() {
+                    errors.push(CompileError::ManifestError(format!(
+                        "plugin glob '{}' ({}) matched no files",
+                        name, p.path
+                    )));
+                }
+                for path in matches {
+                    let stem = path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or_default();
+                    claim(stem.to_string(), name, &mut errors);
+                }
+            }
+            PluginSource::Path(p) => {
+                let wasm_path = resolve_wasm_path(p, base_path);
+                if !wasm_path.is_file() {
+                    errors.push(CompileError::PluginResolution(format!(
+                        "plugin '{}' not found at {}",
+                        name,
+                        wasm_path.display()
+                    )));
+                }
+                claim(normalize_plugin_name(name), name, &mut errors);
+            }
+            PluginSource::Url(u) => {
+                match reqwest::Url::parse(&u.url) {
+                    Ok(url) if url.scheme() == "https" && url.host().is_some() => {}
+                    Ok(_) => errors.push(CompileError::PluginResolution(format!(
+                        "plugin '{}' URL must use HTTPS: {}",
+                        name, u.url
+                    ))),
+                    Err(e) => errors.push(CompileError::PluginResolution(format!(
+                        "plugin '{}' has an invalid URL '{}': {}",
+                        name, u.url, e
+                    ))),
+                }
+                if let Some(sha256) = &u.sha256 {
+                    if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        errors.push(CompileError::ManifestError(format!(
+                            "plugin '{}' sha256 must be 64 hex characters",
+                            name
+                        )));
+                    }
+                }
+                claim(normalize_plugin_name(name), name, &mut errors);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -700,11 +969,132 @@ plugins:
         }
     }
 
+    #[test]
+    fn checksum_matching_download_is_accepted() {
+        let bytes = b"\0asm\x01\0\0\0";
+        let expected = hex::encode(Sha256::digest(bytes));
+        assert!(verify_checksum("jwt-auth", &expected, bytes).is_ok());
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let err = verify_checksum("jwt-auth", "abc123", b"\0asm\x01\0\0\0").unwrap_err();
+        match &err {
+            CompileError::PluginChecksumMismatch {
+                name,
+                expected,
+                actual,
+            } => {
+                assert_eq!(name, "jwt-auth");
+                assert_eq!(expected, "abc123");
+                assert_eq!(actual.len(), 64);
+            }
+            other => panic!("expected PluginChecksumMismatch, got {other:?}"),
+        }
+        assert_eq!(err.code(), DiagnosticCode::E1043);
+    }
+
+    #[test]
+    fn url_plugins_without_checksum_warn() {
+        let content = r#"
+plugins:
+  jwt-auth:
+    url: https://plugins.barbacane.io/jwt-auth.wasm
+  cors:
+    url: https://plugins.barbacane.io/cors.wasm
+    sha256: abc123
+  mock:
+    path: ./mock.wasm
+"#;
+        let manifest = ProjectManifest::parse(content, Path::new("barbacane.yaml")).unwrap();
+
+        let warnings = manifest.unverified_plugin_warnings(["jwt-auth", "cors", "mock"]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, DiagnosticCode::E1044);
+        assert!(warnings[0].message.contains("jwt-auth"));
+
+        // Only plugins actually bundled are reported.
+        assert!(manifest.unverified_plugin_warnings(["cors"]).is_empty());
+    }
+
+    #[test]
+    fn validate_accepts_a_clean_manifest() {
+        let temp = TempDir::new().unwrap();
+        write_valid_wasm(temp.path(), "mock.wasm");
+        let content = format!(
+            r#"
+plugins:
+  mock:
+    path: ./mock.wasm
+  jwt-auth:
+    url: https://plugins.barbacane.io/jwt-auth.wasm
+    sha256: {}
+"#,
+            "a".repeat(64)
+        );
+        let manifest = ProjectManifest::parse(&content, Path::new("barbacane.yaml")).unwrap();
+
+        assert!(validate(&manifest, temp.path()).is_ok());
+    }
+
+    #[test]
+    fn validate_reports_duplicate_plugin_names() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("plugins")).unwrap();
+        write_valid_wasm(&temp.path().join("plugins"), "cors.wasm");
+        let content = r#"
+plugins:
+  cors:
+    url: https://plugins.barbacane.io/cors.wasm
+  local:
+    path: ./plugins/*.wasm
+"#;
+        let manifest = ProjectManifest::parse(content, Path::new("barbacane.yaml")).unwrap();
+
+        let errors = validate(&manifest, temp.path()).unwrap_err();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(
+            errors[0]
+                .to_string()
+                .contains("plugin 'cors' is declared by both 'cors' and 'local'"),
+            "{}",
+            errors[0]
+        );
+    }
+
+    #[test]
+    fn validate_collects_every_problem() {
+        let temp = TempDir::new().unwrap();
+        let content = r#"
+plugins:
+  missing:
+    path: ./missing.wasm
+  insecure:
+    url: http://plugins.barbacane.io/insecure.wasm
+  broken:
+    url: not a url
+    sha256: xyz
+"#;
+        let manifest = ProjectManifest::parse(content, Path::new("barbacane.yaml")).unwrap();
+
+        let errors: Vec<String> = validate(&manifest, temp.path())
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors[0].contains("'broken' has an invalid URL"));
+        assert!(errors[1].contains("'broken' sha256 must be 64 hex"));
+        assert!(errors[2].contains("'insecure' URL must use HTTPS"));
+        assert!(errors[3].contains("'missing' not found at"));
+    }
+
     #[test]
     fn reject_http_url_in_resolve() {
         let source = PluginSource::Url(UrlSource {
             url: "http://example.com/plugin.wasm".to_string(),
             sha256: None,
+            version: None,
         });
         let result = resolve_plugin("test", &source, Path::new("."), false);
         assert!(result.is_err());
@@ -871,6 +1261,7 @@ plugins:
                     middlewares: None,
                     deprecated: false,
                     sunset: None,
+                    timeout_ms: None,
                     extensions: BTreeMap::new(),
                     messages: vec![],
                     bindings: BTreeMap::new(),
@@ -894,6 +1285,7 @@ plugins:
                     }]),
                     deprecated: false,
                     sunset: None,
+                    timeout_ms: None,
                     extensions: BTreeMap::new(),
                     messages: vec![],
                     bindings: BTreeMap::new(),
@@ -941,6 +1333,7 @@ plugins:
                 middlewares: None,
                 deprecated: false,
                 sunset: None,
+                timeout_ms: None,
                 extensions: BTreeMap::new(),
                 messages: vec![],
                 bindings: BTreeMap::new(),
@@ -987,6 +1380,7 @@ plugins:
                 middlewares: None,
                 deprecated: false,
                 sunset: None,
+                timeout_ms: None,
                 extensions: BTreeMap::new(),
                 messages: vec![],
                 bindings: BTreeMap::new(),
@@ -1036,6 +1430,7 @@ plugins:
                 middlewares: None,
                 deprecated: false,
                 sunset: None,
+                timeout_ms: None,
                 extensions: BTreeMap::new(),
                 messages: vec![],
                 bindings: BTreeMap::new(),
@@ -1155,6 +1550,114 @@ plugins:
         assert!(result.unwrap_err().to_string().contains("E1040"));
     }
 
+    /// Resolve a `mock` plugin reporting version 1.2.3, pinned to `version`.
+    fn resolve_pinned(version: &str) -> Result<Vec<ResolvedPlugin>, CompileError> {
+        let temp = TempDir::new().unwrap();
+        let plugin_dir = temp.path().join("plugins");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        write_valid_wasm(&plugin_dir, "mock.wasm");
+        std::fs::write(
+            plugin_dir.join("plugin.toml"),
+            "[plugin]\nname = \"mock\"\nversion = \"1.2.3\"\ntype = \"dispatcher\"\n",
+        )
+        .unwrap();
+
+        let content = format!(
+            "plugins:\n  mock:\n    path: ./plugins/mock.wasm\n    version: \"{}\"\n",
+            version
+        );
+        let manifest = ProjectManifest::parse(&content, Path::new("barbacane.yaml")).unwrap();
+        manifest.resolve_plugins(temp.path(), false)
+    }
+
+    #[test]
+    fn pinned_version_matching_resolves() {
+        let resolved = resolve_pinned("1.2.3").unwrap();
+        assert_eq!(resolved[0].version.as_deref(), Some("1.2.3"));
+    }
+
+    #[test]
+    fn pinned_version_mismatch_errors() {
+        let err = resolve_pinned("1.3.0").unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                CompileError::PluginVersionMismatch { name, requested, found }
+                    if name == "mock" && requested == "1.3.0" && found == "1.2.3"
+            ),
+            "{err:?}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "E1042: plugin 'mock' version mismatch: barbacane.yaml requests 1.3.0, plugin reports 1.2.3"
+        );
+    }
+
+    #[test]
+    fn wildcard_version_skips_check() {
+        let resolved = resolve_pinned("*").unwrap();
+        assert_eq!(resolved[0].version.as_deref(), Some("1.2.3"));
+    }
+
+    #[test]
+    fn glob_source_expands_to_one_plugin_per_file() {
+        let temp = TempDir::new().unwrap();
+        for plugin in ["cors", "jwt-auth"] {
+            let dir = temp.path().join("plugins").join(plugin);
+            std::fs::create_dir_all(&dir).unwrap();
+            write_valid_wasm(&dir, &format!("{}.wasm", plugin));
+            std::fs::write(dir.join("plugin.toml"), "not wasm").unwrap();
+        }
+
+        let content = "plugins:\n  local:\n    path: ./plugins/*/*.wasm\n";
+        let manifest = ProjectManifest::parse(content, Path::new("barbacane.yaml")).unwrap();
+
+        let mut names: Vec<_> = manifest
+            .resolve_plugins(temp.path(), false)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["cors", "jwt-auth"]);
+        assert_eq!(manifest.local_plugin_paths(temp.path()).len(), 2);
+
+        // Specs reference the derived names, not the glob's label.
+        let spec = crate::spec_parser::parse_spec(
+            r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+x-barbacane-middlewares:
+  - name: cors
+paths:
+  /health:
+    get:
+      x-barbacane-dispatch:
+        name: jwt-auth
+"#,
+        )
+        .unwrap();
+        let resolved = manifest
+            .resolve_used_plugins(&[spec], temp.path(), false)
+            .unwrap();
+        assert_eq!(resolved.len(), 2);
+    }
+
+    #[test]
+    fn glob_source_without_matches_errors() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("plugins")).unwrap();
+
+        let content = "plugins:\n  local:\n    path: ./plugins/*/*.wasm\n";
+        let manifest = ProjectManifest::parse(content, Path::new("barbacane.yaml")).unwrap();
+
+        let err = manifest.resolve_plugins(temp.path(), false).unwrap_err();
+        assert!(matches!(err, CompileError::ManifestError(_)), "{err:?}");
+        assert!(err.to_string().contains("matched no files"), "{err}");
+    }
+
     #[test]
     fn resolve_plugins_reads_metadata() {
         let temp = TempDir::new().unwrap();
@@ -1320,4 +1823,44 @@ plugins:
         assert_eq!(paths.len(), 1);
         assert!(paths[0].ends_with("plugins/mock.wasm"));
     }
+
+    #[test]
+    fn parse_accepts_current_and_unversioned_manifests() {
+        let content = "plugins:\n  mock:\n    path: ./mock.wasm\n";
+        let unversioned = ProjectManifest::parse(content, Path::new("barbacane.yaml")).unwrap();
+        let versioned = ProjectManifest::parse(
+            &format!("version: 1\n{}", content),
+            Path::new("barbacane.yaml"),
+        )
+        .unwrap();
+        assert_eq!(unversioned.plugins.len(), 1);
+        assert_eq!(versioned.plugins.len(), 1);
+    }
+
+    #[test]
+    fn parse_rejects_newer_manifest_version() {
+        let err = ProjectManifest::parse(
+            "version: 2\nplugins: {}\nrenamed_in_v2: true\n",
+            Path::new("barbacane.yaml"),
+        )
+        .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("manifest version 2"), "{msg}");
+        assert!(msg.contains("upgrade barbacane"), "{msg}");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_manifest_version() {
+        for version in ["0", "\"1\"", "1.5"] {
+            let err = ProjectManifest::parse(
+                &format!("version: {}\nplugins: {{}}\n", version),
+                Path::new("barbacane.yaml"),
+            )
+            .unwrap_err();
+            assert!(
+                err.to_string().contains("unsupported manifest version"),
+                "{version}: {err}"
+            );
+        }
+    }
 }
@@ -12,4 +12,4 @@ pub use model::{
     ApiSpec, AsyncAction, Channel, ContentSchema, DispatchConfig, Message, MiddlewareConfig,
     Operation, Parameter, RequestBody, ResponseContent, SpecFormat,
 };
-pub use parser::{parse_spec, parse_spec_file};
+pub use parser::{parse_spec, parse_spec_file, parse_spec_with_base_dir};
@@ -22,6 +22,167 @@ pub struct ApiSpec {
     pub extensions: BTreeMap<String, serde_json::Value>,
 }
 
+/// Methods with a fixed field on an OpenAPI 3.1 path item.
+const OPENAPI_31_METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+impl ApiSpec {
+    /// Re-emit the parsed model as an OpenAPI 3.1 document (pretty-printed JSON).
+    ///
+    /// The output reflects what the compiler operates on: `$ref`s are already
+    /// inlined, path-level parameters are repeated on each operation and the
+    /// typed `x-barbacane-*` fields take precedence over the raw extensions.
+    /// Anything OpenAPI 3.1 can't express is dropped: operations on methods
+    /// without a path item field (`QUERY`, `additionalOperations`, AsyncAPI
+    /// actions) and `in: querystring` parameters.
+    pub fn to_openapi_json(&self) -> String {
+        use serde_json::{json, Map, Value};
+
+        let mut paths = Map::new();
+        for op in &self.operations {
+            let method = op.method.to_lowercase();
+            if !OPENAPI_31_METHODS.contains(&method.as_str()) {
+                continue;
+            }
+
+            // `x-barbacane-plugins` is folded into the dispatch/middleware fields.
+            let mut obj: Map<String, Value> = op
+                .extensions
+                .iter()
+                .filter(|(k, _)| k.as_str() != "x-barbacane-plugins")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            if let Some(id) = &op.operation_id {
+                obj.insert("operationId".into(), json!(id));
+            }
+            if let Some(summary) = &op.summary {
+                obj.insert("summary".into(), json!(summary));
+            }
+            if let Some(description) = &op.description {
+                obj.insert("description".into(), json!(description));
+            }
+            if op.deprecated {
+                obj.insert("deprecated".into(), json!(true));
+            }
+            if let Some(sunset) = &op.sunset {
+                obj.insert("x-sunset".into(), json!(sunset));
+            }
+
+            let parameters: Vec<Value> = op
+                .parameters
+                .iter()
+                .filter(|p| p.location != "querystring")
+                .map(Parameter::to_openapi)
+                .collect();
+            if !parameters.is_empty() {
+                obj.insert("parameters".into(), Value::Array(parameters));
+            }
+
+            if let Some(body) = &op.request_body {
+                obj.insert(
+                    "requestBody".into(),
+                    json!({
+                        "required": body.required,
+                        "content": content_to_openapi(&body.content),
+                    }),
+                );
+            }
+            if !op.responses.is_empty() {
+                let responses: Map<String, Value> = op
+                    .responses
+                    .iter()
+                    .map(|(status, resp)| {
+                        let resp = json!({
+                            "description": "",
+                            "content": content_to_openapi(&resp.content),
+                        });
+                        (status.clone(), resp)
+                    })
+                    .collect();
+                obj.insert("responses".into(), Value::Object(responses));
+            }
+
+            if let Some(dispatch) = &op.dispatch {
+                obj.insert("x-barbacane-dispatch".into(), json!(dispatch));
+            }
+            if let Some(middlewares) = &op.middlewares {
+                obj.insert("x-barbacane-middlewares".into(), json!(middlewares));
+            }
+            if let Some(timeout_ms) = op.timeout_ms {
+                obj.insert("x-barbacane-timeout".into(), json!(timeout_ms));
+            }
+
+            if let Some(item) = paths
+                .entry(op.path.clone())
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+            {
+                item.insert(method, Value::Object(obj));
+            }
+        }
+
+        let mut doc: Map<String, Value> = self
+            .extensions
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        doc.insert("openapi".into(), json!("3.1.0"));
+        doc.insert(
+            "info".into(),
+            json!({ "title": self.title, "version": self.api_version }),
+        );
+        doc.insert("paths".into(), Value::Object(paths));
+        if !self.global_middlewares.is_empty() {
+            doc.insert(
+                "x-barbacane-middlewares".into(),
+                json!(self.global_middlewares),
+            );
+        }
+
+        serde_json::to_string_pretty(&Value::Object(doc))
+            .expect("JSON value serialization is infallible")
+    }
+}
+
+impl Parameter {
+    /// The OpenAPI parameter object for this parameter.
+    fn to_openapi(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        obj.insert("name".into(), self.name.clone().into());
+        obj.insert("in".into(), self.location.clone().into());
+        obj.insert("required".into(), self.required.into());
+        if let Some(schema) = &self.schema {
+            obj.insert("schema".into(), schema.clone());
+        }
+        if self.allow_reserved {
+            obj.insert("allowReserved".into(), true.into());
+        }
+        if let Some(style) = &self.style {
+            obj.insert("style".into(), style.clone().into());
+        }
+        if let Some(explode) = self.explode {
+            obj.insert("explode".into(), explode.into());
+        }
+        serde_json::Value::Object(obj)
+    }
+}
+
+/// An OpenAPI `content` map (media type -> `{ schema }`).
+fn content_to_openapi(content: &BTreeMap<String, ContentSchema>) -> serde_json::Value {
+    content
+        .iter()
+        .map(|(media_type, c)| {
+            let mut media = serde_json::Map::new();
+            if let Some(schema) = &c.schema {
+                media.insert("schema".into(), schema.clone());
+            }
+            (media_type.clone(), serde_json::Value::Object(media))
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
 /// Detected spec format.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -49,9 +210,11 @@ pub struct Operation {
     pub parameters: Vec<Parameter>,
     /// Request body definition (OpenAPI: requestBody, AsyncAPI: message payload for SEND).
     pub request_body: Option<RequestBody>,
-    /// The dispatcher configuration from `x-barbacane-dispatch`.
+    /// The dispatcher configuration from `x-barbacane-dispatch` (or the
+    /// inline `x-barbacane-plugins.dispatch`).
     pub dispatch: Option<DispatchConfig>,
-    /// Operation-level middlewares (replaces global chain if present).
+    /// Operation-level middlewares from `x-barbacane-middlewares` (or the
+    /// inline `x-barbacane-plugins.middlewares`), merged over the global chain.
     pub middlewares: Option<Vec<MiddlewareConfig>>,
     /// Whether this operation is deprecated (OpenAPI `deprecated` field).
     #[serde(default)]
@@ -59,6 +222,9 @@ pub struct Operation {
     /// Sunset date for deprecated operations (from `x-sunset` per RFC 8594).
     /// Format: HTTP-date per RFC 9110 (e.g., "Sat, 31 Dec 2024 23:59:59 GMT").
     pub sunset: Option<String>,
+    /// Upstream timeout in milliseconds (from `x-barbacane-timeout`).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
     /// Operation-level `x-barbacane-*` extensions.
     pub extensions: BTreeMap<String, serde_json::Value>,
     /// AsyncAPI messages (for async operations only).
@@ -90,6 +256,16 @@ pub struct Parameter {
     pub required: bool,
     /// The parameter's schema (for validation in M2).
     pub schema: Option<serde_json::Value>,
+    /// OpenAPI `allowReserved`: reserved characters (RFC 3986 §2.2) may appear
+    /// unencoded in the value, so a literal `+` is a plus rather than a space.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_reserved: bool,
+    /// OpenAPI `style` (`form`, `spaceDelimited`, `pipeDelimited`, ...), if declared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// OpenAPI `explode`, if declared; the default depends on `style`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explode: Option<bool>,
 }
 
 /// Dispatcher configuration extracted from `x-barbacane-dispatch`.
@@ -1,4 +1,5 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use serde_json::Value;
 
@@ -65,6 +66,105 @@ fn resolve_schema_refs(
     }
 }
 
+/// Inlines `$ref`s that point into other files, e.g.
+/// `./common.yaml#/components/schemas/Error`, before the spec is parsed.
+///
+/// Paths resolve relative to the directory of the document holding the ref.
+/// Local refs (`#/...`) in the root spec are left for [`resolve_schema_refs`];
+/// local refs inside a referenced file are inlined against that file. Remote
+/// (`http://...`) refs are left as-is and fail later as unresolved.
+#[derive(Default)]
+struct ExternalRefResolver {
+    /// Referenced documents, loaded once, keyed by canonical path.
+    documents: HashMap<PathBuf, Value>,
+    /// Refs being expanded (`path#pointer`), to detect cycles across files.
+    stack: Vec<String>,
+}
+
+impl ExternalRefResolver {
+    /// Inline external refs in `value`, which belongs to the document at `doc`
+    /// (`None` for the root spec) in directory `dir`.
+    fn inline(
+        &mut self,
+        value: &Value,
+        dir: &Path,
+        doc: Option<&Path>,
+    ) -> Result<Value, ParseError> {
+        match value {
+            Value::Object(obj) => {
+                if let Some(ref_str) = obj.get("$ref").and_then(|v| v.as_str()) {
+                    let (file, pointer) = ref_str.split_once('#').unwrap_or((ref_str, ""));
+                    if file.contains("://") {
+                        return Ok(value.clone());
+                    }
+                    let target_path = match (file, doc) {
+                        ("", None) => return Ok(value.clone()),
+                        ("", Some(doc)) => doc.to_path_buf(),
+                        (file, _) => dir.join(file),
+                    };
+                    return self.inline_target(ref_str, &target_path, pointer);
+                }
+                let mut new_obj = serde_json::Map::with_capacity(obj.len());
+                for (key, val) in obj {
+                    new_obj.insert(key.clone(), self.inline(val, dir, doc)?);
+                }
+                Ok(Value::Object(new_obj))
+            }
+            Value::Array(arr) => {
+                let items: Result<Vec<_>, _> =
+                    arr.iter().map(|v| self.inline(v, dir, doc)).collect();
+                Ok(Value::Array(items?))
+            }
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// Load `pointer` from the file at `path` and inline its own refs.
+    fn inline_target(
+        &mut self,
+        ref_str: &str,
+        path: &Path,
+        pointer: &str,
+    ) -> Result<Value, ParseError> {
+        let path = path.canonicalize().map_err(|e| {
+            ParseError::UnresolvedRef(format!("{} ({}: {})", ref_str, path.display(), e))
+        })?;
+        let key = format!("{}#{}", path.display(), pointer);
+        if self.stack.contains(&key) {
+            return Err(ParseError::SchemaError(format!(
+                "circular $ref detected: {}",
+                ref_str
+            )));
+        }
+
+        let document = self.load(&path)?;
+        let target = if pointer.is_empty() {
+            Some(document)
+        } else {
+            resolve_ref(document, &format!("#{}", pointer))
+        }
+        .ok_or_else(|| ParseError::UnresolvedRef(ref_str.to_string()))?
+        .clone();
+
+        self.stack.push(key);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let resolved = self.inline(&target, dir, Some(&path))?;
+        self.stack.pop();
+        Ok(resolved)
+    }
+
+    fn load(&mut self, path: &Path) -> Result<&Value, ParseError> {
+        if !self.documents.contains_key(path) {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| ParseError::UnresolvedRef(format!("{}: {}", path.display(), e)))?;
+            let document: Value = serde_yaml::from_str(&content)
+                .map_err(|e| ParseError::ParseError(format!("{}: {}", path.display(), e)))?;
+            self.documents.insert(path.to_path_buf(), document);
+        }
+        Ok(&self.documents[path])
+    }
+}
+
 /// HTTP methods we recognize in OpenAPI paths.
 /// Includes `query` from OpenAPI 3.2 (RFC 9110 extension).
 const HTTP_METHODS: &[&str] = &[
@@ -72,11 +172,42 @@ const HTTP_METHODS: &[&str] = &[
 ];
 
 /// Parse an OpenAPI or AsyncAPI spec from a YAML/JSON string.
+///
+/// Only local `$ref`s (`#/...`) resolve; use [`parse_spec_with_base_dir`] or
+/// [`parse_spec_file`] for specs that reference other files.
 pub fn parse_spec(input: &str) -> Result<ApiSpec, ParseError> {
+    parse_spec_in(input, None, None)
+}
+
+/// Parse a spec from a YAML/JSON string, resolving `$ref`s to other files
+/// (`./common.yaml#/components/schemas/Error`) relative to `base_dir`.
+pub fn parse_spec_with_base_dir(input: &str, base_dir: &Path) -> Result<ApiSpec, ParseError> {
+    parse_spec_in(input, Some(base_dir), None)
+}
+
+/// Parse a spec, optionally resolving cross-file refs against `base_dir` and
+/// keeping only the operations whose id is in `only_operations`.
+pub(crate) fn parse_spec_in(
+    input: &str,
+    base_dir: Option<&Path>,
+    only_operations: Option<&[String]>,
+) -> Result<ApiSpec, ParseError> {
     // Parse YAML (also handles JSON since JSON is valid YAML)
-    let root: Value =
+    let mut root: Value =
         serde_yaml::from_str(input).map_err(|e| ParseError::ParseError(e.to_string()))?;
 
+    // Drop excluded operations before any $ref is resolved, so their schemas
+    // are never required.
+    if let Some(only) = only_operations {
+        let only: HashSet<&str> = only.iter().map(String::as_str).collect();
+        retain_operations(&mut root, &only);
+    }
+
+    // Inline cross-file refs up front so the rest of parsing sees one document.
+    if let Some(base_dir) = base_dir {
+        root = ExternalRefResolver::default().inline(&root, base_dir, None)?;
+    }
+
     let root_obj = root
         .as_object()
         .ok_or_else(|| ParseError::ParseError("spec root must be an object".into()))?;
@@ -126,10 +257,61 @@ pub fn parse_spec(input: &str) -> Result<ApiSpec, ParseError> {
     })
 }
 
+/// Remove every operation whose id is not in `only` from the raw spec, along
+/// with OpenAPI path items and AsyncAPI channels left without operations.
+fn retain_operations(root: &mut Value, only: &HashSet<&str>) {
+    let kept = |op: &Value| {
+        op.get("operationId")
+            .and_then(|v| v.as_str())
+            .is_some_and(|id| only.contains(id))
+    };
+
+    if let Some(paths) = root.get_mut("paths").and_then(|v| v.as_object_mut()) {
+        for path_item in paths.values_mut() {
+            let Some(path_obj) = path_item.as_object_mut() else {
+                continue;
+            };
+            path_obj.retain(|key, op| !HTTP_METHODS.contains(&key.as_str()) || kept(op));
+            if let Some(additional) = path_obj
+                .get_mut("additionalOperations")
+                .and_then(|v| v.as_object_mut())
+            {
+                additional.retain(|_, op| kept(op));
+                if additional.is_empty() {
+                    path_obj.remove("additionalOperations");
+                }
+            }
+        }
+        paths.retain(|_, path_item| {
+            path_item.as_object().is_some_and(|path_obj| {
+                path_obj.keys().any(|key| {
+                    HTTP_METHODS.contains(&key.as_str()) || key == "additionalOperations"
+                })
+            })
+        });
+    }
+
+    // AsyncAPI operations are keyed by id. Keep only the channels they use.
+    let Some(ops) = root.get_mut("operations").and_then(|v| v.as_object_mut()) else {
+        return;
+    };
+    ops.retain(|op_id, _| only.contains(op_id.as_str()));
+    let used_channels: HashSet<String> = ops
+        .values()
+        .filter_map(|op| op.get("channel")?.get("$ref")?.as_str())
+        .filter_map(|r| r.strip_prefix("#/channels/"))
+        .map(|name| name.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    if let Some(channels) = root.get_mut("channels").and_then(|v| v.as_object_mut()) {
+        channels.retain(|name, _| used_channels.contains(name));
+    }
+}
+
 /// Parse a spec from a file path.
-pub fn parse_spec_file(path: &std::path::Path) -> Result<ApiSpec, ParseError> {
+pub fn parse_spec_file(path: &Path) -> Result<ApiSpec, ParseError> {
     let content = std::fs::read_to_string(path)?;
-    let mut spec = parse_spec(&content)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mut spec = parse_spec_with_base_dir(&content, base_dir)?;
     spec.filename = path
         .file_name()
         .and_then(|s| s.to_str())
@@ -188,6 +370,101 @@ fn extract_dispatch(obj: &serde_json::Map<String, Value>) -> Option<DispatchConf
         .and_then(|v| serde_json::from_value(v.clone()).ok())
 }
 
+/// Name an operation in parse errors: `'getUser' (GET /users/{id})`.
+fn operation_label(operation_id: Option<&str>, method: &str, path: &str) -> String {
+    match operation_id {
+        Some(id) => format!("'{}' ({} {})", id, method, path),
+        None => format!("{} {}", method, path),
+    }
+}
+
+/// Extract `x-barbacane-timeout` (milliseconds) from an operation object.
+/// Anything but a non-negative integer is an error naming the operation.
+fn extract_timeout(
+    obj: &serde_json::Map<String, Value>,
+    operation_id: Option<&str>,
+    method: &str,
+    path: &str,
+) -> Result<Option<u64>, ParseError> {
+    let Some(value) = obj.get("x-barbacane-timeout") else {
+        return Ok(None);
+    };
+    value.as_u64().map(Some).ok_or_else(|| {
+        ParseError::SchemaError(format!(
+            "x-barbacane-timeout on operation {} must be a non-negative integer (milliseconds), got {}",
+            operation_label(operation_id, method, path),
+            value
+        ))
+    })
+}
+
+/// Extract an operation's dispatcher and middleware chain.
+///
+/// `x-barbacane-dispatch` and `x-barbacane-middlewares` take precedence; a
+/// slot they leave unset is filled from the inline `x-barbacane-plugins`
+/// object (`dispatch` entry, `middlewares` list). Unlike the dedicated
+/// extensions, malformed inline entries are an error: every entry must be an
+/// object with a non-empty string `name` and, if present, an object `config`.
+fn extract_operation_plugins(
+    obj: &serde_json::Map<String, Value>,
+    operation_id: Option<&str>,
+    method: &str,
+    path: &str,
+) -> Result<(Option<DispatchConfig>, Option<Vec<MiddlewareConfig>>), ParseError> {
+    let mut dispatch = extract_dispatch(obj);
+    let mut middlewares = if obj.contains_key("x-barbacane-middlewares") {
+        Some(extract_middlewares(obj))
+    } else {
+        None
+    };
+
+    let Some(inline) = obj.get("x-barbacane-plugins") else {
+        return Ok((dispatch, middlewares));
+    };
+    let invalid = |what: String| {
+        ParseError::SchemaError(format!(
+            "x-barbacane-plugins on operation {} {}",
+            operation_label(operation_id, method, path),
+            what
+        ))
+    };
+    let inline = inline
+        .as_object()
+        .ok_or_else(|| invalid("must be an object".to_string()))?;
+    let entry = |value: &Value, at: &str| -> Result<(String, Value), ParseError> {
+        let entry = value
+            .as_object()
+            .ok_or_else(|| invalid(format!("{} must be an object", at)))?;
+        let name = match entry.get("name").and_then(|v| v.as_str()) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => return Err(invalid(format!("{}.name must be a non-empty string", at))),
+        };
+        let config = match entry.get("config") {
+            None => Value::Null,
+            Some(config @ Value::Object(_)) => config.clone(),
+            Some(_) => return Err(invalid(format!("{}.config must be an object", at))),
+        };
+        Ok((name, config))
+    };
+
+    if let Some(value) = inline.get("dispatch") {
+        let (name, config) = entry(value, "dispatch")?;
+        dispatch = dispatch.or(Some(DispatchConfig { name, config }));
+    }
+    if let Some(value) = inline.get("middlewares") {
+        let items = value
+            .as_array()
+            .ok_or_else(|| invalid("middlewares must be an array".to_string()))?;
+        let mut chain = Vec::with_capacity(items.len());
+        for (idx, item) in items.iter().enumerate() {
+            let (name, config) = entry(item, &format!("middlewares[{}]", idx))?;
+            chain.push(MiddlewareConfig { name, config });
+        }
+        middlewares = middlewares.or(Some(chain));
+    }
+    Ok((dispatch, middlewares))
+}
+
 /// Parse OpenAPI 3.x paths into operations.
 fn parse_openapi_paths(
     root: &serde_json::Map<String, Value>,
@@ -240,13 +517,12 @@ fn parse_openapi_paths(
                 let request_body = parse_request_body(op_obj, spec_root)?;
                 let responses = parse_responses(op_obj, spec_root)?;
 
-                let dispatch = extract_dispatch(op_obj);
-
-                let middlewares = if op_obj.contains_key("x-barbacane-middlewares") {
-                    Some(extract_middlewares(op_obj))
-                } else {
-                    None
-                };
+                let (dispatch, middlewares) = extract_operation_plugins(
+                    op_obj,
+                    operation_id.as_deref(),
+                    &method.to_uppercase(),
+                    path,
+                )?;
 
                 // Extract deprecated flag (standard OpenAPI field)
                 let deprecated = op_obj
@@ -260,6 +536,13 @@ fn parse_openapi_paths(
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string());
 
+                let timeout_ms = extract_timeout(
+                    op_obj,
+                    operation_id.as_deref(),
+                    &method.to_uppercase(),
+                    path,
+                )?;
+
                 let extensions = extract_extensions(op_obj);
 
                 operations.push(Operation {
@@ -274,6 +557,7 @@ fn parse_openapi_paths(
                     middlewares,
                     deprecated,
                     sunset,
+                    timeout_ms,
                     extensions,
                     messages: Vec::new(), // OpenAPI doesn't use AsyncAPI messages
                     bindings: BTreeMap::new(), // OpenAPI doesn't use protocol bindings
@@ -315,13 +599,12 @@ fn parse_openapi_paths(
 
                 let request_body = parse_request_body(op_obj, spec_root)?;
                 let responses = parse_responses(op_obj, spec_root)?;
-                let dispatch = extract_dispatch(op_obj);
-
-                let middlewares = if op_obj.contains_key("x-barbacane-middlewares") {
-                    Some(extract_middlewares(op_obj))
-                } else {
-                    None
-                };
+                let (dispatch, middlewares) = extract_operation_plugins(
+                    op_obj,
+                    operation_id.as_deref(),
+                    &method_name.to_uppercase(),
+                    path,
+                )?;
 
                 let deprecated = op_obj
                     .get("deprecated")
@@ -333,6 +616,13 @@ fn parse_openapi_paths(
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string());
 
+                let timeout_ms = extract_timeout(
+                    op_obj,
+                    operation_id.as_deref(),
+                    &method_name.to_uppercase(),
+                    path,
+                )?;
+
                 let extensions = extract_extensions(op_obj);
 
                 operations.push(Operation {
@@ -347,6 +637,7 @@ fn parse_openapi_paths(
                     middlewares,
                     deprecated,
                     sunset,
+                    timeout_ms,
                     extensions,
                     messages: Vec::new(),
                     bindings: BTreeMap::new(),
@@ -403,6 +694,15 @@ fn parse_parameters(
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false),
             schema,
+            allow_reserved: param_obj
+                .get("allowReserved")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            style: param_obj
+                .get("style")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            explode: param_obj.get("explode").and_then(|v| v.as_bool()),
         });
     }
     Ok(params)
@@ -576,15 +876,9 @@ fn parse_asyncapi_channels(
             }
         }
 
-        // Extract dispatch config
-        let dispatch = extract_dispatch(op_obj);
-
-        // Extract middlewares
-        let middlewares = if op_obj.contains_key("x-barbacane-middlewares") {
-            Some(extract_middlewares(op_obj))
-        } else {
-            None
-        };
+        // Extract dispatch config and middlewares
+        let (dispatch, middlewares) =
+            extract_operation_plugins(op_obj, Some(op_id), &method, &address)?;
 
         // Extract deprecated and sunset
         let deprecated = op_obj
@@ -597,6 +891,8 @@ fn parse_asyncapi_channels(
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
+        let timeout_ms = extract_timeout(op_obj, Some(op_id), &method, &address)?;
+
         let extensions = extract_extensions(op_obj);
 
         operations.push(Operation {
@@ -611,6 +907,7 @@ fn parse_asyncapi_channels(
             middlewares,
             deprecated,
             sunset,
+            timeout_ms,
             extensions,
             messages,
             bindings,
@@ -748,6 +1045,9 @@ fn parse_channel_parameters(
             location: "path".to_string(),
             required: true,
             schema,
+            allow_reserved: false,
+            style: None,
+            explode: None,
         });
     }
     Ok(result)
@@ -1140,6 +1440,59 @@ paths:
         assert!(new_op.sunset.is_none());
     }
 
+    #[test]
+    fn parse_operation_timeout() {
+        let yaml = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /slow:
+    get:
+      x-barbacane-timeout: 30000
+      x-barbacane-dispatch:
+        name: mock
+  /fast:
+    get:
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec = parse_spec(yaml).unwrap();
+        let find = |path: &str| spec.operations.iter().find(|op| op.path == path).unwrap();
+        assert_eq!(find("/slow").timeout_ms, Some(30000));
+        assert_eq!(find("/fast").timeout_ms, None);
+    }
+
+    #[test]
+    fn parse_operation_timeout_rejects_invalid_values() {
+        for value in ["-5", "1.5", "\"30s\""] {
+            let yaml = format!(
+                r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /users/{{id}}:
+    get:
+      operationId: getUser
+      x-barbacane-timeout: {value}
+      x-barbacane-dispatch:
+        name: mock
+"#
+            );
+            match parse_spec(&yaml) {
+                Err(ParseError::SchemaError(msg)) => {
+                    assert!(msg.contains("x-barbacane-timeout"), "got: {msg}");
+                    assert!(msg.contains("'getUser'"), "got: {msg}");
+                    assert!(msg.contains("GET /users/{id}"), "got: {msg}");
+                }
+                other => panic!("expected SchemaError for {value}, got {other:?}"),
+            }
+        }
+    }
+
     // ==================== AsyncAPI 3.x Tests ====================
 
     #[test]
@@ -1606,6 +1959,67 @@ paths:
         );
     }
 
+    #[test]
+    fn parse_allow_reserved_query_parameter() {
+        let yaml = r#"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /files:
+    get:
+      parameters:
+        - name: path
+          in: query
+          allowReserved: true
+          schema:
+            type: string
+        - name: sort
+          in: query
+          schema:
+            type: string
+      x-barbacane-dispatch:
+        name: mock
+"#;
+        let spec = parse_spec(yaml).unwrap();
+        let params = &spec.operations[0].parameters;
+        assert!(params[0].allow_reserved);
+        assert!(!params[1].allow_reserved);
+    }
+
+    #[test]
+    fn parse_query_parameter_style_and_explode() {
+        let yaml = r#"
+openapi: "3.1.0"
+info:
+  title: Test
+  version: "1.0.0"
+paths:
+  /items:
+    get:
+      parameters:
+        - name: ids
+          in: query
+          style: form
+          explode: false
+          schema:
+            type: array
+            items:
+              type: integer
+        - name: tags
+          in: query
+          schema:
+            type: array
+"#;
+        let spec = parse_spec(yaml).unwrap();
+        let params = &spec.operations[0].parameters;
+        assert_eq!(params[0].style.as_deref(), Some("form"));
+        assert_eq!(params[0].explode, Some(false));
+        assert_eq!(params[1].style, None);
+        assert_eq!(params[1].explode, None);
+    }
+
     // ── $ref resolution tests ────────────────────────────────────────────
 
     #[test]
@@ -1776,6 +2190,113 @@ paths:
         );
     }
 
+    /// Write `files` into a temp dir and parse `api.yaml` from it.
+    fn parse_spec_files(files: &[(&str, &str)]) -> Result<ApiSpec, ParseError> {
+        let dir = tempfile::TempDir::new().unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        parse_spec_file(&dir.path().join("api.yaml"))
+    }
+
+    const CROSS_FILE_SPEC: &str = r##"
+openapi: "3.1.0"
+info:
+  title: Test API
+  version: "1.0.0"
+paths:
+  /users:
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "./schemas/common.yaml#/components/schemas/Error"
+      x-barbacane-dispatch:
+        name: mock
+"##;
+
+    #[test]
+    fn resolve_cross_file_schema_ref() {
+        let spec = parse_spec_files(&[
+            ("api.yaml", CROSS_FILE_SPEC),
+            (
+                "schemas/common.yaml",
+                r##"
+components:
+  schemas:
+    Error:
+      type: object
+      properties:
+        code:
+          $ref: "#/components/schemas/Code"
+        details:
+          $ref: "./details.yaml"
+    Code:
+      type: integer
+"##,
+            ),
+            (
+                "schemas/details.yaml",
+                "type: array\nitems:\n  type: string\n",
+            ),
+        ])
+        .unwrap();
+
+        let body = spec.operations[0].request_body.as_ref().unwrap();
+        let schema = body.content["application/json"].schema.as_ref().unwrap();
+        assert!(schema.get("$ref").is_none());
+        assert_eq!(schema["type"], "object");
+        // Local refs inside the referenced file resolve against that file,
+        // and its own relative refs against its directory.
+        assert_eq!(schema["properties"]["code"]["type"], "integer");
+        assert_eq!(schema["properties"]["details"]["items"]["type"], "string");
+    }
+
+    #[test]
+    fn cross_file_ref_to_missing_file_returns_error() {
+        let err = parse_spec_files(&[("api.yaml", CROSS_FILE_SPEC)]).unwrap_err();
+        assert!(
+            matches!(err, ParseError::UnresolvedRef(ref s) if s.contains("common.yaml")),
+            "expected UnresolvedRef, got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn circular_cross_file_ref_returns_error() {
+        let err = parse_spec_files(&[
+            ("api.yaml", CROSS_FILE_SPEC),
+            (
+                "schemas/common.yaml",
+                r##"
+components:
+  schemas:
+    Error:
+      type: object
+      properties:
+        cause:
+          $ref: "./cause.yaml#/Cause"
+"##,
+            ),
+            (
+                "schemas/cause.yaml",
+                r##"
+Cause:
+  $ref: "./common.yaml#/components/schemas/Error"
+"##,
+            ),
+        ])
+        .unwrap_err();
+        assert!(
+            matches!(err, ParseError::SchemaError(ref s) if s.contains("circular")),
+            "expected SchemaError with 'circular', got: {:?}",
+            err
+        );
+    }
+
     #[test]
     fn asyncapi_message_payload_ref() {
         let yaml = r##"
@@ -1967,4 +2488,202 @@ paths:
         let op = &spec.operations[0];
         assert!(op.responses.is_empty());
     }
+
+    #[test]
+    fn to_openapi_json_round_trips_operations() {
+        let yaml = r##"
+openapi: "3.0.3"
+info:
+  title: Users
+  version: "2.0.0"
+x-barbacane-middlewares:
+  - name: cors
+paths:
+  /users/{id}:
+    parameters:
+      - name: id
+        in: path
+        required: true
+        schema:
+          $ref: "#/components/schemas/Id"
+    get:
+      operationId: getUser
+      deprecated: true
+      x-sunset: "Sat, 31 Dec 2033 23:59:59 GMT"
+      x-barbacane-timeout: 5000
+      x-barbacane-dispatch:
+        name: http-upstream
+        config:
+          url: "https://users.internal"
+      responses:
+        "200":
+          description: The user
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/User"
+    put:
+      operationId: updateUser
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/User"
+      x-barbacane-middlewares:
+        - name: jwt-auth
+      x-barbacane-dispatch:
+        name: mock
+    query:
+      operationId: searchUsers
+      x-barbacane-dispatch:
+        name: mock
+components:
+  schemas:
+    Id:
+      type: string
+    User:
+      type: object
+      properties:
+        id:
+          $ref: "#/components/schemas/Id"
+"##;
+        let spec = parse_spec(yaml).expect("should parse");
+        let json = spec.to_openapi_json();
+        assert!(!json.contains("$ref"), "refs are emitted inlined");
+
+        let reparsed = parse_spec(&json).expect("emitted document should parse");
+        assert_eq!(reparsed.version, "3.1.0");
+        assert_eq!(reparsed.title, "Users");
+        assert_eq!(reparsed.api_version, "2.0.0");
+        assert_eq!(reparsed.global_middlewares.len(), 1);
+
+        // QUERY has no OpenAPI 3.1 field and is dropped.
+        let routes = |s: &ApiSpec| -> Vec<(String, String, Option<String>)> {
+            let mut routes: Vec<_> = s
+                .operations
+                .iter()
+                .filter(|op| op.method != "QUERY")
+                .map(|op| (op.path.clone(), op.method.clone(), op.operation_id.clone()))
+                .collect();
+            routes.sort();
+            routes
+        };
+        assert_eq!(routes(&reparsed), routes(&spec));
+        assert_eq!(reparsed.operations.len(), 2);
+
+        let get = reparsed
+            .operations
+            .iter()
+            .find(|op| op.method == "GET")
+            .expect("GET survives");
+        assert!(get.deprecated);
+        assert_eq!(get.sunset.as_deref(), Some("Sat, 31 Dec 2033 23:59:59 GMT"));
+        assert_eq!(get.timeout_ms, Some(5000));
+        assert_eq!(get.dispatch.as_ref().unwrap().name, "http-upstream");
+        assert_eq!(get.parameters.len(), 1);
+        assert_eq!(
+            get.parameters[0].schema,
+            Some(serde_json::json!({"type": "string"}))
+        );
+        let schema = get.responses["200"].content["application/json"]
+            .schema
+            .as_ref()
+            .unwrap();
+        assert_eq!(schema["properties"]["id"]["type"], "string");
+
+        let put = reparsed
+            .operations
+            .iter()
+            .find(|op| op.method == "PUT")
+            .expect("PUT survives");
+        assert!(put.request_body.as_ref().unwrap().required);
+        assert_eq!(put.middlewares.as_ref().unwrap()[0].name, "jwt-auth");
+        assert_eq!(put.parameters.len(), 1, "path-level parameter is repeated");
+    }
+
+    #[test]
+    fn parse_inline_plugins_extension() {
+        let yaml = r#"
+openapi: "3.1.0"
+info:
+  title: Test
+  version: "1.0.0"
+paths:
+  /users:
+    get:
+      operationId: listUsers
+      x-barbacane-plugins:
+        middlewares:
+          - name: jwt-auth
+            config:
+              issuer: https://auth.example.com
+          - name: cors
+        dispatch:
+          name: http-upstream
+          config:
+            url: https://users.internal
+    post:
+      x-barbacane-dispatch:
+        name: mock
+      x-barbacane-plugins:
+        dispatch:
+          name: http-upstream
+        middlewares: []
+"#;
+        let spec = parse_spec(yaml).unwrap();
+
+        let get = &spec.operations[0];
+        let dispatch = get.dispatch.as_ref().unwrap();
+        assert_eq!(dispatch.name, "http-upstream");
+        assert_eq!(dispatch.config["url"], "https://users.internal");
+        let middlewares = get.middlewares.as_ref().unwrap();
+        assert_eq!(middlewares.len(), 2);
+        assert_eq!(middlewares[0].name, "jwt-auth");
+        assert_eq!(middlewares[0].config["issuer"], "https://auth.example.com");
+        assert_eq!(middlewares[1].name, "cors");
+
+        // x-barbacane-dispatch wins; the unset middleware slot is filled inline.
+        let post = &spec.operations[1];
+        assert_eq!(post.dispatch.as_ref().unwrap().name, "mock");
+        assert_eq!(post.middlewares.as_ref().map(Vec::len), Some(0));
+    }
+
+    #[test]
+    fn parse_inline_plugins_rejects_invalid_entries() {
+        let spec_with = |plugins: &str| {
+            format!(
+                "openapi: \"3.1.0\"\ninfo:\n  title: Test\n  version: \"1.0.0\"\npaths:\n  /users:\n    get:\n      operationId: listUsers\n      x-barbacane-plugins:\n{}",
+                plugins
+            )
+        };
+        let cases = [
+            (
+                "        dispatch:\n          name: mock\n          config: [1, 2]\n",
+                "dispatch.config must be an object",
+            ),
+            (
+                "        middlewares:\n          - name: cors\n            config: \"*\"\n",
+                "middlewares[0].config must be an object",
+            ),
+            (
+                "        middlewares:\n          - config: {}\n",
+                "middlewares[0].name must be a non-empty string",
+            ),
+            (
+                "        dispatch:\n          name: \"\"\n",
+                "dispatch.name must be a non-empty string",
+            ),
+        ];
+        for (plugins, expected) in cases {
+            let err = parse_spec(&spec_with(plugins)).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "E1004: schema validation error: x-barbacane-plugins on operation 'listUsers' (GET /users) {}",
+                    expected
+                )
+            );
+        }
+    }
 }
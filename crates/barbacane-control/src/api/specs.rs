@@ -511,6 +511,7 @@ mod tests {
             middlewares: None,
             deprecated: false,
             sunset: None,
+            timeout_ms: None,
             extensions: BTreeMap::new(),
             messages: vec![],
             bindings: BTreeMap::new(),
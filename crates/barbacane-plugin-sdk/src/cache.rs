@@ -0,0 +1,129 @@
+//! Shared response cache via the `host_cache_get` / `host_cache_set` imports.
+//!
+//! The host keeps one cache per gateway, namespaced per plugin, so every
+//! instance of a plugin sees the entries its siblings stored (the `cache`
+//! middleware stores responses; `oidc-auth` shares fetched key sets). On
+//! non-wasm targets these read and write the `mock_host::MockHost` cache
+//! (`mock-host` feature), and miss / do nothing without one.
+//!
+//! ```
+//! use barbacane_plugin_sdk::cache::{self, CacheEntry};
+//! let entry = CacheEntry::new(200, br#"{"keys":[]}"#.to_vec());
+//! cache::set("jwks:https://idp/keys", &entry, 300);
+//! let _hit: Option<CacheEntry> = cache::get("jwks:https://idp/keys");
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A cached response, as stored by the host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, with = "crate::types::base64_body")]
+    pub body: Option<Vec<u8>>,
+}
+
+impl CacheEntry {
+    /// An entry with `status`, no headers, and `body`.
+    pub fn new(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: BTreeMap::new(),
+            body: Some(body),
+        }
+    }
+}
+
+/// Result of `host_cache_get`.
+#[cfg(target_arch = "wasm32")]
+#[derive(Deserialize)]
+struct CacheResult {
+    hit: bool,
+    entry: Option<CacheEntry>,
+}
+
+/// Look up `key` in the shared cache. Misses, expired entries, and host
+/// errors all read as `None`.
+#[cfg(target_arch = "wasm32")]
+pub fn get(key: &str) -> Option<CacheEntry> {
+    #[link(wasm_import_module = "barbacane")]
+    extern "C" {
+        fn host_cache_get(key_ptr: i32, key_len: i32) -> i32;
+        fn host_cache_read_result(buf_ptr: i32, buf_len: i32) -> i32;
+    }
+
+    let len = unsafe { host_cache_get(key.as_ptr() as i32, key.len() as i32) };
+    if len <= 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u8; len as usize];
+    let read_len = unsafe { host_cache_read_result(buf.as_mut_ptr() as i32, len) };
+    if read_len != len {
+        return None;
+    }
+
+    let result: CacheResult = serde_json::from_slice(&buf).ok()?;
+    if result.hit {
+        result.entry
+    } else {
+        None
+    }
+}
+
+/// Store `entry` under `key` for `ttl_secs`. Returns whether the host
+/// accepted it; callers treat the cache as best effort.
+#[cfg(target_arch = "wasm32")]
+pub fn set(key: &str, entry: &CacheEntry, ttl_secs: u32) -> bool {
+    #[link(wasm_import_module = "barbacane")]
+    extern "C" {
+        fn host_cache_set(
+            key_ptr: i32,
+            key_len: i32,
+            entry_ptr: i32,
+            entry_len: i32,
+            ttl_secs: u32,
+        ) -> i32;
+    }
+
+    let Ok(entry_json) = serde_json::to_vec(entry) else {
+        return false;
+    };
+    let status = unsafe {
+        host_cache_set(
+            key.as_ptr() as i32,
+            key.len() as i32,
+            entry_json.as_ptr() as i32,
+            entry_json.len() as i32,
+            ttl_secs,
+        )
+    };
+    status == 0
+}
+
+/// Native: reads the mock host's cache.
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn get(key: &str) -> Option<CacheEntry> {
+    crate::mock_host::cache_get(key)
+}
+
+/// Native: writes the mock host's cache; `false` without one installed.
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn set(key: &str, entry: &CacheEntry, ttl_secs: u32) -> bool {
+    crate::mock_host::cache_set(key, entry, ttl_secs)
+}
+
+/// Native without a mock host: always a miss.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
+pub fn get(_key: &str) -> Option<CacheEntry> {
+    None
+}
+
+/// Native without a mock host: nothing is stored.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
+pub fn set(_key: &str, _entry: &CacheEntry, _ttl_secs: u32) -> bool {
+    false
+}
@@ -0,0 +1,161 @@
+//! Wall-clock time via the `host_get_unix_timestamp` import.
+//!
+//! Plugins hold a [`BoxedClock`] (defaulting to [`HostClock`]); tests inject
+//! a [`FixedClock`].
+//!
+//! ```
+//! use barbacane_plugin_sdk::clock::{BoxedClock, Clock, FixedClock};
+//! let clock = BoxedClock::new(FixedClock(1_700_000_000));
+//! assert_eq!(clock.now(), 1_700_000_000);
+//! ```
+//!
+//! [`sleep_ms`] wraps the `host_sleep` import (the `sleep` capability), used
+//! for backoff between retried host calls.
+
+use std::fmt;
+
+/// A source of the current Unix time.
+pub trait Clock {
+    /// Current Unix timestamp in seconds.
+    fn now(&self) -> u64;
+}
+
+/// The host wall clock. On non-wasm targets (unit tests) it reads the
+/// `mock_host::MockHost` time if one is set (`mock-host` feature), and the
+/// system clock otherwise, so code under test that doesn't inject a clock sees
+/// real time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostClock;
+
+#[cfg(target_arch = "wasm32")]
+impl Clock for HostClock {
+    fn now(&self) -> u64 {
+        #[link(wasm_import_module = "barbacane")]
+        extern "C" {
+            fn host_get_unix_timestamp() -> u64;
+        }
+        unsafe { host_get_unix_timestamp() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for HostClock {
+    fn now(&self) -> u64 {
+        #[cfg(any(test, feature = "mock-host"))]
+        if let Some(ts) = crate::mock_host::time() {
+            return ts;
+        }
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// A clock frozen at a given Unix timestamp (seconds). Intended for tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A type-erased clock for storing on plugin config structs. Defaults to
+/// [`HostClock`], so a `#[serde(skip)]` field picks up the host clock when the
+/// plugin is initialized from its JSON config.
+pub struct BoxedClock(Box<dyn Clock>);
+
+impl BoxedClock {
+    /// Wrap any [`Clock`] implementation.
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Box::new(clock))
+    }
+
+    /// A clock frozen at `ts` (shorthand for `BoxedClock::new(FixedClock(ts))`).
+    pub fn fixed(ts: u64) -> Self {
+        Self::new(FixedClock(ts))
+    }
+}
+
+impl Default for BoxedClock {
+    fn default() -> Self {
+        Self::new(HostClock)
+    }
+}
+
+impl Clock for BoxedClock {
+    fn now(&self) -> u64 {
+        self.0.now()
+    }
+}
+
+impl fmt::Debug for BoxedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoxedClock").field(&self.0.now()).finish()
+    }
+}
+
+/// Current Unix timestamp in seconds from the host clock.
+pub fn now() -> u64 {
+    HostClock.now()
+}
+
+/// Block for `ms` milliseconds via the host. The host caps a single sleep and
+/// does not count the wait against the plugin's execution time limit.
+#[cfg(target_arch = "wasm32")]
+pub fn sleep_ms(ms: u32) {
+    #[link(wasm_import_module = "barbacane")]
+    extern "C" {
+        fn host_sleep(ms: u32);
+    }
+    unsafe { host_sleep(ms) }
+}
+
+/// Native: recorded by the mock host if one is installed (without waiting),
+/// a real thread sleep otherwise.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn sleep_ms(ms: u32) {
+    #[cfg(any(test, feature = "mock-host"))]
+    if crate::mock_host::sleep(ms).is_some() {
+        return;
+    }
+    std::thread::sleep(std::time::Duration::from_millis(u64::from(ms)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn fixed_clock_returns_its_value() {
+        assert_eq!(FixedClock(42).now(), 42);
+        assert_eq!(BoxedClock::fixed(7).now(), 7);
+    }
+
+    #[test]
+    fn host_clock_reads_system_time_on_native() {
+        // Any date after 2020-01-01 proves it isn't a zeroed stub.
+        assert!(HostClock.now() > 1_577_836_800);
+        assert!(BoxedClock::default().now() > 1_577_836_800);
+    }
+
+    #[test]
+    fn boxed_clock_accepts_custom_implementations() {
+        struct SharedClock(Rc<Cell<u64>>);
+        impl Clock for SharedClock {
+            fn now(&self) -> u64 {
+                self.0.get()
+            }
+        }
+
+        let time = Rc::new(Cell::new(100));
+        let clock = BoxedClock::new(SharedClock(Rc::clone(&time)));
+        assert_eq!(clock.now(), 100);
+        time.set(200);
+        assert_eq!(clock.now(), 200);
+    }
+}
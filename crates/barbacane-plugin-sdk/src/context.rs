@@ -0,0 +1,74 @@
+//! Per-request context via the `host_context_get` / `host_context_set` imports.
+//!
+//! Middlewares share values along the chain (e.g. `correlation-id`) through the
+//! host's request context. On non-wasm targets these read and write the
+//! `mock_host::MockHost` context (`mock-host` feature), and are no-ops
+//! without one.
+//!
+//! ```
+//! use barbacane_plugin_sdk::context;
+//! context::set("correlation-id", "abc");
+//! let _id: Option<String> = context::get("correlation-id");
+//! ```
+
+/// Read a value from the request context.
+#[cfg(target_arch = "wasm32")]
+pub fn get(key: &str) -> Option<String> {
+    #[link(wasm_import_module = "barbacane")]
+    extern "C" {
+        fn host_context_get(key_ptr: i32, key_len: i32) -> i32;
+        fn host_context_read_result(buf_ptr: i32, buf_len: i32) -> i32;
+    }
+
+    let len = unsafe { host_context_get(key.as_ptr() as i32, key.len() as i32) };
+    if len <= 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u8; len as usize];
+    let read_len = unsafe { host_context_read_result(buf.as_mut_ptr() as i32, len) };
+    if read_len != len {
+        return None;
+    }
+
+    String::from_utf8(buf).ok()
+}
+
+/// Store a value in the request context.
+#[cfg(target_arch = "wasm32")]
+pub fn set(key: &str, value: &str) {
+    #[link(wasm_import_module = "barbacane")]
+    extern "C" {
+        fn host_context_set(key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32);
+    }
+    unsafe {
+        host_context_set(
+            key.as_ptr() as i32,
+            key.len() as i32,
+            value.as_ptr() as i32,
+            value.len() as i32,
+        );
+    }
+}
+
+/// Native: reads the mock host's context.
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn get(key: &str) -> Option<String> {
+    crate::mock_host::context_get(key)
+}
+
+/// Native: writes the mock host's context.
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn set(key: &str, value: &str) {
+    crate::mock_host::context_set(key, value)
+}
+
+/// Native without a mock host: there is no request context.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
+pub fn get(_key: &str) -> Option<String> {
+    None
+}
+
+/// Native without a mock host: a no-op.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
+pub fn set(_key: &str, _value: &str) {}
@@ -0,0 +1,76 @@
+//! Signature and password verification via the `host_verify_signature` and
+//! `host_verify_password` imports.
+//!
+//! Crypto runs in the host, not the sandbox: the plugin sends a JSON request
+//! (`algorithm`, `jwk`, `message`, `signature`), or a stored password hash and
+//! a candidate password, and gets back a verdict. On non-wasm targets the
+//! `mock_host::MockHost` answers (`mock-host` feature); without one installed
+//! every call is a host error.
+
+/// Verify a signature. `request_json` is the serialized verification request.
+///
+/// Returns `Some(true)` if valid, `Some(false)` if invalid, and `None` if the
+/// host could not evaluate it (malformed key, unsupported algorithm, ...).
+#[cfg(target_arch = "wasm32")]
+pub fn verify_signature(request_json: &[u8]) -> Option<bool> {
+    #[link(wasm_import_module = "barbacane")]
+    extern "C" {
+        fn host_verify_signature(req_ptr: i32, req_len: i32) -> i32;
+    }
+    let result =
+        unsafe { host_verify_signature(request_json.as_ptr() as i32, request_json.len() as i32) };
+    match result {
+        1 => Some(true),
+        0 => Some(false),
+        _ => None,
+    }
+}
+
+/// Native: answered by the mock host (host error when none is installed).
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn verify_signature(request_json: &[u8]) -> Option<bool> {
+    crate::mock_host::verify_signature(request_json).flatten()
+}
+
+/// Native without a mock host: a host error.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
+pub fn verify_signature(_request_json: &[u8]) -> Option<bool> {
+    None
+}
+
+/// Verify `password` against a stored bcrypt or argon2 (PHC string) hash.
+///
+/// Returns `Some(true)` on a match, `Some(false)` on a mismatch, and `None`
+/// if the host could not evaluate it (malformed hash, unsupported scheme, ...).
+#[cfg(target_arch = "wasm32")]
+pub fn verify_password(hash: &str, password: &[u8]) -> Option<bool> {
+    #[link(wasm_import_module = "barbacane")]
+    extern "C" {
+        fn host_verify_password(hash_ptr: i32, hash_len: i32, pw_ptr: i32, pw_len: i32) -> i32;
+    }
+    let result = unsafe {
+        host_verify_password(
+            hash.as_ptr() as i32,
+            hash.len() as i32,
+            password.as_ptr() as i32,
+            password.len() as i32,
+        )
+    };
+    match result {
+        1 => Some(true),
+        0 => Some(false),
+        _ => None,
+    }
+}
+
+/// Native: answered by the mock host (host error when none is installed).
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn verify_password(hash: &str, password: &[u8]) -> Option<bool> {
+    crate::mock_host::verify_password(hash, password).flatten()
+}
+
+/// Native without a mock host: a host error.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
+pub fn verify_password(_hash: &str, _password: &[u8]) -> Option<bool> {
+    None
+}
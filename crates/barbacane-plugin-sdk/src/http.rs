@@ -5,7 +5,14 @@
 //! them. The request/response body travels via the side-channel body functions
 //! in [`crate::body`], not in the JSON.
 //!
-//! On non-wasm targets (unit tests) [`call`] returns [`HttpError::Unsupported`].
+//! On non-wasm targets (unit tests) [`call`] is answered by an installed
+//! `mock_host::MockHost` (`mock-host` feature), and returns
+//! [`HttpError::Unsupported`] otherwise.
+//!
+//! [`call_with_retry`] adds a bounded retry loop for idempotent requests,
+//! backing off through [`crate::clock::sleep_ms`] (the `sleep` capability).
+//! [`CircuitBreaker`] fast-fails calls to an upstream that keeps failing.
+//! [`is_hop_by_hop`] tells proxying dispatchers which headers to drop.
 
 use std::collections::BTreeMap;
 
@@ -119,16 +126,190 @@ pub fn call(request: &HttpRequest, body_bytes: Option<&[u8]>) -> Result<HttpResp
     Ok(resp)
 }
 
-/// Non-wasm stub: no host to call.
-#[cfg(not(target_arch = "wasm32"))]
+/// Native: answered by the mock host, if one is installed.
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn call(request: &HttpRequest, body_bytes: Option<&[u8]>) -> Result<HttpResponse, HttpError> {
+    crate::mock_host::http_call(request, body_bytes).unwrap_or(Err(HttpError::Unsupported))
+}
+
+/// Native without a mock host: outbound HTTP is unsupported.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
 pub fn call(_request: &HttpRequest, _body_bytes: Option<&[u8]>) -> Result<HttpResponse, HttpError> {
     Err(HttpError::Unsupported)
 }
 
+/// Upper bound on [`RetryPolicy::max_retries`]. The host lifts the execution
+/// deadline while a plugin sleeps, so an unbounded count could hold a worker
+/// for as long as the config asks.
+pub const MAX_RETRIES: u32 = 10;
+
+/// Retry settings for [`call_with_retry`], deserializable from plugin config.
+/// The default performs no retries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct RetryPolicy {
+    /// Extra attempts after the first one, clamped to [`MAX_RETRIES`].
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Delay before the first retry, in milliseconds; doubled on each further
+    /// retry.
+    #[serde(default)]
+    pub backoff_ms: u64,
+}
+
+impl RetryPolicy {
+    /// Retries actually performed: `max_retries`, clamped to [`MAX_RETRIES`].
+    fn retries(&self) -> u32 {
+        self.max_retries.min(MAX_RETRIES)
+    }
+
+    /// Delay before retry number `retry` (0-based).
+    fn delay_ms(&self, retry: u32) -> u32 {
+        let factor = 1u64.checked_shl(retry).unwrap_or(u64::MAX);
+        u32::try_from(self.backoff_ms.saturating_mul(factor)).unwrap_or(u32::MAX)
+    }
+}
+
+/// Whether `method` may be retried without risking a duplicated side effect.
+fn is_idempotent(method: &str) -> bool {
+    method.eq_ignore_ascii_case("GET") || method.eq_ignore_ascii_case("HEAD")
+}
+
+/// Whether a result counts as an upstream failure (worth retrying, and tripping
+/// a [`CircuitBreaker`]): the upstream was unreachable or answered with a 5xx
+/// (the host also reports timeouts as 5xx).
+fn is_upstream_failure(result: &Result<HttpResponse, HttpError>) -> bool {
+    match result {
+        Ok(resp) => resp.status >= 500,
+        Err(e) => *e == HttpError::Unreachable,
+    }
+}
+
+/// [`call`], retrying GET/HEAD requests on connection failure or a 5xx up to
+/// `policy.max_retries` times (at most [`MAX_RETRIES`]), sleeping with exponential backoff in between.
+/// Other methods are sent once. When retries run out, the last result is
+/// returned as is.
+pub fn call_with_retry(
+    request: &HttpRequest,
+    body_bytes: Option<&[u8]>,
+    policy: &RetryPolicy,
+) -> Result<HttpResponse, HttpError> {
+    let retries = if is_idempotent(&request.method) {
+        policy.retries()
+    } else {
+        0
+    };
+
+    let mut result = call(request, body_bytes);
+    for retry in 0..retries {
+        if !is_upstream_failure(&result) {
+            break;
+        }
+        let delay = policy.delay_ms(retry);
+        if delay > 0 {
+            crate::clock::sleep_ms(delay);
+        }
+        result = call(request, body_bytes);
+    }
+    result
+}
+
+/// Consecutive-failure circuit breaker, kept in plugin instance state.
+///
+/// Deserializes from a config object with `failure_threshold` (0, the default,
+/// disables the breaker) and `open_seconds`. After `failure_threshold`
+/// consecutive failures the circuit opens and [`allow`](Self::allow) refuses
+/// calls for `open_seconds`; the next call after that is a half-open probe that
+/// closes the circuit on success or reopens it on failure.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CircuitBreaker {
+    /// Consecutive failures that open the circuit. 0 disables the breaker.
+    #[serde(default)]
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a probe is let through.
+    #[serde(default = "default_open_seconds")]
+    pub open_seconds: u64,
+    #[serde(skip)]
+    consecutive_failures: u32,
+    #[serde(skip)]
+    opened_at: Option<u64>,
+}
+
+fn default_open_seconds() -> u64 {
+    30
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(0, default_open_seconds())
+    }
+}
+
+impl CircuitBreaker {
+    /// A closed breaker with the given settings.
+    pub fn new(failure_threshold: u32, open_seconds: u64) -> Self {
+        Self {
+            failure_threshold,
+            open_seconds,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+
+    /// Whether the circuit is open (refusing calls) at `now` (Unix seconds).
+    pub fn is_open(&self, now: u64) -> bool {
+        self.opened_at
+            .is_some_and(|opened| now < opened.saturating_add(self.open_seconds))
+    }
+
+    /// Whether a call may go out at `now`. `false` means fast-fail.
+    pub fn allow(&self, now: u64) -> bool {
+        !self.is_open(now)
+    }
+
+    /// Record the outcome of a call made at `now`.
+    pub fn record(&mut self, result: &Result<HttpResponse, HttpError>, now: u64) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        if !is_upstream_failure(result) {
+            self.consecutive_failures = 0;
+            self.opened_at = None;
+            return;
+        }
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        // A failed half-open probe reopens immediately.
+        if self.opened_at.is_some() || self.consecutive_failures >= self.failure_threshold {
+            self.opened_at = Some(now);
+        }
+    }
+}
+
+/// Hop-by-hop headers (RFC 9110 §7.6.1) apply to a single connection and are
+/// never forwarded in either direction.
+pub fn is_hop_by_hop(name: &str) -> bool {
+    const HOP_BY_HOP: &[&str] = &[
+        "connection",
+        "keep-alive",
+        "transfer-encoding",
+        "te",
+        "trailer",
+        "upgrade",
+    ];
+    HOP_BY_HOP.iter().any(|h| name.eq_ignore_ascii_case(h))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn hop_by_hop_is_case_insensitive() {
+        assert!(is_hop_by_hop("Transfer-Encoding"));
+        assert!(is_hop_by_hop("te"));
+        assert!(!is_hop_by_hop("content-type"));
+        assert!(!is_hop_by_hop("tea"));
+    }
+
     #[test]
     fn request_serializes_without_body_field() {
         let req = HttpRequest::new("POST", "https://upstream/x")
@@ -156,4 +337,147 @@ mod tests {
             Err(HttpError::Unsupported)
         ));
     }
+
+    fn status(status: u16) -> Result<HttpResponse, HttpError> {
+        Ok(HttpResponse {
+            status,
+            headers: BTreeMap::new(),
+            body: None,
+        })
+    }
+
+    #[test]
+    fn retry_succeeds_on_second_attempt() {
+        use crate::mock_host::MockHost;
+
+        let host = MockHost::install();
+        host.on_http_once("GET", "https://store/obj", status(503))
+            .on_http_once("GET", "https://store/obj", status(200));
+        let policy = RetryPolicy {
+            max_retries: 3,
+            backoff_ms: 50,
+        };
+
+        let resp =
+            call_with_retry(&HttpRequest::new("GET", "https://store/obj"), None, &policy).unwrap();
+        assert_eq!(resp.status, 200);
+        assert_eq!(host.http_calls().len(), 2);
+        assert_eq!(host.sleeps(), vec![50]);
+    }
+
+    #[test]
+    fn retry_exhaustion_returns_last_result() {
+        use crate::mock_host::MockHost;
+
+        let host = MockHost::install();
+        host.on_http_once("HEAD", "https://store/obj", status(502));
+        let policy = RetryPolicy {
+            max_retries: 2,
+            backoff_ms: 10,
+        };
+
+        // 502, then the unscripted URL is unreachable on both retries.
+        let result = call_with_retry(
+            &HttpRequest::new("HEAD", "https://store/obj"),
+            None,
+            &policy,
+        );
+        assert_eq!(result.unwrap_err(), HttpError::Unreachable);
+        assert_eq!(host.http_calls().len(), 3);
+        assert_eq!(host.sleeps(), vec![10, 20]);
+    }
+
+    #[test]
+    fn retry_count_is_clamped() {
+        use crate::mock_host::MockHost;
+
+        let host = MockHost::install();
+        let policy = RetryPolicy {
+            max_retries: 1000,
+            backoff_ms: 1,
+        };
+
+        let result = call_with_retry(&HttpRequest::new("GET", "https://store/obj"), None, &policy);
+        assert_eq!(result.unwrap_err(), HttpError::Unreachable);
+        assert_eq!(host.http_calls().len(), 1 + MAX_RETRIES as usize);
+        assert_eq!(host.sleeps().len(), MAX_RETRIES as usize);
+    }
+
+    #[test]
+    fn non_idempotent_methods_are_not_retried() {
+        use crate::mock_host::MockHost;
+
+        let host = MockHost::install();
+        host.on_http_once("PUT", "https://store/obj", status(503))
+            .on_http_once("PUT", "https://store/obj", status(200));
+        let policy = RetryPolicy {
+            max_retries: 3,
+            backoff_ms: 10,
+        };
+
+        let resp = call_with_retry(
+            &HttpRequest::new("PUT", "https://store/obj"),
+            Some(b"data"),
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(resp.status, 503);
+        assert_eq!(host.http_calls().len(), 1);
+        assert!(host.sleeps().is_empty());
+    }
+
+    #[test]
+    fn retry_policy_defaults_to_no_retries() {
+        let policy: RetryPolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(policy, RetryPolicy::default());
+        assert_eq!(
+            RetryPolicy {
+                max_retries: 0,
+                backoff_ms: u64::MAX
+            }
+            .delay_ms(40),
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn circuit_breaker_opens_fast_fails_and_recovers() {
+        let mut breaker = CircuitBreaker::new(3, 10);
+        let failure = Err(HttpError::Unreachable);
+
+        breaker.record(&failure, 100);
+        breaker.record(&status(500), 101);
+        assert!(breaker.allow(101));
+        breaker.record(&failure, 102);
+
+        // Open: fast-fail until open_seconds have passed.
+        assert!(!breaker.allow(102));
+        assert!(!breaker.allow(111));
+        assert!(breaker.allow(112));
+
+        // The half-open probe fails: reopen for another period.
+        breaker.record(&status(503), 112);
+        assert!(!breaker.allow(113));
+
+        // The next probe succeeds: closed, failure count reset.
+        breaker.record(&status(200), 122);
+        assert!(breaker.allow(122));
+        breaker.record(&failure, 123);
+        assert!(breaker.allow(123));
+    }
+
+    #[test]
+    fn circuit_breaker_disabled_by_default() {
+        let mut breaker: CircuitBreaker = serde_json::from_str("{}").unwrap();
+        assert_eq!(breaker.open_seconds, 30);
+        for now in 0..10 {
+            breaker.record(&Err(HttpError::Unreachable), now);
+        }
+        assert!(breaker.allow(10));
+
+        // 4xx responses are the client's problem, not an upstream failure.
+        let mut breaker = CircuitBreaker::new(1, 10);
+        breaker.record(&status(404), 0);
+        assert!(breaker.allow(0));
+    }
 }
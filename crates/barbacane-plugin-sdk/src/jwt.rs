@@ -1,7 +1,7 @@
 //! JWT parsing helpers shared by the auth plugins.
 //!
 //! These cover the *parsing* that jwt-auth / oidc-auth / oauth2-auth each
-//! duplicated: Bearer extraction, base64url segment decoding, an `aud` claim
+//! duplicated: token extraction, base64url segment decoding, an `aud` claim
 //! that may be a string or array, and decoding the payload for inspection.
 //!
 //! Signature verification is **not** done here — that is the host's job
@@ -11,6 +11,8 @@
 use base64::Engine;
 use serde::{Deserialize, Serialize};
 
+use crate::types::Request;
+
 /// The JWT `aud` claim: a single audience or a list (RFC 7519 §4.1.3).
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -27,6 +29,20 @@ impl Audience {
             Audience::Multiple(v) => v.iter().any(|s| s == value),
         }
     }
+
+    /// The audience value(s) as a slice.
+    pub fn values(&self) -> &[String] {
+        match self {
+            Audience::Single(s) => std::slice::from_ref(s),
+            Audience::Multiple(v) => v,
+        }
+    }
+
+    /// Whether any of `expected` is among the audience(s). Used to match a
+    /// token's `aud` against one or more configured audiences.
+    pub fn contains_any(&self, expected: &Audience) -> bool {
+        expected.values().iter().any(|value| self.contains(value))
+    }
 }
 
 /// Errors decoding a JWT's structure (not signature validity).
@@ -55,6 +71,189 @@ pub fn bearer_token(authorization: &str) -> Option<&str> {
     }
 }
 
+/// Where an auth plugin looks for the token. Configured as an ordered
+/// `token_sources` list; the first source that yields a token wins.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSource {
+    /// A request header (name matched case-insensitively). With a `scheme`
+    /// the value must be `<scheme> <token>`; without one the whole value is
+    /// the token.
+    Header {
+        name: String,
+        #[serde(default)]
+        scheme: Option<String>,
+    },
+    /// A query-string parameter, percent-decoded.
+    Query { name: String },
+}
+
+impl TokenSource {
+    /// `Authorization: Bearer <token>`, the source used when none are configured.
+    pub fn authorization_bearer() -> Self {
+        TokenSource::Header {
+            name: "authorization".to_string(),
+            scheme: Some("Bearer".to_string()),
+        }
+    }
+
+    /// The header this source reads, if it is a header source.
+    pub fn header_name(&self) -> Option<&str> {
+        match self {
+            TokenSource::Header { name, .. } => Some(name),
+            TokenSource::Query { .. } => None,
+        }
+    }
+}
+
+/// Why [`extract_token`] found no token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSourceError {
+    /// None of the sources were present.
+    Missing,
+    /// A header source was present but empty or used a different scheme.
+    Malformed,
+}
+
+/// Extract the token from the first of `sources` that yields one. An empty
+/// `sources` means [`TokenSource::authorization_bearer`]. A malformed header
+/// does not stop later sources from being tried; it is only reported when
+/// none of them match.
+pub fn extract_token(req: &Request, sources: &[TokenSource]) -> Result<String, TokenSourceError> {
+    let default = [TokenSource::authorization_bearer()];
+    let sources = if sources.is_empty() {
+        &default[..]
+    } else {
+        sources
+    };
+
+    let mut malformed = false;
+    for source in sources {
+        match source {
+            TokenSource::Header { name, scheme } => {
+                let Some(value) = req
+                    .headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                    .map(|(_, v)| v.as_str())
+                else {
+                    continue;
+                };
+                match scheme_token(value, scheme.as_deref()) {
+                    Some(token) => return Ok(token.to_string()),
+                    None => malformed = true,
+                }
+            }
+            TokenSource::Query { name } => {
+                let token = req.query.as_deref().and_then(|query| {
+                    query
+                        .split('&')
+                        .filter_map(|pair| pair.split_once('='))
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| percent_decode(value))
+                });
+                if let Some(token) = token.filter(|t| !t.is_empty()) {
+                    return Ok(token);
+                }
+            }
+        }
+    }
+
+    Err(if malformed {
+        TokenSourceError::Malformed
+    } else {
+        TokenSourceError::Missing
+    })
+}
+
+/// Header set on requests let through without credentials by an auth plugin
+/// in optional mode.
+pub const ANONYMOUS_HEADER: &str = "x-auth-anonymous";
+
+/// Default prefix of the identity headers auth plugins inject (`x-auth-sub`,
+/// `x-auth-consumer`, ...). Plugins may let users override it with a
+/// `claim_header_prefix` config field.
+pub const DEFAULT_CLAIM_HEADER_PREFIX: &str = "x-auth-";
+
+/// Name of the injected identity header `name` under `prefix`, or under
+/// [`DEFAULT_CLAIM_HEADER_PREFIX`] when unset: `claim_header(Some("x-consumer-"),
+/// "sub")` is `x-consumer-sub`.
+pub fn claim_header(prefix: Option<&str>, name: &str) -> String {
+    format!("{}{}", prefix.unwrap_or(DEFAULT_CLAIM_HEADER_PREFIX), name)
+}
+
+/// Prepare a request that carries no token to continue anonymously: drop any
+/// client-supplied `x-auth-*` headers, which would otherwise pass for a
+/// verified identity downstream, and set [`ANONYMOUS_HEADER`] to `true`.
+pub fn mark_anonymous(req: &mut Request) {
+    mark_anonymous_with_prefix(req, None);
+}
+
+/// [`mark_anonymous`] for a custom claim header prefix: drops client-supplied
+/// headers under both the default and the custom prefix, and sets
+/// `<prefix>anonymous`.
+pub fn mark_anonymous_with_prefix(req: &mut Request, prefix: Option<&str>) {
+    strip_claim_headers(req, prefix, &[]);
+    req.headers
+        .insert(claim_header(prefix, "anonymous"), "true".to_string());
+}
+
+/// Drop client-supplied identity headers — `x-auth-*`, plus `<prefix>*` for a
+/// custom prefix — so only values set by the auth plugin reach the upstream.
+/// Auth plugins call this first thing in `on_request`. Headers named in `keep`
+/// (matched case-insensitively, e.g. a token source configured as
+/// `x-auth-token`) are left in place.
+pub fn strip_claim_headers(req: &mut Request, prefix: Option<&str>, keep: &[&str]) {
+    let custom = prefix.map(str::to_ascii_lowercase);
+    req.headers.retain(|name, _| {
+        if keep.iter().any(|k| k.eq_ignore_ascii_case(name)) {
+            return true;
+        }
+        let name = name.to_ascii_lowercase();
+        !name.starts_with(DEFAULT_CLAIM_HEADER_PREFIX)
+            && custom.as_deref().is_none_or(|p| !name.starts_with(p))
+    });
+}
+
+/// The token in a header value, after `scheme` (case-insensitive) if given.
+fn scheme_token<'a>(value: &'a str, scheme: Option<&str>) -> Option<&'a str> {
+    let token = match scheme {
+        Some(scheme) => {
+            let (actual, rest) = value.split_once(' ')?;
+            if !actual.eq_ignore_ascii_case(scheme) {
+                return None;
+            }
+            rest.trim()
+        }
+        None => value.trim(),
+    };
+    if token.is_empty() {
+        None
+    } else {
+        Some(token)
+    }
+}
+
+/// Decode `%XX` escapes, leaving invalid escapes (and `+`) as-is.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
 /// Split a compact JWT into its three base64url segments (header, payload,
 /// signature). Returns `None` if the token is not exactly three segments.
 pub fn split(token: &str) -> Option<(&str, &str, &str)> {
@@ -99,6 +298,16 @@ mod tests {
         assert!(!multi.contains("c"));
     }
 
+    #[test]
+    fn audience_contains_any_of_expected() {
+        let token = Audience::Multiple(vec!["a".into(), "b".into()]);
+        assert!(token.contains_any(&Audience::Single("b".into())));
+        assert!(token.contains_any(&Audience::Multiple(vec!["x".into(), "a".into()])));
+        assert!(!token.contains_any(&Audience::Multiple(vec!["x".into(), "y".into()])));
+        assert!(!token.contains_any(&Audience::Multiple(vec![])));
+        assert_eq!(Audience::Single("a".into()).values(), ["a".to_string()]);
+    }
+
     #[test]
     fn audience_deserializes_string_or_array() {
         let s: Audience = serde_json::from_str(r#""api""#).unwrap();
@@ -117,6 +326,157 @@ mod tests {
         assert_eq!(bearer_token("token"), None);
     }
 
+    fn request(headers: &[(&str, &str)], query: Option<&str>) -> Request {
+        Request {
+            method: "GET".into(),
+            path: "/".into(),
+            query: query.map(str::to_string),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: None,
+            client_ip: "127.0.0.1".into(),
+            path_params: Default::default(),
+        }
+    }
+
+    #[test]
+    fn token_sources_default_to_authorization_bearer() {
+        let req = request(&[("Authorization", "bearer abc")], None);
+        assert_eq!(extract_token(&req, &[]), Ok("abc".to_string()));
+        let req = request(&[("authorization", "Basic abc")], None);
+        assert_eq!(extract_token(&req, &[]), Err(TokenSourceError::Malformed));
+        let req = request(&[], Some("access_token=abc"));
+        assert_eq!(extract_token(&req, &[]), Err(TokenSourceError::Missing));
+    }
+
+    #[test]
+    fn token_from_query_param_and_bare_header() {
+        let query = [TokenSource::Query {
+            name: "token".into(),
+        }];
+        let req = request(&[], Some("a=1&token=eyJ%2Bx%3D&b=2"));
+        assert_eq!(extract_token(&req, &query), Ok("eyJ+x=".to_string()));
+        let req = request(&[], Some("token="));
+        assert_eq!(extract_token(&req, &query), Err(TokenSourceError::Missing));
+
+        let header = [TokenSource::Header {
+            name: "X-Api-Token".into(),
+            scheme: None,
+        }];
+        let req = request(&[("x-api-token", " abc ")], None);
+        assert_eq!(extract_token(&req, &header), Ok("abc".to_string()));
+    }
+
+    #[test]
+    fn token_sources_tried_in_order() {
+        let sources = [
+            TokenSource::authorization_bearer(),
+            TokenSource::Header {
+                name: "x-token".into(),
+                scheme: None,
+            },
+            TokenSource::Query {
+                name: "access_token".into(),
+            },
+        ];
+        let all = request(
+            &[("authorization", "Bearer from-auth"), ("x-token", "from-x")],
+            Some("access_token=from-query"),
+        );
+        assert_eq!(extract_token(&all, &sources), Ok("from-auth".to_string()));
+
+        // A malformed earlier source falls through to the next one.
+        let fallback = request(
+            &[("authorization", "Basic creds")],
+            Some("access_token=from-query"),
+        );
+        assert_eq!(
+            extract_token(&fallback, &sources),
+            Ok("from-query".to_string())
+        );
+
+        let config: Vec<TokenSource> = serde_json::from_value(serde_json::json!([
+            {"header": {"name": "authorization", "scheme": "Bearer"}},
+            {"header": {"name": "x-token"}},
+            {"query": {"name": "access_token"}}
+        ]))
+        .unwrap();
+        assert_eq!(config, sources);
+    }
+
+    #[test]
+    fn mark_anonymous_strips_client_identity_headers() {
+        let mut req = request(
+            &[
+                ("X-Auth-Consumer", "admin"),
+                ("x-auth-consumer-groups", "admin"),
+                ("x-request-id", "r1"),
+            ],
+            None,
+        );
+        mark_anonymous(&mut req);
+        assert_eq!(
+            req.headers.keys().collect::<Vec<_>>(),
+            ["x-auth-anonymous", "x-request-id"]
+        );
+        assert_eq!(req.headers[ANONYMOUS_HEADER], "true");
+    }
+
+    #[test]
+    fn custom_claim_header_prefix() {
+        assert_eq!(claim_header(None, "anonymous"), ANONYMOUS_HEADER);
+        assert_eq!(claim_header(Some("x-consumer-"), "sub"), "x-consumer-sub");
+
+        let mut req = request(
+            &[
+                ("X-Consumer-Sub", "admin"),
+                ("x-auth-consumer", "admin"),
+                ("x-request-id", "r1"),
+            ],
+            None,
+        );
+        mark_anonymous_with_prefix(&mut req, Some("x-consumer-"));
+        assert_eq!(
+            req.headers.keys().collect::<Vec<_>>(),
+            ["x-consumer-anonymous", "x-request-id"]
+        );
+    }
+
+    #[test]
+    fn strip_claim_headers_keeps_token_source_headers() {
+        let mut req = request(
+            &[
+                ("x-auth-sub", "admin"),
+                ("X-Auth-Token", "opaque"),
+                ("x-consumer-groups", "admin"),
+                ("authorization", "Bearer t"),
+            ],
+            None,
+        );
+        let source = TokenSource::Header {
+            name: "x-auth-token".to_string(),
+            scheme: None,
+        };
+        strip_claim_headers(
+            &mut req,
+            Some("x-consumer-"),
+            &[source.header_name().unwrap()],
+        );
+        assert_eq!(
+            req.headers.keys().collect::<Vec<_>>(),
+            ["X-Auth-Token", "authorization"]
+        );
+        assert_eq!(
+            TokenSource::Query {
+                name: "access_token".to_string()
+            }
+            .header_name(),
+            None
+        );
+    }
+
     #[test]
     fn decode_claims_reads_payload_without_verifying() {
         // {"sub":"u1","aud":"api"} as base64url, dummy header/sig.
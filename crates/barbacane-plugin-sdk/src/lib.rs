@@ -28,10 +28,19 @@
 //! ```
 
 pub mod body;
+pub mod cache;
+pub mod clock;
+pub mod context;
+pub mod crypto;
 pub mod errors;
 pub mod http;
 pub mod jwt;
 pub mod log;
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub mod mock_host;
+pub mod net;
+#[cfg(feature = "transform")]
+pub mod transform;
 pub mod types;
 
 /// Re-export proc macros for plugin development.
@@ -42,6 +51,7 @@ pub mod prelude {
     pub use crate::errors::ProblemDetails;
     pub use crate::types::*;
     pub use crate::{barbacane_dispatcher, barbacane_middleware};
-    // `http`, `jwt`, and `log` are used via their module path (e.g. `log::warn`,
+    // `cache`, `clock`, `context`, `crypto`, `http`, `jwt`, `log`, `net`, and
+    // `transform` are used via their module path (e.g. `log::warn`,
     // `jwt::Audience`, `http::call`) to keep the prelude unambiguous.
 }
@@ -1,7 +1,9 @@
 //! Host logging via the `host_log` import.
 //!
 //! Plugins each redeclared the `host_log` extern + a wasm/native cfg wrapper.
-//! This centralizes it. On non-wasm targets (unit tests) logging is a no-op.
+//! This centralizes it. On non-wasm targets (unit tests) lines are recorded by
+//! an installed `mock_host::MockHost` (`mock-host` feature) and dropped
+//! otherwise.
 //!
 //! ```
 //! use barbacane_plugin_sdk::log;
@@ -26,8 +28,14 @@ pub fn log(level: i32, msg: &str) {
     }
 }
 
-/// No-op on non-wasm targets (native unit tests).
-#[cfg(not(target_arch = "wasm32"))]
+/// Native: recorded by the mock host, if one is installed.
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "mock-host")))]
+pub fn log(level: i32, msg: &str) {
+    crate::mock_host::log(level, msg)
+}
+
+/// Native without a mock host: dropped.
+#[cfg(all(not(target_arch = "wasm32"), not(any(test, feature = "mock-host"))))]
 pub fn log(_level: i32, _msg: &str) {}
 
 /// Log at ERROR level.
@@ -50,6 +58,26 @@ pub fn debug(msg: &str) {
     log(LEVEL_DEBUG, msg);
 }
 
+/// Record an auth plugin letting a request through, as one compact JSON line
+/// at WARN: `{"plugin":"jwt-auth","decision":"allow","sub":"user-123"}`.
+/// `sub` is `None` for anonymous requests.
+pub fn access_allowed(plugin: &str, sub: Option<&str>) {
+    let line = serde_json::json!({"plugin": plugin, "decision": "allow", "sub": sub});
+    warn(&line.to_string());
+}
+
+/// Record an auth plugin rejecting a request, with its error code as `reason`:
+/// `{"plugin":"jwt-auth","decision":"deny","reason":"invalid_token","sub":null}`.
+pub fn access_denied(plugin: &str, reason: &str) {
+    let line = serde_json::json!({
+        "plugin": plugin,
+        "decision": "deny",
+        "reason": reason,
+        "sub": null
+    });
+    warn(&line.to_string());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,4 +90,26 @@ mod tests {
         info("i");
         debug("d");
     }
+
+    #[test]
+    fn access_decisions_are_json_lines() {
+        let host = crate::mock_host::MockHost::install();
+        access_allowed("jwt-auth", Some("alice"));
+        access_denied("jwt-auth", "invalid_token");
+        let logs: Vec<serde_json::Value> = host
+            .logs()
+            .iter()
+            .map(|(level, line)| {
+                assert_eq!(*level, LEVEL_WARN);
+                serde_json::from_str(line).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            logs,
+            [
+                serde_json::json!({"plugin": "jwt-auth", "decision": "allow", "sub": "alice"}),
+                serde_json::json!({"plugin": "jwt-auth", "decision": "deny", "reason": "invalid_token", "sub": null}),
+            ]
+        );
+    }
 }
@@ -0,0 +1,400 @@
+//! In-memory stand-in for the gateway host, for native (non-wasm) tests.
+//!
+//! Plugins used to grow their own `mock_host` / `mock_time` modules and per-
+//! import native stubs. On non-wasm targets the SDK's host bindings
+//! ([`crate::http::call`], [`crate::crypto::verify_signature`],
+//! [`crate::crypto::verify_password`], [`crate::context`], [`crate::cache`],
+//! [`crate::clock::HostClock`], [`crate::clock::sleep_ms`], [`crate::log`])
+//! consult a [`MockHost`] installed on the current thread, so a test can
+//! script the host and drive a plugin end to end. Without one installed they
+//! keep their plain native behavior (HTTP unsupported, host errors, system
+//! clock).
+//!
+//! The module is behind the `mock-host` cargo feature, which plugins enable
+//! from `[dev-dependencies]` only; non-test builds compile the plain native
+//! behavior and never consult a test registry.
+//!
+//! State is thread-local: each `#[test]` runs on its own thread, and the host
+//! is cleared when the [`MockHost`] guard is dropped.
+//!
+//! ```
+//! use barbacane_plugin_sdk::http::{self, HttpRequest};
+//! use barbacane_plugin_sdk::mock_host::MockHost;
+//!
+//! let host = MockHost::install();
+//! host.on_http_json("GET", "https://idp/keys", 200, &serde_json::json!({"keys": []}));
+//!
+//! let resp = http::call(&HttpRequest::new("GET", "https://idp/keys"), None).unwrap();
+//! assert_eq!(resp.status, 200);
+//! assert_eq!(host.http_calls().len(), 1);
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use crate::cache::CacheEntry;
+use crate::http::{HttpError, HttpRequest, HttpResponse};
+
+/// An outbound HTTP call observed by the mock host.
+#[derive(Debug, Clone)]
+pub struct RecordedHttpCall {
+    pub request: HttpRequest,
+    pub body: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct HostState {
+    http_routes: Vec<(String, String, HttpResponse)>,
+    http_once: Vec<(String, String, Result<HttpResponse, HttpError>)>,
+    http_calls: Vec<RecordedHttpCall>,
+    verify_result: Option<bool>,
+    verify_requests: Vec<serde_json::Value>,
+    passwords: BTreeMap<String, Vec<u8>>,
+    password_checks: Vec<String>,
+    context: BTreeMap<String, String>,
+    cache: BTreeMap<String, (CacheEntry, u32)>,
+    time: Option<u64>,
+    logs: Vec<(i32, String)>,
+    sleeps: Vec<u32>,
+}
+
+thread_local! {
+    static HOST: RefCell<Option<HostState>> = const { RefCell::new(None) };
+}
+
+/// Run `f` against the installed host state, if any.
+fn with_host<R>(f: impl FnOnce(&mut HostState) -> R) -> Option<R> {
+    HOST.with(|host| host.borrow_mut().as_mut().map(f))
+}
+
+/// Guard for a scripted host on the current thread. Dropping it uninstalls
+/// the host.
+pub struct MockHost {
+    // Host state is per-thread; keep the guard on the thread that made it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl MockHost {
+    /// Install a fresh host on this thread, replacing any previous one.
+    pub fn install() -> Self {
+        HOST.with(|host| *host.borrow_mut() = Some(HostState::default()));
+        Self {
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Answer every `method url` call with `response`. Calls without a
+    /// matching route fail with [`HttpError::Unreachable`].
+    pub fn on_http(&self, method: &str, url: &str, response: HttpResponse) -> &Self {
+        with_host(|h| {
+            h.http_routes
+                .push((method.to_ascii_uppercase(), url.to_string(), response))
+        });
+        self
+    }
+
+    /// [`on_http`](Self::on_http) with a JSON body.
+    pub fn on_http_json(
+        &self,
+        method: &str,
+        url: &str,
+        status: u16,
+        body: &serde_json::Value,
+    ) -> &Self {
+        let mut headers = BTreeMap::new();
+        headers.insert("content-type".to_string(), "application/json".to_string());
+        self.on_http(
+            method,
+            url,
+            HttpResponse {
+                status,
+                headers,
+                body: Some(body.to_string().into_bytes()),
+            },
+        )
+    }
+
+    /// Answer the next `method url` call with `result`, once. One-shot answers
+    /// queue in order and take precedence over [`on_http`](Self::on_http)
+    /// routes, so a test can script a call sequence (e.g. a failure, then a
+    /// success).
+    pub fn on_http_once(
+        &self,
+        method: &str,
+        url: &str,
+        result: Result<HttpResponse, HttpError>,
+    ) -> &Self {
+        with_host(|h| {
+            h.http_once
+                .push((method.to_ascii_uppercase(), url.to_string(), result))
+        });
+        self
+    }
+
+    /// Outbound HTTP calls made so far, in order.
+    pub fn http_calls(&self) -> Vec<RecordedHttpCall> {
+        with_host(|h| h.http_calls.clone()).unwrap_or_default()
+    }
+
+    /// Outcome of every `host_verify_signature` call: `true` valid, `false`
+    /// invalid. Until set, verification reports a host error.
+    pub fn verify_signatures(&self, valid: bool) -> &Self {
+        with_host(|h| h.verify_result = Some(valid));
+        self
+    }
+
+    /// Signature verification requests made so far (parsed JSON).
+    pub fn verify_requests(&self) -> Vec<serde_json::Value> {
+        with_host(|h| h.verify_requests.clone()).unwrap_or_default()
+    }
+
+    /// Make `host_verify_password` accept `password` for `hash`. Other
+    /// passwords for a registered hash are a mismatch; unregistered hashes
+    /// report a host error.
+    pub fn on_verify_password(&self, hash: &str, password: &str) -> &Self {
+        with_host(|h| {
+            h.passwords
+                .insert(hash.to_string(), password.as_bytes().to_vec())
+        });
+        self
+    }
+
+    /// Hashes passed to `host_verify_password` so far, in call order.
+    pub fn password_checks(&self) -> Vec<String> {
+        with_host(|h| h.password_checks.clone()).unwrap_or_default()
+    }
+
+    /// Seed a request-context value.
+    pub fn set_context(&self, key: &str, value: &str) -> &Self {
+        with_host(|h| h.context.insert(key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Read a request-context value (e.g. one the plugin set).
+    pub fn context(&self, key: &str) -> Option<String> {
+        with_host(|h| h.context.get(key).cloned()).flatten()
+    }
+
+    /// Seed a shared-cache entry (no TTL; mock entries never expire).
+    pub fn on_cache(&self, key: &str, entry: CacheEntry) -> &Self {
+        with_host(|h| h.cache.insert(key.to_string(), (entry, 0)));
+        self
+    }
+
+    /// Shared-cache contents as `key -> (entry, ttl_secs)`, including what
+    /// the plugin stored.
+    pub fn cache_entries(&self) -> BTreeMap<String, (CacheEntry, u32)> {
+        with_host(|h| h.cache.clone()).unwrap_or_default()
+    }
+
+    /// Pin the host clock to `ts` (Unix seconds).
+    pub fn set_time(&self, ts: u64) -> &Self {
+        with_host(|h| h.time = Some(ts));
+        self
+    }
+
+    /// Log lines emitted so far as `(level, message)`.
+    pub fn logs(&self) -> Vec<(i32, String)> {
+        with_host(|h| h.logs.clone()).unwrap_or_default()
+    }
+
+    /// `host_sleep` durations requested so far, in milliseconds. The mock
+    /// host records them without waiting.
+    pub fn sleeps(&self) -> Vec<u32> {
+        with_host(|h| h.sleeps.clone()).unwrap_or_default()
+    }
+}
+
+impl Drop for MockHost {
+    fn drop(&mut self) {
+        HOST.with(|host| *host.borrow_mut() = None);
+    }
+}
+
+// --- Hooks for the SDK's native bindings. `None` = no host installed. ---
+
+pub(crate) fn http_call(
+    request: &HttpRequest,
+    body: Option<&[u8]>,
+) -> Option<Result<HttpResponse, HttpError>> {
+    with_host(|h| {
+        h.http_calls.push(RecordedHttpCall {
+            request: request.clone(),
+            body: body.map(<[u8]>::to_vec),
+        });
+        let matches = |method: &str, url: &str| {
+            method.eq_ignore_ascii_case(&request.method) && url == request.url
+        };
+        if let Some(pos) = h
+            .http_once
+            .iter()
+            .position(|(method, url, _)| matches(method, url))
+        {
+            return h.http_once.remove(pos).2;
+        }
+        h.http_routes
+            .iter()
+            .find(|(method, url, _)| matches(method, url))
+            .map(|(_, _, response)| response.clone())
+            .ok_or(HttpError::Unreachable)
+    })
+}
+
+pub(crate) fn verify_signature(request_json: &[u8]) -> Option<Option<bool>> {
+    with_host(|h| {
+        if let Ok(request) = serde_json::from_slice(request_json) {
+            h.verify_requests.push(request);
+        }
+        h.verify_result
+    })
+}
+
+pub(crate) fn verify_password(hash: &str, password: &[u8]) -> Option<Option<bool>> {
+    with_host(|h| {
+        h.password_checks.push(hash.to_string());
+        h.passwords.get(hash).map(|expected| expected == password)
+    })
+}
+
+pub(crate) fn context_get(key: &str) -> Option<String> {
+    with_host(|h| h.context.get(key).cloned()).flatten()
+}
+
+pub(crate) fn context_set(key: &str, value: &str) {
+    with_host(|h| h.context.insert(key.to_string(), value.to_string()));
+}
+
+pub(crate) fn cache_get(key: &str) -> Option<CacheEntry> {
+    with_host(|h| h.cache.get(key).map(|(entry, _)| entry.clone())).flatten()
+}
+
+pub(crate) fn cache_set(key: &str, entry: &CacheEntry, ttl_secs: u32) -> bool {
+    with_host(|h| h.cache.insert(key.to_string(), (entry.clone(), ttl_secs))).is_some()
+}
+
+pub(crate) fn time() -> Option<u64> {
+    with_host(|h| h.time).flatten()
+}
+
+pub(crate) fn log(level: i32, msg: &str) {
+    with_host(|h| h.logs.push((level, msg.to_string())));
+}
+
+pub(crate) fn sleep(ms: u32) -> Option<()> {
+    with_host(|h| h.sleeps.push(ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, HostClock};
+    use crate::{cache, context, crypto, http, log};
+
+    #[test]
+    fn scripted_http_exchange() {
+        let host = MockHost::install();
+        host.on_http_json(
+            "GET",
+            "https://idp.example.com/.well-known/openid-configuration",
+            200,
+            &serde_json::json!({"jwks_uri": "https://idp.example.com/jwks"}),
+        )
+        .on_http_json(
+            "GET",
+            "https://idp.example.com/jwks",
+            200,
+            &serde_json::json!({"keys": []}),
+        );
+
+        let discovery = http::call(
+            &HttpRequest::new(
+                "GET",
+                "https://idp.example.com/.well-known/openid-configuration",
+            ),
+            None,
+        )
+        .unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&discovery.body.unwrap()).unwrap();
+        let jwks_uri = doc["jwks_uri"].as_str().unwrap();
+
+        let jwks = http::call(&HttpRequest::new("GET", jwks_uri), Some(b"ignored")).unwrap();
+        assert_eq!(jwks.status, 200);
+        assert_eq!(jwks.body_str(), Some(r#"{"keys":[]}"#));
+
+        let calls = host.http_calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].request.url, "https://idp.example.com/jwks");
+        assert_eq!(calls[1].body.as_deref(), Some(&b"ignored"[..]));
+
+        // Unscripted URLs are unreachable, not unsupported.
+        assert_eq!(
+            http::call(&HttpRequest::new("GET", "https://elsewhere"), None).unwrap_err(),
+            HttpError::Unreachable
+        );
+    }
+
+    #[test]
+    fn scripted_verify_context_clock_and_logs() {
+        let host = MockHost::install();
+        assert_eq!(crypto::verify_signature(br#"{"algorithm":"RS256"}"#), None);
+
+        host.verify_signatures(true);
+        assert_eq!(
+            crypto::verify_signature(br#"{"algorithm":"ES256"}"#),
+            Some(true)
+        );
+        host.verify_signatures(false);
+        assert_eq!(crypto::verify_signature(b"{}"), Some(false));
+        assert_eq!(host.verify_requests()[1]["algorithm"], "ES256");
+
+        assert_eq!(crypto::verify_password("$2b$04$hash", b"pw"), None);
+        host.on_verify_password("$2b$04$hash", "pw");
+        assert_eq!(crypto::verify_password("$2b$04$hash", b"pw"), Some(true));
+        assert_eq!(crypto::verify_password("$2b$04$hash", b"nope"), Some(false));
+        assert_eq!(host.password_checks().len(), 3);
+
+        host.set_context("tenant", "acme");
+        assert_eq!(context::get("tenant").as_deref(), Some("acme"));
+        context::set("consumer", "alice");
+        assert_eq!(host.context("consumer").as_deref(), Some("alice"));
+
+        host.set_time(1_700_000_000);
+        assert_eq!(HostClock.now(), 1_700_000_000);
+
+        assert_eq!(cache::get("jwks"), None);
+        host.on_cache("seeded", CacheEntry::new(200, b"one".to_vec()));
+        assert!(cache::set(
+            "jwks",
+            &CacheEntry::new(200, b"keys".to_vec()),
+            300
+        ));
+        assert_eq!(
+            cache::get("seeded").unwrap().body.as_deref(),
+            Some(&b"one"[..])
+        );
+        assert_eq!(host.cache_entries()["jwks"].1, 300);
+
+        log::warn("careful");
+        assert_eq!(host.logs(), vec![(log::LEVEL_WARN, "careful".to_string())]);
+    }
+
+    #[test]
+    fn dropping_the_guard_restores_native_behavior() {
+        {
+            let host = MockHost::install();
+            host.set_time(5)
+                .set_context("k", "v")
+                .on_cache("k", CacheEntry::new(200, Vec::new()));
+            assert_eq!(HostClock.now(), 5);
+        }
+        assert!(HostClock.now() > 5);
+        assert_eq!(context::get("k"), None);
+        assert!(!cache::set("k", &CacheEntry::new(200, Vec::new()), 60));
+        assert_eq!(cache::get("k"), None);
+        assert_eq!(
+            http::call(&HttpRequest::new("GET", "https://x"), None).unwrap_err(),
+            HttpError::Unsupported
+        );
+    }
+}
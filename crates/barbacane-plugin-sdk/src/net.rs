@@ -0,0 +1,125 @@
+//! IP address matching against single addresses and CIDR ranges.
+//!
+//! `client_ip` reaches plugins as a string (see [`crate::types::resolve_client_ip`]).
+//! Allowlists, denylists, and trusted-proxy checks all need the same IPv4/IPv6
+//! prefix comparison, so it lives here rather than in each plugin.
+//!
+//! ```
+//! use barbacane_plugin_sdk::net;
+//! assert!(net::ip_in_cidr("10.1.2.3", "10.0.0.0/8"));
+//! assert!(net::ip_in_cidr("2001:db8::1", "2001:db8::/32"));
+//! assert!(!net::ip_in_cidr("10.1.2.3", "2001:db8::/32"));
+//! ```
+
+use std::net::IpAddr;
+
+/// Whether `ip` falls within `cidr`. `cidr` is a range (`10.0.0.0/8`,
+/// `2001:db8::/32`) or a single address (`192.168.1.100`), which matches only
+/// itself. An unparseable address or range, a prefix longer than the address
+/// family allows, or an IPv4/IPv6 mismatch never matches.
+pub fn ip_in_cidr(ip: &str, cidr: &str) -> bool {
+    ip.trim()
+        .parse::<IpAddr>()
+        .is_ok_and(|ip| addr_in_cidr(ip, cidr))
+}
+
+/// [`ip_in_cidr`] for an already-parsed address, so a caller checking one
+/// client against a list parses it only once.
+pub fn addr_in_cidr(ip: IpAddr, cidr: &str) -> bool {
+    match cidr.split_once('/') {
+        Some((network, prefix)) => match (
+            network.trim().parse::<IpAddr>(),
+            prefix.trim().parse::<u8>(),
+        ) {
+            (Ok(network), Ok(prefix)) => prefix_matches(ip, network, prefix),
+            _ => false,
+        },
+        None => cidr.trim().parse::<IpAddr>().is_ok_and(|entry| entry == ip),
+    }
+}
+
+/// Whether `ip` and `network` share the first `prefix_len` bits.
+fn prefix_matches(ip: IpAddr, network: IpAddr, prefix_len: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            bits_match(&ip.octets(), &net.octets(), prefix_len, 32)
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            bits_match(&ip.octets(), &net.octets(), prefix_len, 128)
+        }
+        // Mismatched families (e.g. IPv4 client vs IPv6 range) never match.
+        _ => false,
+    }
+}
+
+/// Compare the first `prefix_len` bits of two address byte arrays.
+fn bits_match(a: &[u8], b: &[u8], prefix_len: u8, max_bits: u8) -> bool {
+    if prefix_len > max_bits {
+        return false;
+    }
+    let mut remaining = prefix_len as usize;
+    for (x, y) in a.iter().zip(b.iter()) {
+        if remaining == 0 {
+            break;
+        }
+        let take = remaining.min(8);
+        let mask: u8 = if take == 8 { 0xFF } else { !0u8 << (8 - take) };
+        if (x & mask) != (y & mask) {
+            return false;
+        }
+        remaining -= take;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_exact_ipv4_and_ipv6() {
+        assert!(ip_in_cidr("192.168.1.100", "192.168.1.100"));
+        assert!(!ip_in_cidr("192.168.1.101", "192.168.1.100"));
+        assert!(ip_in_cidr("2001:db8::1", "2001:db8::1"));
+        assert!(!ip_in_cidr("2001:db8::2", "2001:db8::1"));
+    }
+
+    #[test]
+    fn matches_ipv4_cidr() {
+        assert!(ip_in_cidr("10.1.2.3", "10.0.0.0/8"));
+        assert!(!ip_in_cidr("11.0.0.1", "10.0.0.0/8"));
+        assert!(ip_in_cidr("192.168.1.50", "192.168.1.0/24"));
+        assert!(!ip_in_cidr("192.168.2.50", "192.168.1.0/24"));
+        // Non-byte-aligned prefix.
+        assert!(ip_in_cidr("172.31.255.1", "172.16.0.0/12"));
+        assert!(!ip_in_cidr("172.32.0.1", "172.16.0.0/12"));
+    }
+
+    #[test]
+    fn matches_ipv6_cidr() {
+        assert!(ip_in_cidr("2001:db8::abcd", "2001:db8::/32"));
+        assert!(!ip_in_cidr("2001:db9::1", "2001:db8::/32"));
+        assert!(ip_in_cidr("fe80::1", "fe80::/10"));
+        assert!(ip_in_cidr("::1", "::1/128"));
+    }
+
+    #[test]
+    fn cidr_prefix_zero_matches_all() {
+        assert!(ip_in_cidr("1.2.3.4", "0.0.0.0/0"));
+        assert!(ip_in_cidr("2001:db8::1", "::/0"));
+    }
+
+    #[test]
+    fn family_mismatch_never_matches() {
+        assert!(!ip_in_cidr("10.0.0.1", "2001:db8::/32"));
+        assert!(!ip_in_cidr("2001:db8::1", "10.0.0.0/8"));
+    }
+
+    #[test]
+    fn invalid_input_never_matches() {
+        assert!(!ip_in_cidr("10.0.0.1", "not-an-ip"));
+        assert!(!ip_in_cidr("10.0.0.1", "10.0.0.0/99"));
+        assert!(!ip_in_cidr("10.0.0.1", "10.0.0.0/x"));
+        assert!(!ip_in_cidr("unknown", "0.0.0.0/0"));
+    }
+}
@@ -0,0 +1,211 @@
+//! Body editing shared by the request and response transformers.
+//!
+//! Both transformers edit bodies as a `serde_json::Value` (`body` rules or an
+//! RFC 6902 `json_patch` document), re-serialize them in their original
+//! format, and drop the stale `content-length`. Enabled by the `transform`
+//! cargo feature.
+//!
+//! ```
+//! use barbacane_plugin_sdk::transform::{self, BodyFormat, Patch};
+//! let patch: Patch =
+//!     serde_json::from_str(r#"[{"op": "add", "path": "/b", "value": 2}]"#).unwrap();
+//! let body = Some(br#"{"a":1}"#.to_vec());
+//! let edited = transform::apply_json_patch(&body, &patch, BodyFormat::Json);
+//! assert_eq!(edited.as_deref(), Some(&br#"{"a":1,"b":2}"#[..]));
+//! ```
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::log::log;
+
+pub use json_patch::Patch;
+
+/// Serialization of edited bodies. Both `body` and `json_patch` edit a
+/// `serde_json::Value`, so YAML is parsed into and re-serialized from the
+/// same representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyFormat {
+    Json,
+    Yaml,
+}
+
+impl BodyFormat {
+    /// YAML for YAML content types (`application/yaml`, `text/yaml`,
+    /// `application/x-yaml`, `*+yaml`), JSON otherwise.
+    pub fn detect(headers: &BTreeMap<String, String>) -> Self {
+        let is_yaml = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .and_then(|(_, value)| value.split(';').next())
+            .map(|mime| {
+                let mime = mime.trim().to_ascii_lowercase();
+                matches!(
+                    mime.as_str(),
+                    "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml"
+                ) || mime.ends_with("+yaml")
+            })
+            .unwrap_or(false);
+        if is_yaml {
+            BodyFormat::Yaml
+        } else {
+            BodyFormat::Json
+        }
+    }
+
+    /// Display name used in log messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            BodyFormat::Json => "JSON",
+            BodyFormat::Yaml => "YAML",
+        }
+    }
+
+    /// Parse `bytes`; `None` if they are not valid in this format.
+    pub fn parse(self, bytes: &[u8]) -> Option<Value> {
+        match self {
+            BodyFormat::Json => serde_json::from_slice(bytes).ok(),
+            BodyFormat::Yaml => serde_yaml::from_slice(bytes).ok(),
+        }
+    }
+
+    /// Serialize `value` in this format.
+    pub fn serialize(self, value: &Value) -> Result<Vec<u8>, String> {
+        match self {
+            BodyFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            BodyFormat::Yaml => serde_yaml::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Apply a JSON Patch document to a body in `format`.
+///
+/// The patch is atomic: if any operation fails (including a `test`), the body
+/// is returned unchanged. Bodies that do not parse are returned unchanged.
+pub fn apply_json_patch(
+    body: &Option<Vec<u8>>,
+    patch: &Patch,
+    format: BodyFormat,
+) -> Option<Vec<u8>> {
+    let body_bytes = match body {
+        Some(b) if !b.is_empty() => b,
+        _ => return body.clone(),
+    };
+
+    let Some(mut json) = format.parse(body_bytes) else {
+        log(
+            1,
+            &format!("Body is not valid {}, skipping JSON Patch", format.name()),
+        );
+        return body.clone();
+    };
+
+    if let Err(e) = json_patch::patch(&mut json, patch) {
+        log(1, &format!("JSON Patch not applied: {}", e));
+        return body.clone();
+    }
+
+    match format.serialize(&json) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log(
+                0,
+                &format!("Failed to serialize {} after patch: {}", format.name(), e),
+            );
+            body.clone()
+        }
+    }
+}
+
+/// Whether `body` fits `max_bytes` (unset means no limit). An oversized body
+/// is logged and should be left untouched rather than parsed; `kind` names
+/// it in the log ("request" or "response").
+pub fn body_within_limit(max_bytes: Option<usize>, body: Option<&[u8]>, kind: &str) -> bool {
+    match (max_bytes, body) {
+        (Some(max), Some(body)) if body.len() > max => {
+            log(
+                1,
+                &format!(
+                    "{} body of {} bytes exceeds max_body_bytes ({}); skipping body transforms",
+                    kind,
+                    body.len(),
+                    max
+                ),
+            );
+            false
+        }
+        _ => true,
+    }
+}
+
+/// Drop `content-length` (any casing) after a body edit, so a stale length
+/// never travels with the new body; the host sets it from the actual bytes.
+pub fn remove_content_length(headers: &mut BTreeMap<String, String>) {
+    headers.retain(|name, _| !name.eq_ignore_ascii_case("content-length"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(ops: &str) -> Patch {
+        serde_json::from_str(ops).expect("valid patch")
+    }
+
+    #[test]
+    fn detects_yaml_content_types() {
+        for mime in [
+            "application/yaml",
+            "text/x-yaml",
+            "application/vnd.api+yaml; q=1",
+        ] {
+            let headers = BTreeMap::from([("Content-Type".to_string(), mime.to_string())]);
+            assert_eq!(BodyFormat::detect(&headers), BodyFormat::Yaml, "{mime}");
+        }
+        let headers = BTreeMap::from([("content-type".to_string(), "text/plain".to_string())]);
+        assert_eq!(BodyFormat::detect(&headers), BodyFormat::Json);
+        assert_eq!(BodyFormat::detect(&BTreeMap::new()), BodyFormat::Json);
+    }
+
+    #[test]
+    fn patch_round_trips_yaml() {
+        let body = Some(b"name: John\n".to_vec());
+        let ops = patch(r#"[{"op": "replace", "path": "/name", "value": "Jane"}]"#);
+        let edited = apply_json_patch(&body, &ops, BodyFormat::Yaml).expect("body");
+        assert_eq!(edited, b"name: Jane\n");
+    }
+
+    #[test]
+    fn failed_patch_leaves_body_unchanged() {
+        let body = Some(br#"{"a":1}"#.to_vec());
+        let ops = patch(
+            r#"[{"op": "add", "path": "/b", "value": 2}, {"op": "test", "path": "/a", "value": 0}]"#,
+        );
+        assert_eq!(apply_json_patch(&body, &ops, BodyFormat::Json), body);
+
+        let invalid = Some(b"not json".to_vec());
+        assert_eq!(apply_json_patch(&invalid, &ops, BodyFormat::Json), invalid);
+    }
+
+    #[test]
+    fn body_limit_is_inclusive() {
+        assert!(body_within_limit(None, Some(b"12345"), "request"));
+        assert!(body_within_limit(Some(5), Some(b"12345"), "request"));
+        assert!(!body_within_limit(Some(4), Some(b"12345"), "request"));
+        assert!(body_within_limit(Some(0), None, "response"));
+    }
+
+    #[test]
+    fn content_length_is_removed_in_any_casing() {
+        let mut headers = BTreeMap::from([
+            ("Content-Length".to_string(), "5".to_string()),
+            ("content-type".to_string(), "application/json".to_string()),
+        ]);
+        remove_content_length(&mut headers);
+        assert_eq!(headers.keys().collect::<Vec<_>>(), ["content-type"]);
+    }
+}
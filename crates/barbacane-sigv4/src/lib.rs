@@ -10,6 +10,10 @@ use std::collections::BTreeMap;
 
 // --- Public types ---
 
+/// `body_sha256` sentinel that leaves the payload out of the signature, so a
+/// large or streamed body need not be hashed. S3 accepts it over HTTPS.
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
 /// AWS credentials.
 pub struct Credentials {
     pub access_key_id: String,
@@ -35,7 +39,7 @@ pub struct SigningInput<'a> {
     /// Headers to sign. Keys **must be lowercase**; must include `"host"`.
     /// `BTreeMap` guarantees lexicographic order required by SigV4.
     pub headers_to_sign: &'a BTreeMap<String, String>,
-    /// Pre-computed SHA-256 hex of the request body.
+    /// Pre-computed SHA-256 hex of the request body, or [`UNSIGNED_PAYLOAD`].
     pub body_sha256: &'a str,
     /// `YYYYMMDDTHHMMSSZ`
     pub datetime: &'a str,
@@ -55,6 +59,20 @@ pub struct SignedHeaders {
     pub x_amz_security_token: Option<String>,
 }
 
+/// Intermediate SigV4 strings returned by [`sign_with_debug`], for comparing
+/// against what a server (MinIO, Ceph, AWS) reports on a signature mismatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigV4Debug {
+    /// Canonical request: method, URI, query, headers, signed headers, and
+    /// payload hash, newline-separated.
+    pub canonical_request: String,
+    /// `AWS4-HMAC-SHA256`, datetime, credential scope, and the canonical
+    /// request's hash, newline-separated.
+    pub string_to_sign: String,
+    /// `YYYYMMDD/region/service/aws4_request`
+    pub credential_scope: String,
+}
+
 // --- Public functions ---
 
 /// Format a Unix timestamp (seconds since epoch) into SigV4 datetime strings.
@@ -173,6 +191,16 @@ fn canonical_header_value(v: &str) -> String {
 /// # Panics
 /// Never panics — HMAC accepts any key length.
 pub fn sign(input: &SigningInput, creds: &Credentials, config: &SigningConfig) -> SignedHeaders {
+    sign_with_debug(input, creds, config).0
+}
+
+/// [`sign`], also returning the canonical request, string to sign, and
+/// credential scope the signature was computed from.
+pub fn sign_with_debug(
+    input: &SigningInput,
+    creds: &Credentials,
+    config: &SigningConfig,
+) -> (SignedHeaders, SigV4Debug) {
     // BTreeMap guarantees keys are already sorted; keys must be lowercase.
     // SigV4 requires header values to be trimmed AND have sequential internal
     // whitespace collapsed to a single space; `v.trim()` alone diverges from
@@ -230,12 +258,18 @@ pub fn sign(input: &SigningInput, creds: &Credentials, config: &SigningConfig) -
         creds.access_key_id, credential_scope, signed_headers_str, signature,
     );
 
-    SignedHeaders {
+    let signed = SignedHeaders {
         authorization,
         x_amz_date: input.datetime.to_string(),
         x_amz_content_sha256: input.body_sha256.to_string(),
         x_amz_security_token: creds.session_token.clone(),
-    }
+    };
+    let debug = SigV4Debug {
+        canonical_request,
+        string_to_sign,
+        credential_scope,
+    };
+    (signed, debug)
 }
 
 // --- Private helpers ---
@@ -502,6 +536,116 @@ mod tests {
         assert!(signed.x_amz_security_token.is_none());
     }
 
+    #[test]
+    fn test_sign_with_debug_get_object() {
+        // Same request as `test_sigv4_signing_get_object`; the expected
+        // intermediate strings are the ones published with that example.
+        let empty_body_hash = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "host".to_string(),
+            "examplebucket.s3.amazonaws.com".to_string(),
+        );
+        headers.insert("range".to_string(), "bytes=0-9".to_string());
+        headers.insert(
+            "x-amz-content-sha256".to_string(),
+            empty_body_hash.to_string(),
+        );
+        headers.insert("x-amz-date".to_string(), "20130524T000000Z".to_string());
+
+        let creds = Credentials {
+            access_key_id: TEST_ACCESS_KEY.to_string(),
+            secret_access_key: TEST_SECRET_KEY.to_string(),
+            session_token: None,
+        };
+        let config = SigningConfig {
+            region: "us-east-1",
+            service: "s3",
+        };
+        let input = SigningInput {
+            method: "GET",
+            canonical_uri: "/test.txt",
+            canonical_query: "",
+            headers_to_sign: &headers,
+            body_sha256: empty_body_hash,
+            datetime: "20130524T000000Z",
+            date: "20130524",
+        };
+
+        let (signed, debug) = sign_with_debug(&input, &creds, &config);
+
+        assert_eq!(
+            debug.canonical_request,
+            "GET\n\
+             /test.txt\n\
+             \n\
+             host:examplebucket.s3.amazonaws.com\n\
+             range:bytes=0-9\n\
+             x-amz-content-sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\n\
+             x-amz-date:20130524T000000Z\n\
+             \n\
+             host;range;x-amz-content-sha256;x-amz-date\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            debug.string_to_sign,
+            "AWS4-HMAC-SHA256\n\
+             20130524T000000Z\n\
+             20130524/us-east-1/s3/aws4_request\n\
+             7344ae5b7ee6c3e7e6b0fe0640412a37625d1fbfff95c48bbb2dc43964946972"
+        );
+        assert_eq!(debug.credential_scope, "20130524/us-east-1/s3/aws4_request");
+        // The debug path signs identically to the lean one.
+        assert_eq!(
+            signed.authorization,
+            sign(&input, &creds, &config).authorization
+        );
+    }
+
+    #[test]
+    fn test_unsigned_payload() {
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "host".to_string(),
+            "examplebucket.s3.amazonaws.com".to_string(),
+        );
+        headers.insert(
+            "x-amz-content-sha256".to_string(),
+            UNSIGNED_PAYLOAD.to_string(),
+        );
+        headers.insert("x-amz-date".to_string(), "20130524T000000Z".to_string());
+
+        let creds = Credentials {
+            access_key_id: TEST_ACCESS_KEY.to_string(),
+            secret_access_key: TEST_SECRET_KEY.to_string(),
+            session_token: None,
+        };
+        let config = SigningConfig {
+            region: "us-east-1",
+            service: "s3",
+        };
+        let input = SigningInput {
+            method: "PUT",
+            canonical_uri: "/big.bin",
+            canonical_query: "",
+            headers_to_sign: &headers,
+            body_sha256: UNSIGNED_PAYLOAD,
+            datetime: "20130524T000000Z",
+            date: "20130524",
+        };
+
+        let (signed, debug) = sign_with_debug(&input, &creds, &config);
+
+        assert!(debug
+            .canonical_request
+            .ends_with("\nhost;x-amz-content-sha256;x-amz-date\nUNSIGNED-PAYLOAD"));
+        assert!(debug
+            .canonical_request
+            .contains("\nx-amz-content-sha256:UNSIGNED-PAYLOAD\n"));
+        assert_eq!(signed.x_amz_content_sha256, "UNSIGNED-PAYLOAD");
+    }
+
     #[test]
     fn test_sigv4_signing_with_session_token() {
         // Session token must appear in signed headers and in x_amz_security_token output
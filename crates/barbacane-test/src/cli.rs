@@ -420,3 +420,47 @@ fn compile_discovers_specs_from_manifest_folder() {
 
     assert!(output.exists(), "artifact should be created");
 }
+
+#[test]
+fn compile_resolves_env_placeholders_from_process_environment() {
+    let tmp = TempDir::new().expect("temp dir");
+    let spec = tmp.path().join("api.yaml");
+    std::fs::write(
+        &spec,
+        "openapi: \"3.1.0\"\ninfo:\n  title: Env API\n  version: \"1.0.0\"\npaths:\n  /health:\n    get:\n      operationId: getHealth\n      x-barbacane-dispatch:\n        name: mock\n        config:\n          status: 200\n          body: \"${env:BARBACANE_TEST_GREETING}\"\n      responses:\n        \"200\":\n          description: OK\n",
+    )
+    .expect("write spec");
+    let manifest = tmp.path().join("barbacane.yaml");
+    std::fs::write(
+        &manifest,
+        format!(
+            "plugins:\n  mock:\n    path: {}\n",
+            fixtures().join("../../plugins/mock/mock.wasm").display()
+        ),
+    )
+    .expect("write manifest");
+
+    barbacane()
+        .env("BARBACANE_TEST_GREETING", "hello")
+        .args(["compile", "--spec"])
+        .arg(&spec)
+        .arg("--manifest")
+        .arg(&manifest)
+        .arg("--output")
+        .arg(tmp.path().join("out.bca"))
+        .assert()
+        .success();
+
+    barbacane()
+        .env_remove("BARBACANE_TEST_GREETING")
+        .args(["compile", "--spec"])
+        .arg(&spec)
+        .arg("--manifest")
+        .arg(&manifest)
+        .arg("--output")
+        .arg(tmp.path().join("missing.bca"))
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(contains("E1025"));
+}
@@ -242,6 +242,25 @@ pub mod unix_timestamp {
     pub const FUNCTION_NAME: &str = "host_get_unix_timestamp";
 }
 
+/// Host function for pausing a plugin between retries.
+///
+/// ```text
+/// host_sleep(ms: u32)
+/// ```
+///
+/// Blocks the calling plugin for up to `ms` milliseconds (capped per call).
+/// The wait does not count against the execution time limit.
+pub mod sleep {
+    /// The capability name.
+    pub const CAPABILITY: &str = "sleep";
+
+    /// The function name.
+    pub const FUNCTION_NAME: &str = "host_sleep";
+
+    /// Longest single sleep the host honors, in milliseconds.
+    pub const MAX_SLEEP_MS: u32 = 5_000;
+}
+
 /// Host functions for rate limiting.
 ///
 /// ```text
@@ -300,7 +319,7 @@ pub mod cache {
 ///
 /// Returns: 1 (valid), 0 (invalid), -1 (error)
 ///
-/// Supported algorithms: RS256, RS384, RS512, ES256, ES384
+/// Supported algorithms: RS256, RS384, RS512, ES256, ES384, ES512, EdDSA (Ed25519)
 pub mod verify_signature {
     /// The capability name.
     pub const CAPABILITY: &str = "verify_signature";
@@ -309,6 +328,25 @@ pub mod verify_signature {
     pub const FUNCTION_NAME: &str = "host_verify_signature";
 }
 
+/// Host function for password hash verification.
+///
+/// ```text
+/// host_verify_password(hash_ptr: i32, hash_len: i32, pw_ptr: i32, pw_len: i32) -> i32
+/// ```
+///
+/// Input: the stored hash (UTF-8) and the candidate password (raw bytes).
+///
+/// Returns: 1 (match), 0 (mismatch), -1 (malformed or unsupported hash)
+///
+/// Supported schemes: bcrypt (`$2a$`, `$2b$`, `$2y$`), argon2 PHC strings
+pub mod verify_password {
+    /// The capability name.
+    pub const CAPABILITY: &str = "verify_password";
+
+    /// The function name in the barbacane namespace.
+    pub const FUNCTION_NAME: &str = "host_verify_password";
+}
+
 /// Host functions for UUID generation.
 ///
 /// ```text
@@ -1108,6 +1108,23 @@ fn add_host_functions(linker: &mut Linker<PluginState>) -> Result<(), WasmError>
             WasmError::Instantiation(format!("failed to add host_get_unix_timestamp: {}", e))
         })?;
 
+    // host_sleep - block the plugin for a bounded time (retry backoff)
+    linker
+        .func_wrap(
+            "barbacane",
+            "host_sleep",
+            |mut caller: Caller<'_, PluginState>, ms: u32| {
+                let ms = ms.min(crate::host::sleep::MAX_SLEEP_MS);
+                std::thread::sleep(std::time::Duration::from_millis(u64::from(ms)));
+
+                // Sleeping is not WASM CPU work — refresh the execution
+                // deadline, as the blocking HTTP handlers do.
+                let deadline = caller.data().max_execution_ms.max(1);
+                caller.as_context_mut().set_epoch_deadline(deadline);
+            },
+        )
+        .map_err(|e| WasmError::Instantiation(format!("failed to add host_sleep: {}", e)))?;
+
     // host_uuid_generate - generates UUID v7 and returns length
     linker
         .func_wrap(
@@ -1558,6 +1575,60 @@ fn add_host_functions(linker: &mut Linker<PluginState>) -> Result<(), WasmError>
             WasmError::Instantiation(format!("failed to add host_verify_signature: {}", e))
         })?;
 
+    // host_verify_password - verify a password against a bcrypt/argon2 hash
+    linker
+        .func_wrap(
+            "barbacane",
+            "host_verify_password",
+            |mut caller: Caller<'_, PluginState>,
+             hash_ptr: i32,
+             hash_len: i32,
+             pw_ptr: i32,
+             pw_len: i32|
+             -> i32 {
+                let memory = match caller.get_export("memory").and_then(|e| e.into_memory()) {
+                    Some(m) => m,
+                    None => return -1,
+                };
+
+                let data = memory.data(&caller);
+                let read = |ptr: i32, len: i32| {
+                    let start = ptr as usize;
+                    let end = start.saturating_add(len as usize);
+                    data.get(start..end).map(<[u8]>::to_vec)
+                };
+                let (hash, password) = match (read(hash_ptr, hash_len), read(pw_ptr, pw_len)) {
+                    (Some(h), Some(p)) => (h, p),
+                    _ => return -1,
+                };
+                let hash = match String::from_utf8(hash) {
+                    Ok(h) => h,
+                    Err(_) => return -1,
+                };
+
+                let result = match crate::crypto::verify_password(&hash, &password) {
+                    Ok(true) => 1,
+                    Ok(false) => 0,
+                    Err(e) => {
+                        tracing::error!(
+                            plugin = %caller.data_mut().plugin_name,
+                            "password verification error: {}", e
+                        );
+                        -1
+                    }
+                };
+
+                // Password hashing is deliberately slow host work, not WASM
+                // CPU time — refresh the execution deadline.
+                let deadline = caller.data().max_execution_ms.max(1);
+                caller.as_context_mut().set_epoch_deadline(deadline);
+                result
+            },
+        )
+        .map_err(|e| {
+            WasmError::Instantiation(format!("failed to add host_verify_password: {}", e))
+        })?;
+
     // host_get_secret - get a secret by reference
     linker
         .func_wrap(
@@ -153,9 +153,11 @@ const KNOWN_CAPABILITIES: &[&str] = &[
     "telemetry",
     "generate_uuid",
     "verify_signature",
+    "verify_password",
     "ws_upgrade",
     "cache",
     "rate_limit",
+    "sleep",
 ];
 
 /// Check if a capability name is known.
@@ -195,7 +197,9 @@ pub fn capability_to_imports(capability: &str) -> &'static [&'static str] {
         ],
         "generate_uuid" => &["host_uuid_generate", "host_uuid_read_result"],
         "verify_signature" => &["host_verify_signature"],
+        "verify_password" => &["host_verify_password"],
         "ws_upgrade" => &["host_ws_upgrade", "host_http_read_result"],
+        "sleep" => &["host_sleep"],
         _ => &[],
     }
 }
@@ -334,6 +338,43 @@ host_functions = ["ws_upgrade", "log"]
         );
     }
 
+    #[test]
+    fn parse_sleep_capability() {
+        let manifest_str = r#"
+[plugin]
+name = "s3"
+version = "0.1.0"
+type = "dispatcher"
+wasm = "s3.wasm"
+
+[capabilities]
+host_functions = ["http_call", "sleep"]
+"#;
+        let manifest = PluginManifest::from_toml(manifest_str).unwrap();
+        assert!(manifest.has_capability("sleep"));
+        assert_eq!(capability_to_imports("sleep"), &["host_sleep"]);
+    }
+
+    #[test]
+    fn parse_verify_password_capability() {
+        let manifest_str = r#"
+[plugin]
+name = "basic-auth"
+version = "0.1.0"
+type = "middleware"
+wasm = "basic-auth.wasm"
+
+[capabilities]
+host_functions = ["log", "verify_password"]
+"#;
+        let manifest = PluginManifest::from_toml(manifest_str).unwrap();
+        assert!(manifest.has_capability("verify_password"));
+        assert_eq!(
+            capability_to_imports("verify_password"),
+            &["host_verify_password"]
+        );
+    }
+
     #[test]
     fn required_exports_dispatcher() {
         let exports = PluginType::Dispatcher.required_exports();
@@ -20,6 +20,9 @@ fn create_parameters() -> Vec<Parameter> {
                 "type": "string",
                 "format": "uuid"
             })),
+            allow_reserved: false,
+            style: None,
+            explode: None,
         },
         Parameter {
             name: "page".to_string(),
@@ -29,6 +32,9 @@ fn create_parameters() -> Vec<Parameter> {
                 "type": "integer",
                 "minimum": 1
             })),
+            allow_reserved: false,
+            style: None,
+            explode: None,
         },
         Parameter {
             name: "limit".to_string(),
@@ -39,6 +45,9 @@ fn create_parameters() -> Vec<Parameter> {
                 "minimum": 1,
                 "maximum": 100
             })),
+            allow_reserved: false,
+            style: None,
+            explode: None,
         },
         Parameter {
             name: "x-api-key".to_string(),
@@ -48,6 +57,9 @@ fn create_parameters() -> Vec<Parameter> {
                 "type": "string",
                 "minLength": 32
             })),
+            allow_reserved: false,
+            style: None,
+            explode: None,
         },
     ]
 }
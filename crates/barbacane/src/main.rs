@@ -68,6 +68,10 @@ fn validation_error_reason(err: &ValidationError2) -> String {
         ValidationError2::MissingRequiredParameter { .. } => {
             "missing_required_parameter".to_string()
         }
+        // Body schema violations are reported per field but still count as invalid_body.
+        ValidationError2::InvalidParameter { location, .. } if location == "body" => {
+            "invalid_body".to_string()
+        }
         ValidationError2::InvalidParameter { .. } => "invalid_parameter".to_string(),
         ValidationError2::MissingRequiredBody => "missing_required_body".to_string(),
         ValidationError2::UnsupportedContentType(_) => "unsupported_content_type".to_string(),
@@ -662,6 +666,9 @@ struct Gateway {
     /// (BARBACANE_ALLOW_INTERNAL_EGRESS). The HTTP/broker clients carry their own
     /// copy; this one guards WebSocket upstream connections.
     allow_internal_egress: bool,
+    /// Scheme of the listener serving this gateway (`https` behind its own
+    /// TLS termination), exposed to middlewares as `request.scheme`.
+    scheme: &'static str,
     /// WASM engine for plugin execution (kept alive for engine lifetime).
     _wasm_engine: Arc<WasmEngine>,
     /// Plugin instance pool.
@@ -687,6 +694,7 @@ impl Gateway {
         dev_mode: bool,
         limits: RequestLimits,
         allow_plaintext_upstream: bool,
+        scheme: &'static str,
         metrics: Arc<MetricsRegistry>,
     ) -> Result<Self, String> {
         let manifest =
@@ -930,7 +938,9 @@ impl Gateway {
             );
 
             // Pre-compile validator for this operation
-            let validator = OperationValidator::new(&op.parameters, op.request_body.as_ref());
+            let validator = OperationValidator::new(&op.parameters, op.request_body.as_ref())
+                .with_accepted_content_types(&op.accepted_content_types)
+                .with_max_decoded_body_size(limits.max_body_size);
             validators.push(validator);
 
             // Log middleware chain for this operation (informational)
@@ -978,6 +988,7 @@ impl Gateway {
             limits,
             dev_mode,
             allow_internal_egress,
+            scheme,
             _wasm_engine: wasm_engine,
             plugin_pool: Arc::new(plugin_pool),
             _plugin_limits: plugin_limits,
@@ -1090,7 +1101,8 @@ impl Gateway {
 
         // Check URI length limit early
         if let Err(e) = self.limits.validate_uri(&uri_string) {
-            let response = self.validation_error_response(&[e]);
+            // No `instance`: echoing an over-long path back would defeat the limit.
+            let response = self.validation_error_response(&[e], None);
             self.record_request_metrics(
                 &method_str,
                 // Bounded label: raw paths would let a scanner explode metric cardinality.
@@ -1127,20 +1139,15 @@ impl Gateway {
 
         // Enforce header limits on the RAW header map first: `HeaderMap::len()`
         // counts every header line (including repeated names), so a flood of
-        // duplicate-named headers can't slip under the count limit, and each
-        // value is size-checked (not just the last one for a given name).
+        // duplicate-named headers can't slip under the count limit. Sizes are
+        // checked per name on the folded representation (all values joined
+        // with ", "), which is what a merged header actually costs.
         let raw_headers = req.headers();
         let header_limit_error = self
             .limits
             .validate_header_count(raw_headers.len())
             .err()
-            .or_else(|| {
-                raw_headers.iter().find_map(|(name, value)| {
-                    self.limits
-                        .validate_header_size(name.as_str(), name.as_str().len() + value.len())
-                        .err()
-                })
-            });
+            .or_else(|| self.limits.validate_folded_header_sizes(raw_headers).err());
 
         // Extract headers for downstream validation and plugin forwarding. This
         // collapses duplicate names (last value wins), which is fine now that the
@@ -1153,7 +1160,7 @@ impl Gateway {
 
         // Check header limits
         if let Some(e) = header_limit_error {
-            let response = self.validation_error_response(&[e]);
+            let response = self.validation_error_response(&[e], None);
             self.record_request_metrics(
                 &method_str,
                 UNMATCHED_ROUTE_LABEL,
@@ -1294,7 +1301,7 @@ impl Gateway {
                         self.metrics
                             .record_validation_failure(&method_str, &route_path, &reason);
                     }
-                    let response = self.validation_error_response(&errors);
+                    let response = self.validation_error_response(&errors, Some(path.as_str()));
                     self.record_request_metrics(
                         &method_str,
                         &route_path,
@@ -1604,6 +1611,9 @@ impl Gateway {
         // not embedded in JSON. Only body-access middleware receives the body.
         let mut body_ctrl = barbacane_wasm::BodyAccessControl::new(request_json.to_vec(), raw_body);
         let mut current_context = RequestContext::default();
+        current_context
+            .values
+            .insert("request.scheme".to_string(), self.scheme.to_string());
 
         for (index, instance) in instances.iter_mut().enumerate() {
             let has_body_access = body_access_flags[index];
@@ -2789,9 +2799,16 @@ impl Gateway {
             .expect("valid response")
     }
 
-    /// Build a 400 validation error response (RFC 9457).
-    fn validation_error_response(&self, errors: &[ValidationError2]) -> Response<Full<Bytes>> {
-        let problem = ProblemDetails::validation_error(errors, self.dev_mode);
+    /// Build a 400 validation error response (RFC 9457), using `instance`
+    /// (the request path) as the problem `instance`. Limit violations pass
+    /// `None` so the offending input is not reflected back.
+    fn validation_error_response(
+        &self,
+        errors: &[ValidationError2],
+        instance: Option<&str>,
+    ) -> Response<Full<Bytes>> {
+        let problem =
+            ProblemDetails::validation_error(errors, self.dev_mode, instance.map(str::to_string));
 
         Response::builder()
             .status(StatusCode::BAD_REQUEST)
@@ -3588,11 +3605,14 @@ async fn perform_hot_reload(
     };
 
     // Step 2: Load and compile new Gateway
+    // The listener doesn't change across reloads; keep its scheme.
+    let scheme = shared_gateway.load().scheme;
     let new_gateway = match Gateway::load(
         &artifact_path,
         dev_mode,
         limits,
         allow_plaintext_upstream,
+        scheme,
         metrics,
     ) {
         Ok(g) => g,
@@ -3645,6 +3665,9 @@ fn run_compile(
         provenance_commit,
         provenance_source,
         no_cache,
+        // `${env:VAR}` placeholders in plugin configs resolve against the
+        // environment `barbacane compile` runs in.
+        env: std::env::vars().collect(),
         ..Default::default()
     };
 
@@ -3810,6 +3833,7 @@ async fn run_dev(
     // and url: plugins should use the download cache to avoid re-fetching on every reload.
     let compile_options = CompileOptions {
         allow_plaintext: true,
+        env: std::env::vars().collect(),
         ..Default::default()
     };
 
@@ -3839,20 +3863,26 @@ async fn run_dev(
 
     // Load gateway.
     let limits = RequestLimits::default();
-    let gateway: SharedGateway =
-        match Gateway::load(&temp_path, true, limits.clone(), true, metrics.clone()) {
-            Ok(g) => {
-                eprintln!(
-                    "barbacane dev: compiled {} route(s) in {}ms",
-                    g.manifest.routes_count, compile_ms
-                );
-                Arc::new(ArcSwap::new(Arc::new(g)))
-            }
-            Err(e) => {
-                eprintln!("barbacane dev: {}", e);
-                return ExitCode::from(1);
-            }
-        };
+    let gateway: SharedGateway = match Gateway::load(
+        &temp_path,
+        true,
+        limits.clone(),
+        true,
+        "http",
+        metrics.clone(),
+    ) {
+        Ok(g) => {
+            eprintln!(
+                "barbacane dev: compiled {} route(s) in {}ms",
+                g.manifest.routes_count, compile_ms
+            );
+            Arc::new(ArcSwap::new(Arc::new(g)))
+        }
+        Err(e) => {
+            eprintln!("barbacane dev: {}", e);
+            return ExitCode::from(1);
+        }
+    };
 
     // Parse listen address.
     let addr: SocketAddr = match listen.parse() {
@@ -4074,7 +4104,14 @@ async fn run_dev(
                 }
 
                 // Load new gateway.
-                match Gateway::load(&new_path, true, limits.clone(), true, metrics.clone()) {
+                match Gateway::load(
+                    &new_path,
+                    true,
+                    limits.clone(),
+                    true,
+                    "http",
+                    metrics.clone(),
+                ) {
                     Ok(new_gw) => {
                         let routes = new_gw.manifest.routes_count;
                         let ms = compile_start.elapsed().as_millis();
@@ -4344,11 +4381,17 @@ async fn run_serve(
         .unwrap_or(Path::new("."))
         .to_path_buf();
 
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
     let gateway: SharedGateway = match Gateway::load(
         artifact_path,
         dev,
         limits.clone(),
         allow_plaintext_upstream,
+        scheme,
         metrics.clone(),
     ) {
         Ok(g) => Arc::new(ArcSwap::new(Arc::new(g))),
@@ -4400,12 +4443,7 @@ async fn run_serve(
         None => None,
     };
 
-    let protocol = if tls_acceptor.is_some() {
-        "https"
-    } else {
-        "http"
-    };
-    eprintln!("barbacane: listening on {}://{}", protocol, addr);
+    eprintln!("barbacane: listening on {}://{}", scheme, addr);
 
     // Create shutdown signal channel
     let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
@@ -308,6 +308,7 @@ mod tests {
                 path: "/health".to_string(),
                 method: "GET".to_string(),
                 operation_id: Some("getHealth".to_string()),
+                operation_id_synthesized: false,
                 summary: Some("Health check".to_string()),
                 description: None,
                 parameters: vec![],
@@ -319,17 +320,21 @@ mod tests {
                 middlewares: vec![],
                 deprecated: false,
                 sunset: None,
+                timeout_ms: None,
                 messages: vec![],
                 bindings: BTreeMap::new(),
                 responses: BTreeMap::new(),
                 mcp_enabled: Some(true),
                 mcp_description: None,
+                accepted_content_types: Vec::new(),
+                config_fingerprint: String::new(),
             },
             CompiledOperation {
                 index: 1,
                 path: "/secret".to_string(),
                 method: "GET".to_string(),
                 operation_id: Some("getSecret".to_string()),
+                operation_id_synthesized: false,
                 summary: Some("Not exposed".to_string()),
                 description: None,
                 parameters: vec![],
@@ -341,11 +346,14 @@ mod tests {
                 middlewares: vec![],
                 deprecated: false,
                 sunset: None,
+                timeout_ms: None,
                 messages: vec![],
                 bindings: BTreeMap::new(),
                 responses: BTreeMap::new(),
                 mcp_enabled: None,
                 mcp_description: None,
+                accepted_content_types: Vec::new(),
+                config_fingerprint: String::new(),
             },
         ]
     }
@@ -607,6 +615,7 @@ mod tests {
             path: "/users/{id}".to_string(),
             method: "GET".to_string(),
             operation_id: Some("getUser".to_string()),
+            operation_id_synthesized: false,
             summary: Some("Get user".to_string()),
             description: None,
             parameters: vec![barbacane_compiler::Parameter {
@@ -614,6 +623,9 @@ mod tests {
                 location: "path".to_string(),
                 required: true,
                 schema: None,
+                allow_reserved: false,
+                style: None,
+                explode: None,
             }],
             request_body: None,
             dispatch: DispatchConfig {
@@ -623,11 +635,14 @@ mod tests {
             middlewares: vec![],
             deprecated: false,
             sunset: None,
+            timeout_ms: None,
             messages: vec![],
             bindings: BTreeMap::new(),
             responses: BTreeMap::new(),
             mcp_enabled: Some(true),
             mcp_description: None,
+            accepted_content_types: Vec::new(),
+            config_fingerprint: String::new(),
         }];
         let config = McpConfig {
             enabled: true,
@@ -29,11 +29,16 @@ pub struct ToolEntry {
 
 /// Generate MCP tools from compiled operations.
 ///
-/// Only includes operations where `mcp_enabled == Some(true)` and `operation_id` is present.
+/// Only includes operations where `mcp_enabled == Some(true)` and the spec
+/// declares an `operation_id` (synthesized ids are not stable tool names).
 pub fn generate_tools(operations: &[CompiledOperation]) -> Vec<ToolEntry> {
     operations
         .iter()
-        .filter(|op| op.mcp_enabled == Some(true) && op.operation_id.is_some())
+        .filter(|op| {
+            op.mcp_enabled == Some(true)
+                && op.operation_id.is_some()
+                && !op.operation_id_synthesized
+        })
         .map(|op| {
             let name = op.operation_id.clone().expect("filtered above");
             let description = build_description(op);
@@ -325,6 +330,7 @@ mod tests {
             path: path.to_string(),
             method: method.to_string(),
             operation_id: operation_id.map(|s| s.to_string()),
+            operation_id_synthesized: false,
             summary: summary.map(|s| s.to_string()),
             description: None,
             parameters: vec![],
@@ -336,11 +342,14 @@ mod tests {
             middlewares: vec![],
             deprecated: false,
             sunset: None,
+            timeout_ms: None,
             messages: vec![],
             bindings: BTreeMap::new(),
             responses: BTreeMap::new(),
             mcp_enabled,
             mcp_description: None,
+            accepted_content_types: Vec::new(),
+            config_fingerprint: String::new(),
         }
     }
 
@@ -388,12 +397,18 @@ mod tests {
                 location: "path".to_string(),
                 required: true,
                 schema: Some(serde_json::json!({"type": "string"})),
+                allow_reserved: false,
+                style: None,
+                explode: None,
             },
             Parameter {
                 name: "fields".to_string(),
                 location: "query".to_string(),
                 required: false,
                 schema: Some(serde_json::json!({"type": "string"})),
+                allow_reserved: false,
+                style: None,
+                explode: None,
             },
         ];
         let schema = build_input_schema(&op);
@@ -497,12 +512,18 @@ mod tests {
                     location: "path".to_string(),
                     required: true,
                     schema: None,
+                    allow_reserved: false,
+                    style: None,
+                    explode: None,
                 },
                 Parameter {
                     name: "fields".to_string(),
                     location: "query".to_string(),
                     required: false,
                     schema: None,
+                    allow_reserved: false,
+                    style: None,
+                    explode: None,
                 },
             ],
         };
@@ -576,6 +597,9 @@ mod tests {
                 location: "path".to_string(),
                 required: true,
                 schema: None,
+                allow_reserved: false,
+                style: None,
+                explode: None,
             }],
         };
         let args = serde_json::json!({"path": "docs/2024/report.pdf"});
@@ -602,6 +626,9 @@ mod tests {
                 location: "path".to_string(),
                 required: true,
                 schema: None,
+                allow_reserved: false,
+                style: None,
+                explode: None,
             }],
         };
         // Numeric value instead of string
@@ -627,6 +654,9 @@ mod tests {
                 location: "path".to_string(),
                 required: true,
                 schema: None,
+                allow_reserved: false,
+                style: None,
+                explode: None,
             }],
         };
         // Missing "id" argument
@@ -651,6 +681,9 @@ mod tests {
                 location: "path".to_string(),
                 required: true,
                 schema: None,
+                allow_reserved: false,
+                style: None,
+                explode: None,
             }],
         }
     }
@@ -703,6 +736,9 @@ mod tests {
                 location: "path".to_string(),
                 required: true,
                 schema: None,
+                allow_reserved: false,
+                style: None,
+                explode: None,
             }],
         };
         let args = serde_json::json!({"path": "docs/../../etc/passwd"});
@@ -3,12 +3,15 @@
 //! Validates incoming requests against OpenAPI parameter and body schemas.
 //! Used by the data plane to reject non-conforming requests before dispatch.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io::Read;
 
+use hyper::HeaderMap;
 use serde_json::Value;
 use thiserror::Error;
 
-use barbacane_compiler::{Parameter, RequestBody};
+use barbacane_compiler::{Message, Parameter, RequestBody};
 
 /// Validation errors returned when a request doesn't conform to the spec.
 #[derive(Debug, Error)]
@@ -66,7 +69,13 @@ pub struct ProblemDetails {
 }
 
 impl ProblemDetails {
-    pub fn validation_error(errors: &[ValidationError2], dev_mode: bool) -> Self {
+    /// Build a 400 problem for the given validation errors. `instance` is the
+    /// request path identifying this occurrence; it is omitted when `None`.
+    pub fn validation_error(
+        errors: &[ValidationError2],
+        dev_mode: bool,
+        instance: Option<String>,
+    ) -> Self {
         let mut extensions = HashMap::new();
 
         if dev_mode && !errors.is_empty() {
@@ -169,11 +178,53 @@ impl ProblemDetails {
             title: "Request validation failed".into(),
             status: 400,
             detail,
-            instance: None,
+            instance,
             extensions,
         }
     }
 
+    /// Problem details and `WWW-Authenticate` value for an authentication
+    /// (401) or authorization (403) failure, in the shape the auth plugins
+    /// emit: `urn:barbacane:error:authentication-failed` (or
+    /// `authorization-failed` for 403) with `description` as the detail.
+    ///
+    /// `challenge` is the auth scheme and its parameters, e.g.
+    /// `Bearer realm="api"`; `error_code` and `description` are appended to it
+    /// as the RFC 6750 `error` / `error_description` parameters. Without a
+    /// challenge no header value is returned.
+    pub fn auth_error(
+        status: u16,
+        error_code: &str,
+        description: &str,
+        challenge: Option<&str>,
+    ) -> (Self, Option<String>) {
+        let (error_type, title) = match status {
+            403 => (
+                "urn:barbacane:error:authorization-failed",
+                "Authorization failed",
+            ),
+            _ => (
+                "urn:barbacane:error:authentication-failed",
+                "Authentication failed",
+            ),
+        };
+        let www_authenticate = challenge.map(|challenge| {
+            format!(
+                "{}, error=\"{}\", error_description=\"{}\"",
+                challenge, error_code, description
+            )
+        });
+        let problem = ProblemDetails {
+            error_type: error_type.into(),
+            title: title.into(),
+            status,
+            detail: Some(description.into()),
+            instance: None,
+            extensions: HashMap::new(),
+        };
+        (problem, www_authenticate)
+    }
+
     pub fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap_or_else(|_| {
             r#"{"type":"urn:barbacane:error:internal","title":"Serialization error","status":500}"#.into()
@@ -269,6 +320,37 @@ impl RequestLimits {
         Ok(())
     }
 
+    /// Validate header sizes against their folded wire representation.
+    ///
+    /// Repeated header lines may be merged by the host into one field whose
+    /// values are joined with `", "` (RFC 9110 §5.3), so the size that matters
+    /// per name is `name + sum(values) + 2 * (lines - 1)`, not each line alone.
+    /// `HeaderMap` names are already lowercase, so each name is visited once;
+    /// the first oversized one is reported with its folded size.
+    pub fn validate_folded_header_sizes(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<(), ValidationError2> {
+        for name in headers.keys() {
+            let (lines, values_len) = headers
+                .get_all(name)
+                .iter()
+                .fold((0usize, 0), |(lines, len), value| {
+                    (lines + 1, len + value.len())
+                });
+            let size =
+                name.as_str().len() + values_len + FOLD_SEPARATOR.len() * lines.saturating_sub(1);
+            if size > self.max_header_size {
+                return Err(ValidationError2::HeaderTooLarge {
+                    name: name.as_str().to_string(),
+                    size,
+                    limit: self.max_header_size,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Validate body size.
     pub fn validate_body_size(&self, body_len: usize) -> Result<(), ValidationError2> {
         if body_len > self.max_body_size {
@@ -307,8 +389,51 @@ impl RequestLimits {
             Err(errors)
         }
     }
+
+    /// Start an incremental body size check for a streamed body.
+    pub fn body_size_guard(&self) -> BodySizeGuard {
+        BodySizeGuard::new(self.max_body_size)
+    }
+}
+
+/// Incremental body size check for bodies read in chunks.
+///
+/// Unlike [`RequestLimits::validate_body_size`], which needs the whole body
+/// buffered first, the guard fails on the first chunk that takes the running
+/// total past the limit, so the caller can stop reading.
+#[derive(Debug, Clone)]
+pub struct BodySizeGuard {
+    limit: usize,
+    received: usize,
+}
+
+impl BodySizeGuard {
+    /// Create a guard allowing at most `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self { limit, received: 0 }
+    }
+
+    /// Account for a chunk of `chunk_len` bytes.
+    pub fn feed(&mut self, chunk_len: usize) -> Result<(), ValidationError2> {
+        self.received = self.received.saturating_add(chunk_len);
+        if self.received > self.limit {
+            return Err(ValidationError2::BodyTooLarge {
+                size: self.received,
+                limit: self.limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// Bytes fed so far.
+    pub fn received(&self) -> usize {
+        self.received
+    }
 }
 
+/// Separator used when duplicate header lines are folded into one field.
+const FOLD_SEPARATOR: &str = ", ";
+
 /// Compile a JSON schema with format validation enabled.
 ///
 /// Supports formats: date-time, email, uuid, uri, ipv4, ipv6.
@@ -327,16 +452,58 @@ pub struct OperationValidator {
     query_params: Vec<CompiledParam>,
     /// Header parameters with their compiled schemas.
     header_params: Vec<CompiledParam>,
+    /// Cookie parameters with their compiled schemas.
+    cookie_params: Vec<CompiledParam>,
     /// OpenAPI 3.2: querystring parameter (entire query string as single value).
     querystring_param: Option<CompiledParam>,
     /// Request body configuration.
     request_body: Option<CompiledRequestBody>,
+    /// `x-barbacane-accept` content-type allowlist (empty = no allowlist).
+    accepted_content_types: Vec<String>,
+    /// Upper bound on a `Content-Encoding`-decoded body, in bytes.
+    max_decoded_body_size: usize,
 }
 
 struct CompiledParam {
     name: String,
     required: bool,
     schema: Option<jsonschema::Validator>,
+    /// Top-level schema `type`, used to coerce raw query values.
+    schema_type: Option<String>,
+    /// `items.type` of an array schema, used to coerce each element.
+    item_type: Option<String>,
+    /// Serialization of an `array` query parameter; `None` for scalars.
+    array_style: Option<ArrayStyle>,
+    allow_reserved: bool,
+}
+
+/// How an array query parameter is serialized (OpenAPI `style`/`explode`).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ArrayStyle {
+    /// One key per element: `?ids=1&ids=2` (`explode: true`, the default).
+    Exploded,
+    /// A single key whose value joins the elements: `?ids=1,2` (`form`),
+    /// `?ids=1%202` (`spaceDelimited`) or `?ids=1|2` (`pipeDelimited`).
+    Delimited(char),
+}
+
+impl ArrayStyle {
+    /// Query serialization of an `array` parameter; `None` for other types.
+    fn for_param(param: &Parameter, schema_type: Option<&str>) -> Option<Self> {
+        if schema_type != Some("array") {
+            return None;
+        }
+        let style = param.style.as_deref().unwrap_or("form");
+        let explode = param.explode.unwrap_or(style == "form");
+        if explode {
+            return Some(ArrayStyle::Exploded);
+        }
+        Some(ArrayStyle::Delimited(match style {
+            "spaceDelimited" => ' ',
+            "pipeDelimited" => '|',
+            _ => ',',
+        }))
+    }
 }
 
 struct CompiledRequestBody {
@@ -345,21 +512,74 @@ struct CompiledRequestBody {
     content: HashMap<String, Option<jsonschema::Validator>>,
 }
 
+/// The primitive type a raw parameter value is coerced to: the schema's
+/// `type`, the first non-`null` entry of a `type` array (a nullable type),
+/// or, for an untyped `enum`, the type every non-null member shares.
+fn primitive_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => return Some(t),
+        Some(Value::Array(types)) => {
+            return types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|t| *t != "null");
+        }
+        _ => {}
+    }
+    let members: Vec<&Value> = schema
+        .get("enum")?
+        .as_array()?
+        .iter()
+        .filter(|v| !v.is_null())
+        .collect();
+    let first = members.first()?;
+    let shared = |is: fn(&Value) -> bool| members.iter().all(|v| is(v));
+    if first.is_i64() || first.is_u64() {
+        shared(|v| v.is_i64() || v.is_u64()).then_some("integer")
+    } else if first.is_number() {
+        shared(Value::is_number).then_some("number")
+    } else if first.is_boolean() {
+        shared(Value::is_boolean).then_some("boolean")
+    } else {
+        None
+    }
+}
+
+/// Coerce a raw parameter string to the schema's primitive `type`
+/// (`integer`, `number`, `boolean`). Values that don't parse stay strings,
+/// so the schema rejects them.
+fn coerce_param_value(raw: String, schema_type: Option<&str>) -> Value {
+    let coerced = match schema_type {
+        Some("integer") => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| raw.parse::<u64>().map(Value::from))
+            .ok(),
+        Some("number") => raw
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        Some("boolean") => raw.parse::<bool>().ok().map(Value::Bool),
+        _ => None,
+    };
+    coerced.unwrap_or(Value::String(raw))
+}
+
 /// Validate a set of compiled parameters against a value lookup.
 ///
-/// Shared logic for path, query, and header parameter validation.
+/// Shared logic for path, query, header, and cookie parameter validation.
 fn validate_params(
     params: &[CompiledParam],
-    lookup: impl Fn(&str) -> Option<String>,
+    lookup: impl Fn(&CompiledParam) -> Option<Value>,
     location: &str,
 ) -> Result<(), Vec<ValidationError2>> {
     let mut errors = Vec::new();
 
     for param in params {
-        match lookup(&param.name) {
-            Some(value) => {
+        match lookup(param) {
+            Some(json_value) => {
                 if let Some(schema) = &param.schema {
-                    let json_value = Value::String(value);
                     let validation_errors: Vec<_> = schema.iter_errors(&json_value).collect();
                     if !validation_errors.is_empty() {
                         let reasons: Vec<String> =
@@ -395,21 +615,33 @@ impl OperationValidator {
         let mut path_params = Vec::new();
         let mut query_params = Vec::new();
         let mut header_params = Vec::new();
+        let mut cookie_params = Vec::new();
         let mut querystring_param = None;
 
         for param in parameters {
+            let schema_type = param.schema.as_ref().and_then(primitive_type);
+            let item_type = param
+                .schema
+                .as_ref()
+                .and_then(|s| s.get("items"))
+                .and_then(primitive_type);
             let compiled = CompiledParam {
                 name: param.name.clone(),
                 required: param.required || param.location == "path", // Path params always required
                 schema: param.schema.as_ref().and_then(compile_schema_with_formats),
+                schema_type: schema_type.map(str::to_string),
+                item_type: item_type.map(str::to_string),
+                array_style: ArrayStyle::for_param(param, schema_type),
+                allow_reserved: param.allow_reserved,
             };
 
             match param.location.as_str() {
                 "path" => path_params.push(compiled),
                 "query" => query_params.push(compiled),
                 "header" => header_params.push(compiled),
+                "cookie" => cookie_params.push(compiled),
                 "querystring" => querystring_param = Some(compiled),
-                _ => {} // Ignore cookie params for now
+                _ => {}
             }
         }
 
@@ -432,12 +664,32 @@ impl OperationValidator {
             path_params,
             query_params,
             header_params,
+            cookie_params,
             querystring_param,
             request_body: compiled_body,
+            accepted_content_types: Vec::new(),
+            max_decoded_body_size: RequestLimits::default().max_body_size,
         }
     }
 
-    /// Validate path parameters extracted by the router.
+    /// Restrict request bodies to the given media types (`x-barbacane-accept`),
+    /// checked before the body schema lookup. Entries are lowercase
+    /// `type/subtype` or `type/*`; an empty list disables the check.
+    pub fn with_accepted_content_types(mut self, types: &[String]) -> Self {
+        self.accepted_content_types = types.to_vec();
+        self
+    }
+
+    /// Cap the size of a compressed body once decoded (normally
+    /// `RequestLimits::max_body_size`), so small gzip bombs are rejected.
+    pub fn with_max_decoded_body_size(mut self, limit: usize) -> Self {
+        self.max_decoded_body_size = limit;
+        self
+    }
+
+    /// Validate path parameters extracted by the router. Values are coerced
+    /// to the schema's primitive type first, so `/orders/42` matches
+    /// `type: integer` (or an `enum` of integers).
     pub fn validate_path_params(
         &self,
         params: &[(String, String)],
@@ -445,7 +697,10 @@ impl OperationValidator {
         let param_map: HashMap<_, _> = params.iter().cloned().collect();
         validate_params(
             &self.path_params,
-            |name| param_map.get(name).cloned(),
+            |param| {
+                let raw = param_map.get(&param.name)?.clone();
+                Some(coerce_param_value(raw, param.schema_type.as_deref()))
+            },
             "path",
         )
     }
@@ -455,15 +710,15 @@ impl OperationValidator {
         &self,
         query_string: Option<&str>,
     ) -> Result<(), Vec<ValidationError2>> {
-        // Parse pairs, tracking how many times each key occurs. The raw query
-        // string is forwarded verbatim to the dispatcher/upstream, but this
-        // validator only sees the collapsed (last-wins) value — so a repeated
-        // declared parameter is an HTTP-parameter-pollution vector: the check
-        // would pass on one value while a different value the upstream may
-        // consume rides along unvalidated. Reject duplicates of declared query
-        // params rather than validate a value the upstream might not use.
-        let mut param_map: HashMap<String, String> = HashMap::new();
-        let mut counts: HashMap<String, usize> = HashMap::new();
+        // Parse pairs, keeping every occurrence of each key in order. The raw
+        // query string is forwarded verbatim to the dispatcher/upstream, so a
+        // repeated scalar parameter is an HTTP-parameter-pollution vector: the
+        // check would pass on one value while a different value the upstream
+        // may consume rides along unvalidated. Reject duplicates of declared
+        // query params rather than validate a value the upstream might not
+        // use — except exploded arrays, where repetition is the serialization
+        // and every occurrence is validated as an element.
+        let mut param_map: HashMap<String, Vec<String>> = HashMap::new();
         for pair in query_string
             .unwrap_or("")
             .split('&')
@@ -473,14 +728,25 @@ impl OperationValidator {
             let Some(key) = parts.next() else { continue };
             let value = parts.next().unwrap_or("");
             let key = percent_decode(key);
-            *counts.entry(key.clone()).or_insert(0) += 1;
-            param_map.insert(key, percent_decode(value));
+            // `allowReserved` values may carry reserved characters verbatim,
+            // so `+` is a literal plus there, not form-encoded space.
+            let allow_reserved = self
+                .query_params
+                .iter()
+                .any(|p| p.allow_reserved && p.name == key);
+            let value = if allow_reserved {
+                percent_decode_reserved(value)
+            } else {
+                percent_decode(value)
+            };
+            param_map.entry(key).or_default().push(value);
         }
 
         let mut errors: Vec<ValidationError2> = self
             .query_params
             .iter()
-            .filter(|p| counts.get(&p.name).copied().unwrap_or(0) > 1)
+            .filter(|p| p.array_style != Some(ArrayStyle::Exploded))
+            .filter(|p| param_map.get(&p.name).is_some_and(|v| v.len() > 1))
             .map(|p| ValidationError2::InvalidParameter {
                 name: p.name.clone(),
                 location: "query".into(),
@@ -494,7 +760,25 @@ impl OperationValidator {
 
         validate_params(
             &self.query_params,
-            |name| param_map.get(name).cloned(),
+            |param| {
+                let values = param_map.get(&param.name)?;
+                let item_type = param.item_type.as_deref();
+                Some(match param.array_style {
+                    Some(ArrayStyle::Exploded) => Value::Array(
+                        values
+                            .iter()
+                            .map(|v| coerce_param_value(v.clone(), item_type))
+                            .collect(),
+                    ),
+                    Some(ArrayStyle::Delimited(delimiter)) => Value::Array(
+                        values[0]
+                            .split(delimiter)
+                            .map(|v| coerce_param_value(v.to_string(), item_type))
+                            .collect(),
+                    ),
+                    None => coerce_param_value(values[0].clone(), param.schema_type.as_deref()),
+                })
+            },
             "query",
         )
     }
@@ -536,50 +820,174 @@ impl OperationValidator {
         Ok(())
     }
 
-    /// Validate request headers (case-insensitive matching).
+    /// Validate request headers (case-insensitive matching). Each header
+    /// carries one value; see [`validate_headers_multi`](Self::validate_headers_multi).
     pub fn validate_headers(
         &self,
         headers: &HashMap<String, String>,
     ) -> Result<(), Vec<ValidationError2>> {
-        let headers_lower: HashMap<String, String> = headers
+        let headers: HashMap<String, Vec<String>> = headers
             .iter()
-            .map(|(k, v)| (k.to_lowercase(), v.clone()))
+            .map(|(k, v)| (k.clone(), vec![v.clone()]))
             .collect();
+        self.validate_headers_multi(&headers)
+    }
+
+    /// Validate request headers that may repeat (case-insensitive matching).
+    ///
+    /// An `array` header parameter validates every value, each split on
+    /// commas (OpenAPI `simple` style), with elements coerced to the
+    /// schema's `items.type`. A scalar parameter validates the first value.
+    pub fn validate_headers_multi(
+        &self,
+        headers: &HashMap<String, Vec<String>>,
+    ) -> Result<(), Vec<ValidationError2>> {
+        let mut headers_lower: HashMap<String, Vec<&str>> = HashMap::new();
+        for (name, values) in headers {
+            headers_lower
+                .entry(name.to_lowercase())
+                .or_default()
+                .extend(values.iter().map(String::as_str));
+        }
 
         validate_params(
             &self.header_params,
-            |name| headers_lower.get(&name.to_lowercase()).cloned(),
+            |param| {
+                let values = headers_lower.get(&param.name.to_lowercase())?;
+                let first = values.first()?;
+                Some(if param.schema_type.as_deref() == Some("array") {
+                    Value::Array(
+                        values
+                            .iter()
+                            .flat_map(|v| v.split(','))
+                            .map(|item| {
+                                coerce_param_value(
+                                    item.trim().to_string(),
+                                    param.item_type.as_deref(),
+                                )
+                            })
+                            .collect(),
+                    )
+                } else {
+                    Value::String(first.to_string())
+                })
+            },
             "header",
         )
     }
 
+    /// Validate cookie parameters against the `Cookie` header
+    /// (`name=value` pairs separated by `;`, RFC 6265). If a cookie name
+    /// repeats, the first occurrence is validated.
+    pub fn validate_cookies(
+        &self,
+        cookie_header: Option<&str>,
+    ) -> Result<(), Vec<ValidationError2>> {
+        if self.cookie_params.is_empty() {
+            return Ok(());
+        }
+
+        let mut cookies: HashMap<&str, &str> = HashMap::new();
+        for pair in cookie_header.unwrap_or("").split(';') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            // A cookie value may be wrapped in double quotes.
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            cookies.entry(name.trim()).or_insert(value);
+        }
+
+        validate_params(
+            &self.cookie_params,
+            |param| cookies.get(param.name.as_str()).map(|v| Value::from(*v)),
+            "cookie",
+        )
+    }
+
     /// Validate request body.
+    ///
+    /// Emptiness is checked before the media type: an empty body on an
+    /// operation with a required body is reported as `MissingRequiredBody`
+    /// whatever `Content-Type` was sent (or if none was), so the real problem
+    /// isn't masked. `UnsupportedContentType` is only reported for a non-empty
+    /// body whose media type the operation doesn't declare; a non-empty body
+    /// without a `Content-Type` is treated as `application/octet-stream`.
+    ///
+    /// If the operation has an `x-barbacane-accept` allowlist, a non-empty
+    /// body's media type must match it before the schema map is consulted, so
+    /// a permissive `*/*` schema entry can't widen what the operation accepts.
     pub fn validate_body(
         &self,
         content_type: Option<&str>,
         body: &[u8],
     ) -> Result<(), Vec<ValidationError2>> {
-        let Some(body_spec) = &self.request_body else {
-            // No body spec, nothing to validate
-            return Ok(());
-        };
+        self.check_body(content_type, None, body)
+    }
 
-        // Check if body is required but missing
-        if body_spec.required && body.is_empty() {
-            return Err(vec![ValidationError2::MissingRequiredBody]);
-        }
+    /// Validate a request body sent with a `Content-Encoding` (`gzip`,
+    /// `deflate`, or `identity`). The body is only decoded, up to the
+    /// configured maximum decoded size, once its media type has matched a JSON
+    /// schema; bodies nothing reads pass through in their encoded form.
+    pub fn validate_encoded_body(
+        &self,
+        content_type: Option<&str>,
+        content_encoding: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), Vec<ValidationError2>> {
+        self.check_body(content_type, content_encoding, body)
+    }
 
-        // If body is empty and not required, skip validation
+    fn check_body(
+        &self,
+        content_type: Option<&str>,
+        content_encoding: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), Vec<ValidationError2>> {
         if body.is_empty() {
-            return Ok(());
+            return match &self.request_body {
+                Some(body_spec) if body_spec.required => {
+                    Err(vec![ValidationError2::MissingRequiredBody])
+                }
+                _ => Ok(()),
+            };
         }
 
         // Check content type
-        let ct = content_type.unwrap_or("application/octet-stream");
-        let base_ct = ct.split(';').next().unwrap_or(ct).trim();
+        let ct = content_type.unwrap_or("application/octet-stream").trim();
+        let base_ct = media_type_base(ct);
+
+        if !self.accepted_content_types.is_empty()
+            && !self
+                .accepted_content_types
+                .iter()
+                .any(|accepted| media_type_matches(accepted, base_ct))
+        {
+            return Err(vec![ValidationError2::UnsupportedContentType(
+                base_ct.to_string(),
+            )]);
+        }
+
+        let Some(body_spec) = &self.request_body else {
+            // No body spec, nothing to validate
+            return Ok(());
+        };
 
-        // Find matching content type (with wildcard support)
-        let schema = if let Some(schema) = body_spec.content.get(base_ct) {
+        // Find matching content type: an exact (parametered) match wins, then
+        // the bare media type, then any declared type with the same base
+        // (`application/json; charset=utf-8`), then the wildcard.
+        let schema = if let Some(schema) = body_spec.content.get(ct) {
+            schema
+        } else if let Some(schema) = body_spec.content.get(base_ct) {
+            schema
+        } else if let Some((_, schema)) = body_spec
+            .content
+            .iter()
+            .find(|(declared, _)| media_type_base(declared).eq_ignore_ascii_case(base_ct))
+        {
             schema
         } else if let Some(schema) = body_spec.content.get("*/*") {
             schema
@@ -591,8 +999,10 @@ impl OperationValidator {
 
         // Validate JSON body against schema
         if let Some(schema) = schema {
-            if base_ct.contains("json") {
-                let json_body: Value = match serde_json::from_slice(body) {
+            if is_json_media_type(base_ct) {
+                let body = decode_body(content_encoding, body, self.max_decoded_body_size)
+                    .map_err(|e| vec![e])?;
+                let json_body: Value = match serde_json::from_slice(&body) {
                     Ok(v) => v,
                     Err(e) => {
                         return Err(vec![ValidationError2::InvalidBody(format!(
@@ -602,11 +1012,9 @@ impl OperationValidator {
                     }
                 };
 
-                let validation_errors: Vec<_> = schema.iter_errors(&json_body).collect();
-                if !validation_errors.is_empty() {
-                    let reasons: Vec<String> =
-                        validation_errors.iter().map(|e| e.to_string()).collect();
-                    return Err(vec![ValidationError2::InvalidBody(reasons.join("; "))]);
+                let errors = schema_errors(schema, &json_body, "body");
+                if !errors.is_empty() {
+                    return Err(errors);
                 }
             }
         }
@@ -623,14 +1031,201 @@ impl OperationValidator {
         content_type: Option<&str>,
         body: &[u8],
     ) -> Result<(), Vec<ValidationError2>> {
-        // Validate in order: path -> query -> querystring -> headers -> body
+        // Validate in order: path -> query -> querystring -> headers -> cookies -> body
         self.validate_path_params(path_params)?;
         self.validate_query_params(query_string)?;
         self.validate_querystring(query_string)?;
         self.validate_headers(headers)?;
-        self.validate_body(content_type, body)?;
+        self.validate_cookies(header_value(headers, "cookie"))?;
+        self.validate_encoded_body(
+            content_type,
+            header_value(headers, "content-encoding"),
+            body,
+        )?;
         Ok(())
     }
+
+    /// Validate entire request, collecting the errors of every category
+    /// (same order as [`validate_request`](Self::validate_request)). Empty
+    /// when the request is valid.
+    pub fn validate_request_collect(
+        &self,
+        path_params: &[(String, String)],
+        query_string: Option<&str>,
+        headers: &HashMap<String, String>,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Vec<ValidationError2> {
+        [
+            self.validate_path_params(path_params),
+            self.validate_query_params(query_string),
+            self.validate_querystring(query_string),
+            self.validate_headers(headers),
+            self.validate_cookies(header_value(headers, "cookie")),
+            self.validate_encoded_body(
+                content_type,
+                header_value(headers, "content-encoding"),
+                body,
+            ),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .flatten()
+        .collect()
+    }
+}
+
+/// Compiled validator for an AsyncAPI message payload.
+pub struct MessageValidator {
+    /// Compiled payload schema (`None` = any payload).
+    schema: Option<jsonschema::Validator>,
+    /// Whether the payload is JSON (no `contentType`, or a JSON media type).
+    json: bool,
+}
+
+impl MessageValidator {
+    /// Create a validator from a parsed AsyncAPI message.
+    pub fn new(message: &Message) -> Self {
+        Self {
+            schema: message
+                .payload
+                .as_ref()
+                .and_then(compile_schema_with_formats),
+            json: message
+                .content_type
+                .as_deref()
+                .is_none_or(|ct| is_json_media_type(media_type_base(ct))),
+        }
+    }
+
+    /// Validate a message payload against the message schema. Payloads of
+    /// non-JSON messages are not checked. Empty when the payload is valid.
+    pub fn validate_payload(&self, payload: &[u8]) -> Vec<ValidationError2> {
+        let Some(schema) = &self.schema else {
+            return Vec::new();
+        };
+        if !self.json {
+            return Vec::new();
+        }
+
+        match serde_json::from_slice::<Value>(payload) {
+            Ok(value) => schema_errors(schema, &value, "payload"),
+            Err(e) => vec![ValidationError2::InvalidBody(format!(
+                "invalid JSON: {}",
+                e
+            ))],
+        }
+    }
+}
+
+/// Validate `instance` against `schema`, one error per violation, named by
+/// the JSON Pointer of the failing field so problem details can list each one.
+fn schema_errors(
+    schema: &jsonschema::Validator,
+    instance: &Value,
+    location: &str,
+) -> Vec<ValidationError2> {
+    schema
+        .iter_errors(instance)
+        .map(|e| ValidationError2::InvalidParameter {
+            name: schema_error_pointer(&e),
+            location: location.into(),
+            reason: e.to_string(),
+        })
+        .collect()
+}
+
+/// JSON Pointer of the field a schema error is about. For a missing
+/// `required` property that is the property itself, not the object holding it.
+fn schema_error_pointer(error: &jsonschema::ValidationError) -> String {
+    let mut pointer = error.instance_path.as_str().to_string();
+    if let jsonschema::error::ValidationErrorKind::Required { property } = &error.kind {
+        if let Some(property) = property.as_str() {
+            pointer.push('/');
+            pointer.push_str(&property.replace('~', "~0").replace('/', "~1"));
+        }
+    }
+    pointer
+}
+
+/// A header value from a request header map, looked up case-insensitively.
+fn header_value<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Undo a body's `Content-Encoding`. Codings listed as `gzip, deflate` were
+/// applied in that order, so they are removed last to first. Decoding stops
+/// one byte past `limit`, which is reported as [`ValidationError2::BodyTooLarge`].
+fn decode_body<'a>(
+    content_encoding: Option<&str>,
+    body: &'a [u8],
+    limit: usize,
+) -> Result<Cow<'a, [u8]>, ValidationError2> {
+    let mut decoded = Cow::Borrowed(body);
+    if body.is_empty() {
+        return Ok(decoded);
+    }
+
+    let codings = content_encoding.unwrap_or("").split(',').map(str::trim);
+    for coding in codings.rev().filter(|c| !c.is_empty()) {
+        let coding = coding.to_ascii_lowercase();
+        let reader: Box<dyn Read + '_> = match coding.as_str() {
+            "identity" => continue,
+            "gzip" | "x-gzip" => Box::new(flate2::read::GzDecoder::new(&decoded[..])),
+            "deflate" => Box::new(flate2::read::ZlibDecoder::new(&decoded[..])),
+            _ => {
+                return Err(ValidationError2::InvalidBody(format!(
+                    "unsupported content encoding '{}'",
+                    coding
+                )))
+            }
+        };
+
+        let mut buf = Vec::new();
+        reader
+            .take(limit as u64 + 1)
+            .read_to_end(&mut buf)
+            .map_err(|e| {
+                ValidationError2::InvalidBody(format!("invalid {} body: {}", coding, e))
+            })?;
+        if buf.len() > limit {
+            return Err(ValidationError2::BodyTooLarge {
+                size: buf.len(),
+                limit,
+            });
+        }
+        decoded = Cow::Owned(buf);
+    }
+    Ok(decoded)
+}
+
+/// A media type without its parameters (`application/json; charset=utf-8`
+/// -> `application/json`).
+fn media_type_base(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or(media_type).trim()
+}
+
+/// Whether a bare media type carries JSON: `*/json` or a `+json` structured
+/// syntax suffix (RFC 6839), e.g. `application/vnd.foo+json`.
+fn is_json_media_type(media_type: &str) -> bool {
+    media_type.split_once('/').is_some_and(|(_, subtype)| {
+        let subtype = subtype.to_ascii_lowercase();
+        subtype == "json" || subtype.ends_with("+json")
+    })
+}
+
+/// Match a request media type against an allowlist entry (`type/subtype` or
+/// `type/*`), case-insensitively.
+fn media_type_matches(accepted: &str, media_type: &str) -> bool {
+    match accepted.strip_suffix("/*") {
+        Some(ty) => media_type
+            .split_once('/')
+            .is_some_and(|(t, _)| t.eq_ignore_ascii_case(ty)),
+        None => accepted.eq_ignore_ascii_case(media_type),
+    }
 }
 
 /// Canonical percent-decoding of a URL component (handles `%XX` escapes and `+`).
@@ -640,6 +1235,17 @@ impl OperationValidator {
 /// per-byte `as char` cast. This is the single decoder used for both query and
 /// path parameters so routing and validation agree on the decoded value.
 pub fn percent_decode(input: &str) -> String {
+    decode_component(input, true)
+}
+
+/// Percent-decoding for `allowReserved` query values: `%XX` escapes are
+/// decoded but `+` is kept as a literal plus, since reserved characters are
+/// permitted unencoded in such values.
+pub fn percent_decode_reserved(input: &str) -> String {
+    decode_component(input, false)
+}
+
+fn decode_component(input: &str, plus_as_space: bool) -> String {
     let bytes = input.as_bytes();
     let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
     let mut i = 0;
@@ -655,7 +1261,7 @@ pub fn percent_decode(input: &str) -> String {
                     i += 1;
                 }
             },
-            b'+' => {
+            b'+' if plus_as_space => {
                 out.push(b' ');
                 i += 1;
             }
@@ -689,6 +1295,9 @@ mod tests {
             location: location.to_string(),
             required,
             schema,
+            allow_reserved: false,
+            style: None,
+            explode: None,
         }
     }
 
@@ -708,7 +1317,7 @@ mod tests {
 
     #[test]
     fn validate_path_param_schema() {
-        // Path parameters are always strings. Use a pattern to validate format.
+        // A string-typed path parameter is validated as-is; a pattern checks its format.
         let schema = serde_json::json!({
             "type": "string",
             "pattern": "^[0-9]+$"
@@ -725,6 +1334,62 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn validate_integer_path_param_is_coerced() {
+        let schema = serde_json::json!({ "type": "integer", "minimum": 1 });
+        let params = vec![make_param("orderId", "path", true, Some(schema))];
+        let validator = OperationValidator::new(&params, None);
+
+        let path = |v: &str| vec![("orderId".to_string(), v.to_string())];
+        assert!(validator.validate_path_params(&path("42")).is_ok());
+        assert!(validator.validate_path_params(&path("0")).is_err());
+        let errors = validator.validate_path_params(&path("x")).unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidParameter { name, location, .. }
+            if name == "orderId" && location == "path"
+        ));
+    }
+
+    #[test]
+    fn validate_enum_path_param_after_coercion() {
+        let path = |v: &str| vec![("version".to_string(), v.to_string())];
+
+        // Typed enum.
+        let schema = serde_json::json!({ "type": "integer", "enum": [1, 2] });
+        let validator =
+            OperationValidator::new(&[make_param("version", "path", true, Some(schema))], None);
+        assert!(validator.validate_path_params(&path("2")).is_ok());
+        assert!(validator.validate_path_params(&path("3")).is_err());
+
+        // Untyped enum: the members' shared type drives coercion.
+        let schema = serde_json::json!({ "enum": [1, 2] });
+        let validator =
+            OperationValidator::new(&[make_param("version", "path", true, Some(schema))], None);
+        assert!(validator.validate_path_params(&path("1")).is_ok());
+        assert!(validator.validate_path_params(&path("v1")).is_err());
+
+        // String enums are unaffected.
+        let schema = serde_json::json!({ "enum": ["v1", "v2"] });
+        let validator =
+            OperationValidator::new(&[make_param("version", "path", true, Some(schema))], None);
+        assert!(validator.validate_path_params(&path("v2")).is_ok());
+    }
+
+    #[test]
+    fn validate_nullable_path_param_is_coerced() {
+        let schema = serde_json::json!({ "type": ["integer", "null"] });
+        let params = vec![make_param("id", "path", true, Some(schema))];
+        let validator = OperationValidator::new(&params, None);
+
+        assert!(validator
+            .validate_path_params(&[("id".into(), "42".into())])
+            .is_ok());
+        assert!(validator
+            .validate_path_params(&[("id".into(), "x".into())])
+            .is_err());
+    }
+
     #[test]
     fn validate_required_query_param() {
         let params = vec![make_param("page", "query", true, None)];
@@ -749,6 +1414,142 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn validate_integer_query_param_is_coerced() {
+        let schema = serde_json::json!({"type": "integer", "minimum": 1});
+        let params = vec![make_param("page", "query", true, Some(schema))];
+        let validator = OperationValidator::new(&params, None);
+
+        assert!(validator.validate_query_params(Some("page=2")).is_ok());
+
+        let errors = validator
+            .validate_query_params(Some("page=abc"))
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidParameter { name, location, .. }
+            if name == "page" && location == "query"
+        ));
+
+        // Coerced values are still checked against the rest of the schema.
+        assert!(validator.validate_query_params(Some("page=0")).is_err());
+        assert!(validator.validate_query_params(Some("page=2.5")).is_err());
+    }
+
+    #[test]
+    fn validate_number_and_boolean_query_params_are_coerced() {
+        let params = vec![
+            make_param(
+                "ratio",
+                "query",
+                false,
+                Some(serde_json::json!({"type": "number"})),
+            ),
+            make_param(
+                "active",
+                "query",
+                false,
+                Some(serde_json::json!({"type": "boolean"})),
+            ),
+        ];
+        let validator = OperationValidator::new(&params, None);
+
+        assert!(validator
+            .validate_query_params(Some("ratio=0.5&active=true"))
+            .is_ok());
+        assert!(validator
+            .validate_query_params(Some("active=false"))
+            .is_ok());
+        assert!(validator.validate_query_params(Some("active=yes")).is_err());
+        assert!(validator.validate_query_params(Some("ratio=NaN")).is_err());
+    }
+
+    fn make_array_param(name: &str, explode: Option<bool>, schema: Value) -> Parameter {
+        Parameter {
+            explode,
+            ..make_param(name, "query", true, Some(schema))
+        }
+    }
+
+    #[test]
+    fn validate_exploded_array_query_param() {
+        let schema = serde_json::json!({"type": "array", "items": {"type": "integer"}});
+        let params = vec![make_array_param("ids", None, schema)];
+        let validator = OperationValidator::new(&params, None);
+
+        // Repeated keys are the default (form, explode) serialization.
+        assert!(validator.validate_query_params(Some("ids=1&ids=2")).is_ok());
+        assert!(validator.validate_query_params(Some("ids=1")).is_ok());
+
+        // Every occurrence is validated as an element.
+        let errors = validator
+            .validate_query_params(Some("ids=1&ids=x"))
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidParameter { name, location, .. }
+            if name == "ids" && location == "query"
+        ));
+
+        // Exploded form does not split on commas.
+        assert!(validator.validate_query_params(Some("ids=1,2")).is_err());
+    }
+
+    #[test]
+    fn validate_comma_delimited_array_query_param() {
+        let schema = serde_json::json!({"type": "array", "items": {"type": "integer"}});
+        let params = vec![make_array_param("ids", Some(false), schema)];
+        let validator = OperationValidator::new(&params, None);
+
+        assert!(validator.validate_query_params(Some("ids=1,2,3")).is_ok());
+        assert!(validator.validate_query_params(Some("ids=1,b")).is_err());
+
+        // Without explode, a repeated key is still ambiguous.
+        assert!(validator
+            .validate_query_params(Some("ids=1,2&ids=3"))
+            .is_err());
+    }
+
+    #[test]
+    fn validate_pipe_delimited_array_query_param() {
+        let schema = serde_json::json!({"type": "array", "items": {"type": "string"}});
+        let params = vec![Parameter {
+            style: Some("pipeDelimited".to_string()),
+            ..make_array_param("tags", None, schema)
+        }];
+        let validator = OperationValidator::new(&params, None);
+
+        // pipeDelimited defaults to explode: false.
+        let result = validator.validate_query_params(Some("tags=a|b"));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_array_query_param_item_constraints() {
+        let schema = serde_json::json!({
+            "type": "array",
+            "minItems": 2,
+            "maxItems": 3,
+            "items": {"type": "string", "enum": ["red", "green", "blue"]}
+        });
+        let params = vec![make_array_param("color", Some(false), schema)];
+        let validator = OperationValidator::new(&params, None);
+
+        assert!(validator
+            .validate_query_params(Some("color=red,blue"))
+            .is_ok());
+        // minItems
+        assert!(validator.validate_query_params(Some("color=red")).is_err());
+        // maxItems
+        assert!(validator
+            .validate_query_params(Some("color=red,green,blue,red"))
+            .is_err());
+        // items.enum
+        assert!(validator
+            .validate_query_params(Some("color=red,pink"))
+            .is_err());
+    }
+
     #[test]
     fn duplicate_declared_query_param_is_rejected() {
         // HTTP parameter pollution: a declared param with an enum constraint is
@@ -838,11 +1639,74 @@ mod tests {
     }
 
     #[test]
-    fn validate_unsupported_content_type() {
+    fn validate_body_errors_carry_json_pointers() {
         use barbacane_compiler::ContentSchema;
         use std::collections::BTreeMap;
 
-        let mut content = BTreeMap::new();
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "customer": {
+                    "type": "object",
+                    "required": ["email"],
+                    "properties": {
+                        "address": {
+                            "type": "object",
+                            "required": ["city"]
+                        }
+                    }
+                },
+                "items": {
+                    "type": "array",
+                    "items": { "type": "integer" }
+                }
+            }
+        });
+        let mut content = BTreeMap::new();
+        content.insert(
+            "application/json".to_string(),
+            ContentSchema {
+                schema: Some(schema),
+            },
+        );
+        let request_body = RequestBody {
+            required: true,
+            content,
+        };
+        let validator = OperationValidator::new(&[], Some(&request_body));
+
+        let errors = validator
+            .validate_body(Some("application/json"), br#"{"customer":{"address":{}}}"#)
+            .unwrap_err();
+        let mut pointers: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                ValidationError2::InvalidParameter { name, location, .. } => {
+                    assert_eq!(location, "body");
+                    name.as_str()
+                }
+                other => panic!("unexpected error: {other:?}"),
+            })
+            .collect();
+        pointers.sort_unstable();
+        assert_eq!(pointers, ["/customer/address/city", "/customer/email"]);
+
+        // Non-`required` errors point at the failing value itself.
+        let errors = validator
+            .validate_body(Some("application/json"), br#"{"items":[1,"two"]}"#)
+            .unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [ValidationError2::InvalidParameter { name, .. }] if name == "/items/1"
+        ));
+    }
+
+    #[test]
+    fn validate_unsupported_content_type() {
+        use barbacane_compiler::ContentSchema;
+        use std::collections::BTreeMap;
+
+        let mut content = BTreeMap::new();
         content.insert(
             "application/json".to_string(),
             ContentSchema { schema: None },
@@ -866,6 +1730,222 @@ mod tests {
         }
     }
 
+    #[test]
+    fn empty_required_body_reports_missing_body_regardless_of_content_type() {
+        use barbacane_compiler::ContentSchema;
+        use std::collections::BTreeMap;
+
+        let mut content = BTreeMap::new();
+        content.insert(
+            "application/json".to_string(),
+            ContentSchema { schema: None },
+        );
+        let request_body = RequestBody {
+            required: true,
+            content,
+        };
+        let validator = OperationValidator::new(&[], Some(&request_body));
+
+        // No Content-Type at all, and an undeclared one: both are a missing
+        // body, not an unsupported media type.
+        for ct in [None, Some("text/plain"), Some("application/json")] {
+            let errors = validator.validate_body(ct, &[]).unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert!(
+                matches!(errors[0], ValidationError2::MissingRequiredBody),
+                "content-type {ct:?} gave {:?}",
+                errors[0]
+            );
+        }
+    }
+
+    #[test]
+    fn non_empty_body_with_undeclared_content_type_is_unsupported() {
+        use barbacane_compiler::ContentSchema;
+        use std::collections::BTreeMap;
+
+        let mut content = BTreeMap::new();
+        content.insert(
+            "application/json".to_string(),
+            ContentSchema { schema: None },
+        );
+        let request_body = RequestBody {
+            required: true,
+            content,
+        };
+        let validator = OperationValidator::new(&[], Some(&request_body));
+
+        let errors = validator
+            .validate_body(Some("text/plain; charset=utf-8"), b"hello")
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::UnsupportedContentType(ct) if ct == "text/plain"
+        ));
+
+        // A body with no Content-Type is treated as application/octet-stream.
+        let errors = validator.validate_body(None, b"{}").unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::UnsupportedContentType(ct) if ct == "application/octet-stream"
+        ));
+    }
+
+    fn json_body_validator(media_type: &str) -> OperationValidator {
+        use barbacane_compiler::ContentSchema;
+        use std::collections::BTreeMap;
+
+        let mut content = BTreeMap::new();
+        content.insert(
+            media_type.to_string(),
+            ContentSchema {
+                schema: Some(serde_json::json!({
+                    "type": "object",
+                    "required": ["name"]
+                })),
+            },
+        );
+        let request_body = RequestBody {
+            required: true,
+            content,
+        };
+        OperationValidator::new(&[], Some(&request_body))
+    }
+
+    #[test]
+    fn validate_body_charset_parameter_on_either_side() {
+        // Request carries a charset, schema is keyed on the bare type.
+        let validator = json_body_validator("application/json");
+        assert!(validator
+            .validate_body(Some("application/json; charset=utf-8"), br#"{"name":"a"}"#)
+            .is_ok());
+        assert!(validator
+            .validate_body(Some("application/json; charset=utf-8"), b"{}")
+            .is_err());
+
+        // Schema is keyed on a parametered type, request is bare.
+        let validator = json_body_validator("application/json; charset=utf-8");
+        assert!(validator
+            .validate_body(Some("application/json"), br#"{"name":"a"}"#)
+            .is_ok());
+        assert!(validator
+            .validate_body(Some("Application/JSON"), b"{}")
+            .is_err());
+        assert!(validator.validate_body(Some("text/plain"), b"{}").is_err());
+    }
+
+    #[test]
+    fn validate_body_exact_parametered_match_wins() {
+        use barbacane_compiler::ContentSchema;
+        use std::collections::BTreeMap;
+
+        let mut content = BTreeMap::new();
+        content.insert(
+            "application/json".to_string(),
+            ContentSchema { schema: None },
+        );
+        content.insert(
+            "application/json; profile=strict".to_string(),
+            ContentSchema {
+                schema: Some(serde_json::json!({"type": "object", "required": ["name"]})),
+            },
+        );
+        let request_body = RequestBody {
+            required: true,
+            content,
+        };
+        let validator = OperationValidator::new(&[], Some(&request_body));
+
+        assert!(validator
+            .validate_body(Some("application/json"), b"{}")
+            .is_ok());
+        assert!(validator
+            .validate_body(Some("application/json; profile=strict"), b"{}")
+            .is_err());
+    }
+
+    #[test]
+    fn validate_body_vendor_json_suffix() {
+        let validator = json_body_validator("application/vnd.acme.order+json");
+
+        assert!(validator
+            .validate_body(
+                Some("application/vnd.acme.order+json; charset=utf-8"),
+                br#"{"name":"a"}"#
+            )
+            .is_ok());
+        let errors = validator
+            .validate_body(Some("application/vnd.acme.order+json"), b"{}")
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidParameter { location, .. } if location == "body"
+        ));
+        let errors = validator
+            .validate_body(Some("application/vnd.acme.order+json"), b"not json")
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidBody(reason) if reason.starts_with("invalid JSON")
+        ));
+    }
+
+    fn wildcard_body_validator() -> OperationValidator {
+        use barbacane_compiler::ContentSchema;
+        use std::collections::BTreeMap;
+
+        let mut content = BTreeMap::new();
+        content.insert("*/*".to_string(), ContentSchema { schema: None });
+        let request_body = RequestBody {
+            required: true,
+            content,
+        };
+        OperationValidator::new(&[], Some(&request_body))
+    }
+
+    #[test]
+    fn accept_allowlist_admits_listed_types() {
+        let validator = wildcard_body_validator()
+            .with_accepted_content_types(&["application/json".to_string(), "text/*".to_string()]);
+
+        assert!(validator
+            .validate_body(Some("application/json; charset=utf-8"), b"{}")
+            .is_ok());
+        assert!(validator
+            .validate_body(Some("Application/JSON"), b"{}")
+            .is_ok());
+        assert!(validator.validate_body(Some("text/csv"), b"a,b").is_ok());
+    }
+
+    #[test]
+    fn accept_allowlist_rejects_types_the_schema_map_would_accept() {
+        let validator = wildcard_body_validator()
+            .with_accepted_content_types(&["application/json".to_string()]);
+
+        let errors = validator
+            .validate_body(Some("application/xml"), b"<a/>")
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::UnsupportedContentType(ct) if ct == "application/xml"
+        ));
+        assert!(validator.validate_body(None, b"{}").is_err());
+
+        // The allowlist applies even without a requestBody definition.
+        let no_body = OperationValidator::new(&[], None)
+            .with_accepted_content_types(&["application/json".to_string()]);
+        assert!(no_body.validate_body(Some("text/plain"), b"x").is_err());
+        assert!(no_body.validate_body(Some("text/plain"), b"").is_ok());
+    }
+
+    #[test]
+    fn no_accept_allowlist_falls_back_to_schema_matching() {
+        let validator = wildcard_body_validator();
+        assert!(validator
+            .validate_body(Some("application/xml"), b"<a/>")
+            .is_ok());
+    }
+
     #[test]
     fn problem_details_format() {
         let errors = vec![ValidationError2::MissingRequiredParameter {
@@ -873,7 +1953,7 @@ mod tests {
             location: "path".into(),
         }];
 
-        let problem = ProblemDetails::validation_error(&errors, false);
+        let problem = ProblemDetails::validation_error(&errors, false, None);
         assert_eq!(problem.status, 400);
         assert_eq!(problem.error_type, "urn:barbacane:error:validation-failed");
 
@@ -881,6 +1961,94 @@ mod tests {
         assert!(json.contains("validation-failed"));
     }
 
+    #[test]
+    fn auth_error_matches_jwt_auth_401() {
+        let (problem, www_authenticate) = ProblemDetails::auth_error(
+            401,
+            "missing_token",
+            "Bearer token required",
+            Some("Bearer realm=\"api\""),
+        );
+
+        // jwt-auth's `unauthorized_response` for a missing token.
+        assert_eq!(
+            www_authenticate.as_deref(),
+            Some(
+                "Bearer realm=\"api\", error=\"missing_token\", \
+                 error_description=\"Bearer token required\""
+            )
+        );
+        let json: Value = serde_json::from_str(&problem.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "urn:barbacane:error:authentication-failed",
+                "title": "Authentication failed",
+                "status": 401,
+                "detail": "Bearer token required"
+            })
+        );
+    }
+
+    #[test]
+    fn auth_error_matches_oauth2_auth_403() {
+        let (problem, www_authenticate) = ProblemDetails::auth_error(
+            403,
+            "insufficient_scope",
+            "Token does not have required scopes",
+            Some("Bearer realm=\"api\""),
+        );
+
+        // oauth2-auth's `error_response` for insufficient scope.
+        assert_eq!(
+            www_authenticate.as_deref(),
+            Some(
+                "Bearer realm=\"api\", error=\"insufficient_scope\", \
+                 error_description=\"Token does not have required scopes\""
+            )
+        );
+        let json: Value = serde_json::from_str(&problem.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "urn:barbacane:error:authorization-failed",
+                "title": "Authorization failed",
+                "status": 403,
+                "detail": "Token does not have required scopes"
+            })
+        );
+
+        // No challenge, no header.
+        let (_, www_authenticate) =
+            ProblemDetails::auth_error(403, "insufficient_scope", "denied", None);
+        assert!(www_authenticate.is_none());
+    }
+
+    #[test]
+    fn problem_details_instance_serialized_when_set() {
+        let errors = vec![ValidationError2::MissingRequiredParameter {
+            name: "id".into(),
+            location: "path".into(),
+        }];
+
+        let problem =
+            ProblemDetails::validation_error(&errors, false, Some("/users/42".to_string()));
+        let json: Value = serde_json::from_str(&problem.to_json()).unwrap();
+        assert_eq!(json["instance"], "/users/42");
+    }
+
+    #[test]
+    fn problem_details_instance_omitted_when_none() {
+        let errors = vec![ValidationError2::MissingRequiredParameter {
+            name: "id".into(),
+            location: "path".into(),
+        }];
+
+        let problem = ProblemDetails::validation_error(&errors, false, None);
+        let json: Value = serde_json::from_str(&problem.to_json()).unwrap();
+        assert!(json.get("instance").is_none());
+    }
+
     #[test]
     fn problem_details_dev_mode() {
         let errors = vec![ValidationError2::MissingRequiredParameter {
@@ -888,7 +2056,7 @@ mod tests {
             location: "path".into(),
         }];
 
-        let problem = ProblemDetails::validation_error(&errors, true);
+        let problem = ProblemDetails::validation_error(&errors, true, None);
         let json = problem.to_json();
 
         // Dev mode should include error details
@@ -992,6 +2160,36 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn body_size_guard_under_limit() {
+        let limits = RequestLimits {
+            max_body_size: 100,
+            ..Default::default()
+        };
+        let mut guard = limits.body_size_guard();
+        for _ in 0..4 {
+            assert!(guard.feed(25).is_ok());
+        }
+        assert!(guard.feed(0).is_ok());
+        assert_eq!(guard.received(), 100);
+    }
+
+    #[test]
+    fn body_size_guard_fails_mid_stream() {
+        let mut guard = BodySizeGuard::new(100);
+        assert!(guard.feed(60).is_ok());
+        let err = guard.feed(60).unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError2::BodyTooLarge {
+                size: 120,
+                limit: 100
+            }
+        ));
+        // Stays over the limit for any later chunk.
+        assert!(guard.feed(1).is_err());
+    }
+
     // ========================
     // Format Validation Tests
     // ========================
@@ -1310,6 +2508,72 @@ mod tests {
         ));
     }
 
+    fn multi_headers(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(name, values)| {
+                let values = values.iter().map(|v| v.to_string()).collect();
+                (name.to_string(), values)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn validate_array_header_param_collects_every_value() {
+        let schema = serde_json::json!({
+            "type": "array",
+            "items": { "type": "integer", "maximum": 10 },
+            "maxItems": 3
+        });
+        let params = vec![make_param("X-Shard", "header", true, Some(schema))];
+        let validator = OperationValidator::new(&params, None);
+
+        // Repeated lines and comma-joined values are all elements.
+        let headers = multi_headers(&[("x-shard", &["1", "2, 3"])]);
+        assert!(validator.validate_headers_multi(&headers).is_ok());
+
+        // A later occurrence is validated too, not just the first.
+        let headers = multi_headers(&[("X-Shard", &["1", "42"])]);
+        let errors = validator.validate_headers_multi(&headers).unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidParameter { name, location, .. }
+            if name == "X-Shard" && location == "header"
+        ));
+
+        let headers = multi_headers(&[("x-shard", &["1", "2", "3", "4"])]);
+        assert!(validator.validate_headers_multi(&headers).is_err());
+
+        // The single-value API splits the one value the same way.
+        let mut headers = HashMap::new();
+        headers.insert("X-Shard".to_string(), "4,5".to_string());
+        assert!(validator.validate_headers(&headers).is_ok());
+    }
+
+    #[test]
+    fn validate_scalar_header_param_uses_first_value() {
+        let schema = serde_json::json!({
+            "type": "string",
+            "pattern": "^Bearer .+$"
+        });
+        let params = vec![make_param("Authorization", "header", true, Some(schema))];
+        let validator = OperationValidator::new(&params, None);
+
+        let headers = multi_headers(&[("authorization", &["Bearer token123", "Basic abc"])]);
+        assert!(validator.validate_headers_multi(&headers).is_ok());
+
+        let headers = multi_headers(&[("authorization", &["Basic abc", "Bearer token123"])]);
+        assert!(validator.validate_headers_multi(&headers).is_err());
+
+        // No values at all is a missing header.
+        let headers = multi_headers(&[("authorization", &[])]);
+        let errors = validator.validate_headers_multi(&headers).unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::MissingRequiredParameter { name, .. } if name == "Authorization"
+        ));
+    }
+
     #[test]
     fn validate_header_param_case_insensitive() {
         let params = vec![make_param("X-Request-Id", "header", true, None)];
@@ -1328,6 +2592,331 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn validate_request_collect_reports_every_category() {
+        use barbacane_compiler::ContentSchema;
+        use std::collections::BTreeMap;
+
+        let params = vec![
+            make_param(
+                "id",
+                "path",
+                true,
+                Some(serde_json::json!({"type": "string", "format": "uuid"})),
+            ),
+            make_param(
+                "page",
+                "query",
+                true,
+                Some(serde_json::json!({"type": "integer"})),
+            ),
+        ];
+        let mut content = BTreeMap::new();
+        content.insert(
+            "application/json".to_string(),
+            ContentSchema {
+                schema: Some(serde_json::json!({
+                    "type": "object",
+                    "required": ["name"]
+                })),
+            },
+        );
+        let request_body = RequestBody {
+            required: true,
+            content,
+        };
+        let validator = OperationValidator::new(&params, Some(&request_body));
+
+        let path = vec![("id".to_string(), "not-a-uuid".to_string())];
+        let errors = validator.validate_request_collect(
+            &path,
+            Some("page=abc"),
+            &HashMap::new(),
+            Some("application/json"),
+            b"{}",
+        );
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidParameter { location, .. } if location == "path"
+        ));
+        assert!(matches!(
+            &errors[1],
+            ValidationError2::InvalidParameter { location, .. } if location == "query"
+        ));
+        assert!(matches!(
+            &errors[2],
+            ValidationError2::InvalidParameter { location, .. } if location == "body"
+        ));
+
+        // Fail-fast validation stops at the path error.
+        let errors = validator
+            .validate_request(
+                &path,
+                Some("page=abc"),
+                &HashMap::new(),
+                Some("application/json"),
+                b"{}",
+            )
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+
+        // A valid request collects nothing.
+        let path = vec![(
+            "id".to_string(),
+            "6f1c2b1e-8c4d-4a1e-9f0a-2b3c4d5e6f70".to_string(),
+        )];
+        assert!(validator
+            .validate_request_collect(
+                &path,
+                Some("page=2"),
+                &HashMap::new(),
+                Some("application/json"),
+                br#"{"name":"x"}"#,
+            )
+            .is_empty());
+    }
+
+    // ========================
+    // Message Payload Tests
+    // ========================
+
+    fn make_message(content_type: Option<&str>) -> Message {
+        Message {
+            name: "UserSignedUp".to_string(),
+            payload: Some(serde_json::json!({
+                "type": "object",
+                "required": ["userId", "email"],
+                "properties": {
+                    "userId": { "type": "string", "format": "uuid" },
+                    "email": { "type": "string", "format": "email" }
+                }
+            })),
+            content_type: content_type.map(str::to_string),
+            bindings: Default::default(),
+        }
+    }
+
+    #[test]
+    fn validate_conforming_message_payload() {
+        let validator = MessageValidator::new(&make_message(Some("application/json")));
+        let errors = validator.validate_payload(
+            br#"{"userId":"6f1c2b1e-8c4d-4a1e-9f0a-2b3c4d5e6f70","email":"a@example.com"}"#,
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn validate_message_payload_missing_required_field() {
+        // No contentType: AsyncAPI messages default to JSON.
+        let validator = MessageValidator::new(&make_message(None));
+        let errors =
+            validator.validate_payload(br#"{"userId":"6f1c2b1e-8c4d-4a1e-9f0a-2b3c4d5e6f70"}"#);
+        assert!(matches!(
+            &errors[..],
+            [ValidationError2::InvalidParameter { name, location, .. }]
+            if name == "/email" && location == "payload"
+        ));
+    }
+
+    #[test]
+    fn validate_non_json_message_payload() {
+        let validator = MessageValidator::new(&make_message(Some("application/json")));
+        let errors = validator.validate_payload(b"not json");
+        assert!(matches!(
+            &errors[..],
+            [ValidationError2::InvalidBody(reason)] if reason.starts_with("invalid JSON")
+        ));
+
+        // Non-JSON channels are not parsed.
+        let validator = MessageValidator::new(&make_message(Some("application/octet-stream")));
+        assert!(validator.validate_payload(b"\x00\x01").is_empty());
+    }
+
+    // ========================
+    // Content-Encoding Tests
+    // ========================
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn validate_gzip_encoded_body() {
+        let validator = json_body_validator("application/json");
+        let body = gzip(br#"{"name":"a"}"#);
+
+        assert!(validator
+            .validate_encoded_body(Some("application/json"), Some("gzip"), &body)
+            .is_ok());
+        // Undecoded, the compressed bytes are not JSON.
+        assert!(validator
+            .validate_body(Some("application/json"), &body)
+            .is_err());
+        // The schema still applies to the decoded body.
+        assert!(validator
+            .validate_encoded_body(Some("application/json"), Some("GZIP"), &gzip(b"{}"))
+            .is_err());
+
+        // validate_request reads Content-Encoding from the headers.
+        let mut headers = HashMap::new();
+        headers.insert("Content-Encoding".to_string(), "gzip".to_string());
+        assert!(validator
+            .validate_request(&[], None, &headers, Some("application/json"), &body)
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_deflate_encoded_body() {
+        use std::io::Write;
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(br#"{"name":"a"}"#).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let validator = json_body_validator("application/json");
+        assert!(validator
+            .validate_encoded_body(Some("application/json"), Some("deflate"), &body)
+            .is_ok());
+    }
+
+    #[test]
+    fn encoded_body_decompressing_past_limit_is_rejected() {
+        let validator = json_body_validator("application/json").with_max_decoded_body_size(1024);
+        let padding = " ".repeat(4096);
+        let body = gzip(format!(r#"{{"name":"a"{padding}}}"#).as_bytes());
+        assert!(body.len() < 1024);
+
+        let errors = validator
+            .validate_encoded_body(Some("application/json"), Some("gzip"), &body)
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::BodyTooLarge { limit: 1024, .. }
+        ));
+    }
+
+    #[test]
+    fn unsupported_or_corrupt_encoding_is_rejected() {
+        let validator = json_body_validator("application/json");
+
+        let errors = validator
+            .validate_encoded_body(Some("application/json"), Some("br"), b"\x0b\x02")
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidBody(reason) if reason.contains("unsupported content encoding 'br'")
+        ));
+
+        let errors = validator
+            .validate_encoded_body(Some("application/json"), Some("gzip"), br#"{"name":"a"}"#)
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidBody(reason) if reason.starts_with("invalid gzip body")
+        ));
+
+        // identity is a no-op.
+        assert!(validator
+            .validate_encoded_body(
+                Some("application/json"),
+                Some("identity"),
+                br#"{"name":"a"}"#
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn encoded_body_without_json_schema_is_not_decoded() {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Encoding".to_string(), "br".to_string());
+
+        // No requestBody: the body is passed through, whatever its coding.
+        let no_body = OperationValidator::new(&[], None);
+        assert!(no_body
+            .validate_request(&[], None, &headers, Some("application/json"), b"\x0b\x02")
+            .is_ok());
+
+        // A declared but non-JSON media type isn't read either, so a gzip body
+        // that would decode past the limit is not expanded.
+        let validator =
+            json_body_validator("application/octet-stream").with_max_decoded_body_size(16);
+        let body = gzip(&[0u8; 4096]);
+        assert!(validator
+            .validate_encoded_body(Some("application/octet-stream"), Some("gzip"), &body)
+            .is_ok());
+    }
+
+    // ========================
+    // Cookie Validation Tests
+    // ========================
+
+    #[test]
+    fn validate_required_cookie_param() {
+        let schema = serde_json::json!({
+            "type": "string",
+            "pattern": "^[a-f0-9]{8}$"
+        });
+        let params = vec![make_param("session", "cookie", true, Some(schema))];
+        let validator = OperationValidator::new(&params, None);
+
+        // Present and valid, among other cookies
+        let result = validator.validate_cookies(Some("theme=dark; session=deadbeef; lang=en"));
+        assert!(result.is_ok());
+
+        // Quoted value
+        let result = validator.validate_cookies(Some("session=\"deadbeef\""));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_missing_required_cookie_param() {
+        let params = vec![make_param("session", "cookie", true, None)];
+        let validator = OperationValidator::new(&params, None);
+
+        for header in [None, Some("theme=dark")] {
+            let errors = validator.validate_cookies(header).unwrap_err();
+            assert!(matches!(
+                &errors[0],
+                ValidationError2::MissingRequiredParameter { name, location }
+                if name == "session" && location == "cookie"
+            ));
+        }
+    }
+
+    #[test]
+    fn validate_cookie_param_schema() {
+        let schema = serde_json::json!({
+            "type": "string",
+            "pattern": "^[a-f0-9]{8}$"
+        });
+        let params = vec![make_param("session", "cookie", true, Some(schema))];
+        let validator = OperationValidator::new(&params, None);
+
+        let errors = validator
+            .validate_cookies(Some("session=not-hex!"))
+            .unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError2::InvalidParameter { name, location, .. }
+            if name == "session" && location == "cookie"
+        ));
+
+        // validate_request checks cookies from the Cookie header
+        let mut headers = HashMap::new();
+        headers.insert("Cookie".to_string(), "session=not-hex!".to_string());
+        assert!(validator
+            .validate_request(&[], None, &headers, None, b"")
+            .is_err());
+        headers.insert("Cookie".to_string(), "session=0badf00d".to_string());
+        assert!(validator
+            .validate_request(&[], None, &headers, None, b"")
+            .is_ok());
+    }
+
     #[test]
     fn validate_asyncapi_channel_parameter() {
         // Channel parameters (e.g., notifications/{userId}) are path params
@@ -1423,6 +3012,40 @@ mod tests {
         assert_eq!(percent_decode("%zz"), "%zz");
     }
 
+    #[test]
+    fn allow_reserved_query_param_keeps_reserved_chars() {
+        let mut param = make_param(
+            "path",
+            "query",
+            true,
+            Some(serde_json::json!({"type": "string", "pattern": "^[a-z/+]+$"})),
+        );
+        param.allow_reserved = true;
+        let validator = OperationValidator::new(&[param], None);
+
+        // `/` and `+` arrive unencoded; `+` must not be turned into a space.
+        assert!(validator.validate_query_params(Some("path=a/b+c")).is_ok());
+        // Percent escapes are still decoded.
+        assert!(validator
+            .validate_query_params(Some("path=a%2Fb%2Bc"))
+            .is_ok());
+    }
+
+    #[test]
+    fn default_query_param_decodes_plus_as_space() {
+        let params = vec![make_param(
+            "q",
+            "query",
+            true,
+            Some(serde_json::json!({"type": "string", "pattern": "^[a-z+]+$"})),
+        )];
+        let validator = OperationValidator::new(&params, None);
+
+        // Without allowReserved, `+` is form-encoded space and fails the pattern.
+        assert!(validator.validate_query_params(Some("q=a+b")).is_err());
+        assert!(validator.validate_query_params(Some("q=a%2Bb")).is_ok());
+    }
+
     #[test]
     fn header_count_counts_duplicates() {
         let limits = RequestLimits {
@@ -1443,4 +3066,43 @@ mod tests {
         assert!(limits.validate_header_size("x", 10).is_ok());
         assert!(limits.validate_header_size("x-big", 11).is_err());
     }
+
+    #[test]
+    fn folded_header_size_counts_all_values_and_separators() {
+        let limits = RequestLimits {
+            max_header_size: 20,
+            ..Default::default()
+        };
+        // "x-tag" (5) + "aaaa" (4) + ", " (2) + "bbbb" (4) + ", " (2) + "cc" (2) = 19.
+        let mut headers = HeaderMap::new();
+        for value in ["aaaa", "bbbb", "cc"] {
+            headers.append("x-tag", value.parse().unwrap());
+        }
+        assert!(limits.validate_folded_header_sizes(&headers).is_ok());
+
+        // One more byte in any value tips the folded field over the limit,
+        // even though every individual line is far below it.
+        let mut headers = HeaderMap::new();
+        for (name, value) in [
+            ("x-tag", "aaaa"),
+            ("x-other", "o"),
+            ("x-tag", "bbbb"),
+            ("x-tag", "cccc"),
+        ] {
+            headers.append(name, value.parse().unwrap());
+        }
+        for (name, value) in &headers {
+            assert!(limits
+                .validate_header_size(name.as_str(), name.as_str().len() + value.len())
+                .is_ok());
+        }
+        match limits.validate_folded_header_sizes(&headers) {
+            Err(ValidationError2::HeaderTooLarge { name, size, limit }) => {
+                assert_eq!(name, "x-tag");
+                assert_eq!(size, 21);
+                assert_eq!(limit, 20);
+            }
+            other => panic!("expected HeaderTooLarge, got {:?}", other),
+        }
+    }
 }
@@ -1,7 +1,13 @@
 // Flags x-barbacane-* keys that are not recognized by Barbacane.
 // Only x-barbacane-dispatch and x-barbacane-middlewares are valid.
 
-const KNOWN = new Set(["x-barbacane-dispatch", "x-barbacane-middlewares", "x-barbacane-mcp"]);
+const KNOWN = new Set([
+  "x-barbacane-dispatch",
+  "x-barbacane-middlewares",
+  "x-barbacane-mcp",
+  "x-barbacane-timeout",
+  "x-barbacane-plugins",
+]);
 
 function getSchema() {
   return {
@@ -24,7 +30,7 @@ function collectUnknown(obj, path, results) {
   for (const [key, value] of Object.entries(obj)) {
     if (key.startsWith("x-barbacane-") && !KNOWN.has(key)) {
       results.push({
-        message: `Unknown Barbacane extension "${key}" at ${path}. Only x-barbacane-dispatch, x-barbacane-middlewares, and x-barbacane-mcp are recognized.`,
+        message: `Unknown Barbacane extension "${key}" at ${path}. Only x-barbacane-dispatch, x-barbacane-middlewares, x-barbacane-mcp, x-barbacane-timeout, and x-barbacane-plugins are recognized.`,
       });
     }
 
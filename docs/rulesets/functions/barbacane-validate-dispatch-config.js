@@ -36,6 +36,7 @@ const schemas = {
       url: { type: "string" },
       path: { type: "string" },
       timeout: { type: "number", minimum: 0 },
+      circuit_breaker: { type: "object" },
     },
     additionalProperties: false,
   },
@@ -86,11 +87,12 @@ const schemas = {
   },
 
   "s3": {
-    required: ["access_key_id","secret_access_key","region"],
+    required: ["region"],
     properties: {
       access_key_id: { type: "string" },
       secret_access_key: { type: "string", writeOnly: true },
       session_token: { type: "string", writeOnly: true },
+      credentials_endpoint: { type: "string" },
       region: { type: "string" },
       endpoint: { type: "string" },
       force_path_style: { type: "boolean" },
@@ -99,6 +101,11 @@ const schemas = {
       key_param: { type: "string" },
       fallback_key: { type: "string" },
       timeout: { type: "number", minimum: 0 },
+      additional_signed_headers: { type: "array" },
+      unsigned_payload: { type: "boolean" },
+      retry: { type: "object" },
+      circuit_breaker: { type: "object" },
+      binary_response_encoding: { type: "string" },
     },
     additionalProperties: false,
   },
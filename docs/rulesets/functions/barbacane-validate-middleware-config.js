@@ -68,6 +68,7 @@ const schemas = {
       header_name: { type: "string" },
       query_param: { type: "string" },
       keys: { type: "array" },
+      audit_log: { type: "boolean" },
     },
     additionalProperties: false,
   },
@@ -78,6 +79,7 @@ const schemas = {
       realm: { type: "string" },
       strip_credentials: { type: "boolean" },
       credentials: { type: "array" },
+      audit_log: { type: "boolean" },
     },
     additionalProperties: false,
   },
@@ -169,13 +171,19 @@ const schemas = {
     required: [],
     properties: {
       issuer: { type: "string" },
-      audience: { type: "string" },
+      audience: { type: "undefined" },
       clock_skew_seconds: { type: "integer", minimum: 0 },
       skip_signature_validation: { type: "boolean" },
       jwks_url: { type: "string" },
+      jwks_refresh_seconds: { type: "integer", minimum: 0 },
       groups_claim: { type: "string" },
       public_key_pem: { type: "string" },
       public_key_jwk: { type: "object" },
+      hmac_secret: { type: "string" },
+      token_sources: { type: "array" },
+      optional: { type: "boolean" },
+      audit_log: { type: "boolean" },
+      claim_header_prefix: { type: "string" },
     },
     additionalProperties: false,
   },
@@ -187,8 +195,15 @@ const schemas = {
       client_id: { type: "string" },
       client_secret: { type: "string", writeOnly: true },
       required_scopes: { type: "string" },
+      scopes_case_insensitive: { type: "boolean" },
       audience: { type: "string" },
+      require_token_type: { type: "string" },
       timeout: { type: "number", minimum: 0 },
+      cache_ttl_seconds: { type: "integer", minimum: 0 },
+      token_sources: { type: "array" },
+      optional: { type: "boolean" },
+      audit_log: { type: "boolean" },
+      claim_header_prefix: { type: "string" },
     },
     additionalProperties: false,
   },
@@ -208,15 +223,23 @@ const schemas = {
     required: ["issuer_url"],
     properties: {
       issuer_url: { type: "string" },
-      audience: { type: "string" },
+      audience: { type: "undefined" },
       required_scopes: { type: "string" },
+      scopes_case_insensitive: { type: "boolean" },
       issuer_override: { type: "string" },
       clock_skew_seconds: { type: "integer", minimum: 0 },
+      exp_leeway_seconds: { type: "integer", minimum: 0 },
+      nbf_leeway_seconds: { type: "integer", minimum: 0 },
       jwks_refresh_seconds: { type: "integer", minimum: 10 },
       timeout: { type: "number", minimum: 0 },
       allow_query_token: { type: "boolean" },
+      token_sources: { type: "array" },
+      optional: { type: "boolean" },
+      audit_log: { type: "boolean" },
+      claim_header_prefix: { type: "string" },
       groups_claim: { type: "string" },
       groups_claim_separator: { type: "string" },
+      required_claims: { type: "object" },
     },
     additionalProperties: false,
   },
@@ -261,6 +284,9 @@ const schemas = {
     properties: {
       max_bytes: { type: "integer", minimum: 0 },
       check_content_length: { type: "boolean" },
+      max_headers: { type: "integer", minimum: 0 },
+      max_header_size: { type: "integer", minimum: 0 },
+      max_uri_length: { type: "integer", minimum: 0 },
     },
     additionalProperties: false,
   },
@@ -268,10 +294,15 @@ const schemas = {
   "request-transformer": {
     required: [],
     properties: {
+      when: { type: "object" },
       headers: { type: "object" },
       querystring: { type: "object" },
       path: { type: "object" },
       body: { type: "object" },
+      forwarded: { type: "object" },
+      body_format: { type: "string" },
+      max_body_bytes: { type: "integer", minimum: 0 },
+      json_patch: { type: "array" },
     },
     additionalProperties: false,
   },
@@ -280,8 +311,14 @@ const schemas = {
     required: [],
     properties: {
       status: { type: "object" },
+      status_range: { type: "string" },
       headers: { type: "object" },
       body: { type: "object" },
+      body_format: { type: "string" },
+      only_when_accept: { type: "array" },
+      max_body_bytes: { type: "integer", minimum: 0 },
+      set_body: { type: "undefined" },
+      json_patch: { type: "array" },
     },
     additionalProperties: false,
   },
@@ -54,6 +54,9 @@ fuzz_target!(|input: Input| {
             location: "query".to_string(),
             required: false,
             schema: None,
+            allow_reserved: false,
+            style: None,
+            explode: None,
         });
     }
 
@@ -3,6 +3,8 @@
 //! Validates API keys from headers or query parameters and rejects
 //! unauthenticated requests with 401 Unauthorized.
 
+use barbacane_plugin_sdk::jwt;
+use barbacane_plugin_sdk::log;
 use barbacane_plugin_sdk::prelude::*;
 use serde::Deserialize;
 use std::collections::BTreeMap;
@@ -29,6 +31,11 @@ pub struct ApiKeyAuth {
     /// List of valid API keys with their metadata.
     #[serde(default)]
     keys: Vec<ApiKeyEntry>,
+
+    /// Log every allow/deny decision as a JSON line (see
+    /// `barbacane_plugin_sdk::log::access_allowed`). Off by default.
+    #[serde(default)]
+    audit_log: bool,
 }
 
 /// A single API key entry.
@@ -86,9 +93,16 @@ impl ApiKeyError {
 
 impl ApiKeyAuth {
     /// Handle incoming request - validate API key.
-    pub fn on_request(&mut self, req: Request) -> Action<Request> {
+    pub fn on_request(&mut self, mut req: Request) -> Action<Request> {
+        // Only identity headers set below may reach the upstream.
+        jwt::strip_claim_headers(&mut req, None, &[self.header_name.as_str()]);
+
         match self.validate_request(&req) {
             Ok(key_entry) => {
+                if self.audit_log {
+                    log::access_allowed("apikey-auth", Some(key_entry.id.as_str()));
+                }
+
                 // Add auth context to request headers for downstream use
                 let mut modified_req = req;
                 modified_req
@@ -116,7 +130,12 @@ impl ApiKeyAuth {
 
                 Action::Continue(modified_req)
             }
-            Err(e) => Action::ShortCircuit(self.unauthorized_response(&e)),
+            Err(e) => {
+                if self.audit_log {
+                    log::access_denied("apikey-auth", e.as_str());
+                }
+                Action::ShortCircuit(self.unauthorized_response(&e))
+            }
         }
     }
 
@@ -436,6 +455,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_on_request_strips_forged_auth_headers() {
+        let mut plugin = test_plugin();
+        let req = request_with_headers(vec![
+            ("X-API-Key", "sk-noname"),
+            ("x-auth-key-name", "Forged"),
+            ("X-Auth-Consumer-Groups", "admin"),
+        ]);
+        match plugin.on_request(req) {
+            Action::Continue(r) => {
+                assert_eq!(r.headers.get("x-auth-consumer").unwrap(), "key3");
+                assert!(!r.headers.contains_key("x-auth-key-name"));
+                assert!(!r.headers.contains_key("X-Auth-Consumer-Groups"));
+            }
+            _ => panic!("expected Continue"),
+        }
+
+        // A key header under the x-auth- prefix is still read.
+        let mut plugin: ApiKeyAuth = serde_json::from_value(serde_json::json!({
+            "header_name": "X-Auth-Key",
+            "keys": [{ "key": "sk-test-123", "id": "key1" }]
+        }))
+        .unwrap();
+        let req = request_with_headers(vec![("x-auth-key", "sk-test-123")]);
+        assert!(matches!(plugin.on_request(req), Action::Continue(_)));
+    }
+
     #[test]
     fn test_on_request_missing_key_returns_401() {
         let mut plugin = test_plugin();
@@ -546,6 +592,33 @@ mod tests {
 
     // --- on_response passthrough ---
 
+    #[test]
+    fn test_audit_log_records_decisions() {
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        let mut plugin = test_plugin();
+        plugin.on_request(request_with_headers(vec![("X-API-Key", "sk-test-123")]));
+        assert!(host.logs().is_empty(), "audit_log is off by default");
+
+        plugin.audit_log = true;
+        plugin.on_request(request_with_headers(vec![("X-API-Key", "sk-test-123")]));
+        plugin.on_request(request_with_headers(vec![("X-API-Key", "wrong")]));
+        let lines: Vec<serde_json::Value> = host
+            .logs()
+            .iter()
+            .map(|(level, line)| {
+                assert_eq!(*level, 1);
+                serde_json::from_str(line).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                serde_json::json!({"plugin": "apikey-auth", "decision": "allow", "sub": "key1"}),
+                serde_json::json!({"plugin": "apikey-auth", "decision": "deny", "reason": "invalid_key", "sub": null}),
+            ]
+        );
+    }
+
     #[test]
     fn test_on_response_passthrough() {
         let mut plugin = test_plugin();
@@ -3,6 +3,9 @@
 //! Validates credentials from the `Authorization: Basic` header (RFC 7617)
 //! and rejects unauthenticated requests with 401 Unauthorized.
 
+use barbacane_plugin_sdk::crypto;
+use barbacane_plugin_sdk::jwt;
+use barbacane_plugin_sdk::log;
 use barbacane_plugin_sdk::prelude::*;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::Deserialize;
@@ -23,6 +26,11 @@ pub struct BasicAuth {
     /// List of credential entries (username, password, and optional roles).
     #[serde(default)]
     credentials: Vec<CredentialEntry>,
+
+    /// Log every allow/deny decision as a JSON line (see
+    /// `barbacane_plugin_sdk::log::access_allowed`). Off by default.
+    #[serde(default)]
+    audit_log: bool,
 }
 
 /// Credential entry for a single user.
@@ -32,7 +40,13 @@ struct CredentialEntry {
     username: String,
 
     /// Password for this user. Supports secret references (e.g. `env://MY_PASSWORD`).
-    password: String,
+    #[serde(default)]
+    password: Option<String>,
+
+    /// bcrypt or argon2 (PHC string) hash of the password, checked by the
+    /// host. Use instead of `password` to keep plaintext out of the spec.
+    #[serde(default)]
+    password_hash: Option<String>,
 
     /// Optional roles/permissions for this user.
     #[serde(default)]
@@ -83,9 +97,16 @@ impl BasicAuthError {
 
 impl BasicAuth {
     /// Handle incoming request - validate Basic credentials.
-    pub fn on_request(&mut self, req: Request) -> Action<Request> {
+    pub fn on_request(&mut self, mut req: Request) -> Action<Request> {
+        // Only identity headers set below may reach the upstream.
+        jwt::strip_claim_headers(&mut req, None, &[]);
+
         match self.validate_request(&req) {
             Ok((username, entry)) => {
+                if self.audit_log {
+                    log::access_allowed("basic-auth", Some(username.as_str()));
+                }
+
                 let mut modified_req = req;
 
                 // Strip Authorization header before forwarding
@@ -115,7 +136,12 @@ impl BasicAuth {
 
                 Action::Continue(modified_req)
             }
-            Err(e) => Action::ShortCircuit(self.unauthorized_response(&e)),
+            Err(e) => {
+                if self.audit_log {
+                    log::access_denied("basic-auth", e.as_str());
+                }
+                Action::ShortCircuit(self.unauthorized_response(&e))
+            }
         }
     }
 
@@ -134,11 +160,41 @@ impl BasicAuth {
         // early-returning, so timing reveals neither valid usernames (no user
         // enumeration) nor how many password bytes matched.
         let mut matched: Option<&CredentialEntry> = None;
+        let mut hashed: Option<(&CredentialEntry, &str)> = None;
         for e in &self.credentials {
             let user_ok = constant_time_eq(e.username.as_bytes(), username.as_bytes());
-            let pass_ok = constant_time_eq(e.password.as_bytes(), password.as_bytes());
-            if user_ok && pass_ok {
-                matched = Some(e);
+            if let Some(expected) = &e.password {
+                let pass_ok = constant_time_eq(expected.as_bytes(), password.as_bytes());
+                if user_ok && pass_ok {
+                    matched = Some(e);
+                }
+            } else if let Some(hash) = &e.password_hash {
+                if user_ok {
+                    hashed = Some((e, hash));
+                }
+            }
+        }
+        // Hashed entries are verified by the host, once, for the matching user.
+        // Without one, a dummy hash with the same algorithm and cost is
+        // verified instead, so a known hashed user costs no more time than an
+        // unknown one.
+        if matched.is_none() {
+            if let Some((e, hash)) = hashed {
+                match crypto::verify_password(hash, password.as_bytes()) {
+                    Some(true) => matched = Some(e),
+                    Some(false) => {}
+                    None => log::warn(&format!(
+                        "basic-auth: unsupported password_hash for user '{}'",
+                        e.username
+                    )),
+                }
+            } else if let Some(dummy) = self
+                .credentials
+                .iter()
+                .find_map(|e| e.password_hash.as_deref())
+                .and_then(dummy_hash)
+            {
+                let _ = crypto::verify_password(&dummy, password.as_bytes());
             }
         }
         let entry = matched.ok_or(BasicAuthError::InvalidCredentials)?;
@@ -208,22 +264,59 @@ impl BasicAuth {
     }
 }
 
+/// Fixed salt and digest for [`dummy_hash`]: bcrypt's 22 + 31 characters,
+/// and argon2's 16-byte salt and 32-byte digest (unpadded base64).
+const BCRYPT_DUMMY: &str = "dummysaltdummysaltdumOdummydigestdummydigestdummydige";
+const ARGON2_DUMMY_SALT: &str = "c29tZXNhbHRzb21lc2FsdA";
+const ARGON2_DUMMY_DIGEST: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+/// A well-formed hash with `hash`'s algorithm and cost parameters but a fixed
+/// salt and digest, verified when no hashed entry matches the username.
+/// `None` for hashes that are neither bcrypt nor argon2.
+fn dummy_hash(hash: &str) -> Option<String> {
+    if hash.starts_with("$argon2") {
+        // $argon2id$v=19$m=19456,t=2,p=1$<salt>$<digest>
+        let params = hash.rsplitn(3, '$').nth(2)?;
+        Some(format!(
+            "{}${}${}",
+            params, ARGON2_DUMMY_SALT, ARGON2_DUMMY_DIGEST
+        ))
+    } else if hash.starts_with("$2") {
+        // $2b$12$<salt><digest>
+        let prefix = hash.get(..7)?;
+        Some(format!("{}{}", prefix, BCRYPT_DUMMY))
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use base64::{engine::general_purpose::STANDARD, Engine};
 
+    /// bcrypt hash the tests' mock host accepts for `hunter2`.
+    const HASHED_PASSWORD: &str = "$2b$04$abcdefghijklmnopqrstuu5Vq1bYdtLmeGEOr7GPzqd8wUIVdD2fWe";
+
     /// Helper: build a BasicAuth instance with test credentials.
     fn test_plugin() -> BasicAuth {
         let credentials = vec![
             CredentialEntry {
                 username: "admin".to_string(),
-                password: "secret123".to_string(),
+                password: Some("secret123".to_string()),
+                password_hash: None,
                 roles: vec!["admin".to_string(), "editor".to_string()],
             },
+            CredentialEntry {
+                username: "hashed".to_string(),
+                password: None,
+                password_hash: Some(HASHED_PASSWORD.to_string()),
+                roles: vec!["ops".to_string()],
+            },
             CredentialEntry {
                 username: "reader".to_string(),
-                password: "readonly456".to_string(),
+                password: Some("readonly456".to_string()),
+                password_hash: None,
                 roles: vec![],
             },
         ];
@@ -231,6 +324,7 @@ mod tests {
             realm: "test-api".to_string(),
             strip_credentials: true,
             credentials,
+            audit_log: false,
         }
     }
 
@@ -454,6 +548,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn on_request_strips_forged_auth_headers() {
+        let mut plugin = test_plugin();
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "authorization".to_string(),
+            basic_header("reader", "readonly456"),
+        );
+        headers.insert("x-auth-roles".to_string(), "admin".to_string());
+        headers.insert("X-Auth-Consumer-Groups".to_string(), "admin".to_string());
+        let req = request_with_headers(headers);
+
+        match plugin.on_request(req) {
+            Action::Continue(modified) => {
+                assert_eq!(modified.headers.get("x-auth-user").unwrap(), "reader");
+                assert!(!modified.headers.contains_key("x-auth-roles"));
+                assert!(!modified.headers.contains_key("X-Auth-Consumer-Groups"));
+            }
+            Action::ShortCircuit(_) => panic!("expected Continue"),
+        }
+    }
+
     #[test]
     fn on_request_preserves_auth_header_when_strip_disabled() {
         let mut plugin = test_plugin();
@@ -491,6 +607,157 @@ mod tests {
         }
     }
 
+    #[test]
+    fn on_request_audit_log_records_decisions() {
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        let mut plugin = test_plugin();
+        plugin.audit_log = true;
+
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "authorization".to_string(),
+            basic_header("reader", "readonly456"),
+        );
+        plugin.on_request(request_with_headers(headers));
+        plugin.on_request(request_with_headers(BTreeMap::new()));
+
+        let lines: Vec<serde_json::Value> = host
+            .logs()
+            .iter()
+            .map(|(level, line)| {
+                assert_eq!(*level, 1);
+                serde_json::from_str(line).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                serde_json::json!({"plugin": "basic-auth", "decision": "allow", "sub": "reader"}),
+                serde_json::json!({"plugin": "basic-auth", "decision": "deny", "reason": "missing_credentials", "sub": null}),
+            ]
+        );
+    }
+
+    #[test]
+    fn on_request_hashed_password_verified_by_host() {
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        host.on_verify_password(HASHED_PASSWORD, "hunter2");
+        let mut plugin = test_plugin();
+
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "authorization".to_string(),
+            basic_header("hashed", "hunter2"),
+        );
+        match plugin.on_request(request_with_headers(headers)) {
+            Action::Continue(modified) => {
+                assert_eq!(modified.headers.get("x-auth-consumer").unwrap(), "hashed");
+                assert_eq!(modified.headers.get("x-auth-roles").unwrap(), "ops");
+            }
+            Action::ShortCircuit(_) => panic!("expected Continue"),
+        }
+    }
+
+    #[test]
+    fn on_request_hashed_wrong_password_returns_401() {
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        host.on_verify_password(HASHED_PASSWORD, "hunter2");
+        let mut plugin = test_plugin();
+
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "authorization".to_string(),
+            basic_header("hashed", "hunter3"),
+        );
+        match plugin.on_request(request_with_headers(headers)) {
+            Action::ShortCircuit(resp) => {
+                assert_eq!(resp.status, 401);
+                let www_auth = resp.headers.get("www-authenticate").unwrap();
+                assert!(www_auth.starts_with("Basic realm=\"test-api\""));
+                assert!(www_auth.contains("invalid_credentials"));
+            }
+            Action::Continue(_) => panic!("expected ShortCircuit"),
+        }
+    }
+
+    #[test]
+    fn unknown_and_hashed_users_both_run_a_password_check() {
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        host.on_verify_password(HASHED_PASSWORD, "hunter2");
+        let mut plugin = test_plugin();
+
+        for user in ["hashed", "nobody"] {
+            let mut headers = BTreeMap::new();
+            headers.insert("authorization".to_string(), basic_header(user, "wrong"));
+            assert!(matches!(
+                plugin.on_request(request_with_headers(headers)),
+                Action::ShortCircuit(ref resp) if resp.status == 401
+            ));
+        }
+
+        let checks = host.password_checks();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0], HASHED_PASSWORD);
+        // The unknown user is checked against a dummy with the same cost.
+        assert_ne!(checks[1], HASHED_PASSWORD);
+        assert_eq!(checks[1].len(), 60, "a well-formed bcrypt hash");
+        assert!(checks[1].starts_with("$2b$04$"));
+    }
+
+    #[test]
+    fn dummy_hash_keeps_algorithm_and_cost() {
+        assert_eq!(
+            dummy_hash("$argon2id$v=19$m=19456,t=2,p=1$c2FsdHNhbHQ$ZGlnZXN0").as_deref(),
+            Some(
+                "$argon2id$v=19$m=19456,t=2,p=1$c29tZXNhbHRzb21lc2FsdA$AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"
+            )
+        );
+        assert_eq!(
+            dummy_hash("$2y$12$whatever").as_deref(),
+            Some("$2y$12$dummysaltdummysaltdumOdummydigestdummydigestdummydige")
+        );
+        assert_eq!(dummy_hash("plaintext"), None);
+    }
+
+    #[test]
+    fn on_request_hashed_password_host_error_returns_401() {
+        // No registered hash: the mock host reports an unsupported hash.
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        let mut plugin = test_plugin();
+
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "authorization".to_string(),
+            basic_header("hashed", "hunter2"),
+        );
+        assert!(matches!(
+            plugin.on_request(request_with_headers(headers)),
+            Action::ShortCircuit(ref resp) if resp.status == 401
+        ));
+        assert!(host.logs()[0].1.contains("unsupported password_hash"));
+    }
+
+    #[test]
+    fn on_request_malformed_header_returns_401() {
+        let mut plugin = test_plugin();
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "authorization".to_string(),
+            format!("Basic {}", STANDARD.encode("no-colon-here")),
+        );
+        match plugin.on_request(request_with_headers(headers)) {
+            Action::ShortCircuit(resp) => {
+                assert_eq!(resp.status, 401);
+                assert!(resp
+                    .headers
+                    .get("www-authenticate")
+                    .unwrap()
+                    .contains("invalid_request"));
+            }
+            Action::Continue(_) => panic!("expected ShortCircuit"),
+        }
+    }
+
     // ==================== unauthorized_response ====================
 
     #[test]
@@ -530,16 +797,23 @@ mod tests {
             "strip_credentials": false,
             "credentials": [
                 { "username": "alice", "password": "p@ss", "roles": ["admin"] },
-                { "username": "bob", "password": "secret" }
+                { "username": "bob", "password": "secret" },
+                { "username": "carol", "password_hash": "$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA" }
             ]
         }"#;
         let config: BasicAuth = serde_json::from_str(json).unwrap();
         assert_eq!(config.realm, "myapp");
         assert!(!config.strip_credentials);
-        assert_eq!(config.credentials.len(), 2);
+        assert_eq!(config.credentials.len(), 3);
         assert_eq!(config.credentials[0].username, "alice");
         assert_eq!(config.credentials[0].roles, vec!["admin"]);
         assert_eq!(config.credentials[1].username, "bob");
         assert!(config.credentials[1].roles.is_empty());
+        assert!(config.credentials[2].password.is_none());
+        assert!(config.credentials[2]
+            .password_hash
+            .as_deref()
+            .unwrap()
+            .starts_with("$argon2id$"));
     }
 }
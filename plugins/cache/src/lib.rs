@@ -1,12 +1,12 @@
 //! Response caching middleware plugin for Barbacane API gateway.
 //!
 //! Caches responses based on TTL configuration and vary headers.
-//! Uses the host's response cache via host_cache_get/set.
+//! Uses the host's response cache through the SDK `cache` module.
 
+use barbacane_plugin_sdk::cache::{self, CacheEntry};
 use barbacane_plugin_sdk::log::log as log_message;
 use barbacane_plugin_sdk::prelude::*;
 use serde::Deserialize;
-use std::collections::BTreeMap;
 
 /// Cache middleware configuration.
 #[barbacane_middleware]
@@ -49,22 +49,6 @@ fn default_cacheable_status() -> Vec<u16> {
     vec![200, 301, 404]
 }
 
-/// Result from host_cache_get.
-#[derive(Debug, Deserialize)]
-struct CacheResult {
-    hit: bool,
-    entry: Option<CacheEntry>,
-}
-
-/// A cached response entry.
-#[derive(Debug, Deserialize, serde::Serialize)]
-struct CacheEntry {
-    status: u16,
-    headers: BTreeMap<String, String>,
-    #[serde(with = "barbacane_plugin_sdk::types::base64_body")]
-    body: Option<Vec<u8>>,
-}
-
 impl Cache {
     /// Handle incoming request - check cache for hit.
     pub fn on_request(&mut self, req: Request) -> Action<Request> {
@@ -86,24 +70,22 @@ impl Cache {
         self.is_cacheable = true;
 
         // Check cache
-        match self.check_cache(&cache_key) {
-            Some(result) if result.hit => {
+        match cache::get(&cache_key) {
+            Some(entry) => {
                 // Cache hit - return cached response
-                if let Some(entry) = result.entry {
-                    log_message(3, &format!("cache hit: {}", cache_key));
-
-                    // Build response with cache headers
-                    let mut headers = entry.headers;
-                    headers.insert("x-cache".to_string(), "HIT".to_string());
-
-                    return Action::ShortCircuit(Response {
-                        status: entry.status,
-                        headers,
-                        body: entry.body,
-                    });
-                }
+                log_message(3, &format!("cache hit: {}", cache_key));
+
+                // Build response with cache headers
+                let mut headers = entry.headers;
+                headers.insert("x-cache".to_string(), "HIT".to_string());
+
+                return Action::ShortCircuit(Response {
+                    status: entry.status,
+                    headers,
+                    body: entry.body,
+                });
             }
-            _ => {
+            None => {
                 // Cache miss - continue to dispatcher
                 log_message(3, &format!("cache miss: {}", cache_key));
             }
@@ -137,6 +119,11 @@ impl Cache {
             }
         }
 
+        // A shared cache must not replay one client's cookies to others
+        if resp.headers.contains_key("set-cookie") {
+            return resp;
+        }
+
         // Store in cache
         let entry = CacheEntry {
             status: resp.status,
@@ -144,7 +131,7 @@ impl Cache {
             body: resp.body.clone(),
         };
 
-        if self.store_in_cache(&cache_key, &entry) {
+        if cache::set(&cache_key, &entry, self.ttl) {
             log_message(3, &format!("cached response: {}", cache_key));
         }
 
@@ -176,158 +163,16 @@ impl Cache {
 
         key
     }
-
-    /// Check cache for a key.
-    fn check_cache(&self, key: &str) -> Option<CacheResult> {
-        let result_len = call_cache_get(key);
-        if result_len < 0 {
-            return None;
-        }
-
-        let mut buf = vec![0u8; result_len as usize];
-        let read_len = call_cache_read_result(&mut buf);
-        if read_len <= 0 {
-            return None;
-        }
-
-        serde_json::from_slice(&buf[..read_len as usize]).ok()
-    }
-
-    /// Store a response in the cache.
-    fn store_in_cache(&self, key: &str, entry: &CacheEntry) -> bool {
-        let entry_json = match serde_json::to_string(entry) {
-            Ok(j) => j,
-            Err(_) => return false,
-        };
-
-        call_cache_set(key, &entry_json, self.ttl) == 0
-    }
-}
-
-/// Call host_cache_get with a string key.
-#[cfg(target_arch = "wasm32")]
-fn call_cache_get(key: &str) -> i32 {
-    #[link(wasm_import_module = "barbacane")]
-    extern "C" {
-        fn host_cache_get(key_ptr: i32, key_len: i32) -> i32;
-    }
-    unsafe { host_cache_get(key.as_ptr() as i32, key.len() as i32) }
-}
-
-/// Call host_cache_set.
-#[cfg(target_arch = "wasm32")]
-fn call_cache_set(key: &str, entry_json: &str, ttl_secs: u32) -> i32 {
-    #[link(wasm_import_module = "barbacane")]
-    extern "C" {
-        fn host_cache_set(
-            key_ptr: i32,
-            key_len: i32,
-            entry_ptr: i32,
-            entry_len: i32,
-            ttl_secs: u32,
-        ) -> i32;
-    }
-    unsafe {
-        host_cache_set(
-            key.as_ptr() as i32,
-            key.len() as i32,
-            entry_json.as_ptr() as i32,
-            entry_json.len() as i32,
-            ttl_secs,
-        )
-    }
-}
-
-/// Read cache result into buffer.
-#[cfg(target_arch = "wasm32")]
-fn call_cache_read_result(buf: &mut [u8]) -> i32 {
-    #[link(wasm_import_module = "barbacane")]
-    extern "C" {
-        fn host_cache_read_result(buf_ptr: i32, buf_len: i32) -> i32;
-    }
-    unsafe { host_cache_read_result(buf.as_mut_ptr() as i32, buf.len() as i32) }
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-mod mock_host {
-    use std::cell::RefCell;
-    use std::collections::HashMap;
-
-    thread_local! {
-        static CACHE: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
-        static LAST_RESULT: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
-    }
-
-    pub fn cache_get(key: &str) -> i32 {
-        CACHE.with(|c| {
-            if let Some(entry) = c.borrow().get(key) {
-                let result = serde_json::json!({
-                    "hit": true,
-                    "entry": serde_json::from_str::<serde_json::Value>(entry).unwrap()
-                });
-                let bytes = serde_json::to_vec(&result).unwrap();
-                let len = bytes.len() as i32;
-                LAST_RESULT.with(|r| *r.borrow_mut() = Some(bytes));
-                len
-            } else {
-                let result = serde_json::json!({ "hit": false });
-                let bytes = serde_json::to_vec(&result).unwrap();
-                let len = bytes.len() as i32;
-                LAST_RESULT.with(|r| *r.borrow_mut() = Some(bytes));
-                len
-            }
-        })
-    }
-
-    pub fn cache_read_result(buf: &mut [u8]) -> i32 {
-        LAST_RESULT.with(|r| {
-            if let Some(data) = r.borrow().as_ref() {
-                let len = data.len().min(buf.len());
-                buf[..len].copy_from_slice(&data[..len]);
-                len as i32
-            } else {
-                -1
-            }
-        })
-    }
-
-    pub fn cache_set(key: &str, entry_json: &str, _ttl_secs: u32) -> i32 {
-        CACHE.with(|c| {
-            c.borrow_mut()
-                .insert(key.to_string(), entry_json.to_string())
-        });
-        0
-    }
-
-    #[cfg(test)]
-    pub fn reset() {
-        CACHE.with(|c| c.borrow_mut().clear());
-        LAST_RESULT.with(|r| *r.borrow_mut() = None);
-    }
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-fn call_cache_get(key: &str) -> i32 {
-    mock_host::cache_get(key)
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-fn call_cache_read_result(buf: &mut [u8]) -> i32 {
-    mock_host::cache_read_result(buf)
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-fn call_cache_set(key: &str, entry_json: &str, ttl_secs: u32) -> i32 {
-    mock_host::cache_set(key, entry_json, ttl_secs)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use barbacane_plugin_sdk::mock_host::MockHost;
+    use std::collections::BTreeMap;
 
-    fn setup() {
-        #[cfg(not(target_arch = "wasm32"))]
-        mock_host::reset();
+    fn setup() -> MockHost {
+        MockHost::install()
     }
 
     fn create_test_request(method: &str, path: &str) -> Request {
@@ -344,7 +189,7 @@ mod tests {
 
     #[test]
     fn test_build_cache_key_basic() {
-        setup();
+        let _host = setup();
         let cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -361,7 +206,7 @@ mod tests {
 
     #[test]
     fn test_build_cache_key_with_query() {
-        setup();
+        let _host = setup();
         let cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -379,7 +224,7 @@ mod tests {
 
     #[test]
     fn test_build_cache_key_with_vary_headers() {
-        setup();
+        let _host = setup();
         let cache = Cache {
             ttl: 300,
             vary: vec!["accept".to_string(), "accept-language".to_string()],
@@ -404,7 +249,7 @@ mod tests {
 
     #[test]
     fn test_config_defaults() {
-        setup();
+        let _host = setup();
         let json = r#"{}"#;
         let cache: Cache = serde_json::from_str(json).unwrap();
 
@@ -416,7 +261,7 @@ mod tests {
 
     #[test]
     fn test_on_request_non_cacheable_method() {
-        setup();
+        let _host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -443,7 +288,7 @@ mod tests {
 
     #[test]
     fn test_on_request_cacheable_miss() {
-        setup();
+        let _host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -470,7 +315,7 @@ mod tests {
 
     #[test]
     fn test_on_request_cache_hit() {
-        setup();
+        let host = setup();
 
         // Pre-populate cache
         let entry = CacheEntry {
@@ -482,8 +327,7 @@ mod tests {
             },
             body: Some(br#"{"users":[]}"#.to_vec()),
         };
-        let entry_json = serde_json::to_string(&entry).unwrap();
-        call_cache_set("GET:/api/users", &entry_json, 300);
+        host.on_cache("GET:/api/users", entry);
 
         let mut cache = Cache {
             ttl: 300,
@@ -513,7 +357,7 @@ mod tests {
 
     #[test]
     fn test_on_response_cacheable() {
-        setup();
+        let host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -538,14 +382,15 @@ mod tests {
         assert_eq!(result.status, 200);
         assert_eq!(result.headers.get("x-cache"), Some(&"MISS".to_string()));
 
-        // Verify it was stored in cache
-        let result_len = call_cache_get("GET:/api/users");
-        assert!(result_len > 0);
+        // Verify it was stored in cache with the configured TTL
+        let (entry, ttl) = &host.cache_entries()["GET:/api/users"];
+        assert_eq!(entry.body, Some(br#"{"users":[]}"#.to_vec()));
+        assert_eq!(*ttl, 300);
     }
 
     #[test]
     fn test_on_response_non_cacheable_status() {
-        setup();
+        let _host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -567,16 +412,12 @@ mod tests {
         assert!(!result.headers.contains_key("x-cache"));
 
         // Verify it was NOT stored in cache
-        let result_len = call_cache_get("GET:/api/users");
-        let mut buf = vec![0u8; result_len as usize];
-        call_cache_read_result(&mut buf);
-        let cache_result: CacheResult = serde_json::from_slice(&buf).unwrap();
-        assert!(!cache_result.hit);
+        assert!(cache::get("GET:/api/users").is_none());
     }
 
     #[test]
     fn test_on_response_cache_control_no_store() {
-        setup();
+        let _host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -602,16 +443,42 @@ mod tests {
         assert!(!result.headers.contains_key("x-cache"));
 
         // Verify it was NOT stored in cache
-        let result_len = call_cache_get("GET:/api/users");
-        let mut buf = vec![0u8; result_len as usize];
-        call_cache_read_result(&mut buf);
-        let cache_result: CacheResult = serde_json::from_slice(&buf).unwrap();
-        assert!(!cache_result.hit);
+        assert!(cache::get("GET:/api/users").is_none());
+    }
+
+    #[test]
+    fn test_on_response_set_cookie_not_stored() {
+        let _host = setup();
+        let mut cache = Cache {
+            ttl: 300,
+            vary: vec![],
+            methods: default_methods(),
+            cacheable_status: default_cacheable_status(),
+            current_cache_key: Some("GET:/api/session".to_string()),
+            is_cacheable: true,
+        };
+
+        let resp = Response {
+            status: 200,
+            headers: {
+                let mut h = BTreeMap::new();
+                h.insert("set-cookie".to_string(), "session=abc".to_string());
+                h
+            },
+            body: Some(br#"{"ok":true}"#.to_vec()),
+        };
+
+        let result = cache.on_response(resp);
+
+        assert_eq!(result.headers.get("set-cookie").unwrap(), "session=abc");
+        assert!(!result.headers.contains_key("x-cache"));
+
+        assert!(cache::get("GET:/api/session").is_none());
     }
 
     #[test]
     fn test_on_response_cache_control_private() {
-        setup();
+        let _host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -642,7 +509,7 @@ mod tests {
 
     #[test]
     fn test_on_response_not_cacheable_request() {
-        setup();
+        let _host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -664,16 +531,12 @@ mod tests {
         assert!(!result.headers.contains_key("x-cache"));
 
         // Verify it was NOT stored in cache
-        let result_len = call_cache_get("POST:/api/users");
-        let mut buf = vec![0u8; result_len as usize];
-        call_cache_read_result(&mut buf);
-        let cache_result: CacheResult = serde_json::from_slice(&buf).unwrap();
-        assert!(!cache_result.hit);
+        assert!(cache::get("POST:/api/users").is_none());
     }
 
     #[test]
     fn test_response_passthrough_behavior() {
-        setup();
+        let _host = setup();
         let mut cache = Cache {
             ttl: 300,
             vary: vec![],
@@ -709,8 +572,6 @@ mod tests {
 
     #[test]
     fn test_cacheable_status_codes() {
-        setup();
-
         let status_tests = vec![
             (200, true),
             (301, true),
@@ -721,7 +582,7 @@ mod tests {
         ];
 
         for (status, should_cache) in status_tests {
-            mock_host::reset();
+            let _host = setup();
 
             let mut cache = Cache {
                 ttl: 300,
@@ -656,15 +656,21 @@ mod tests {
 
     #[test]
     fn test_on_request_disallowed_origin() {
+        mock_host::reset();
         let mut cors = create_test_cors(vec!["https://example.com".to_string()]);
         let req = create_request("GET", Some("https://evil.com"));
 
         match cors.on_request(req) {
             Action::ShortCircuit(response) => {
                 assert_eq!(response.status, 403);
-                let body =
-                    String::from_utf8(response.body.expect("Response should have a body")).unwrap();
+                assert!(!response.headers.contains_key("access-control-allow-origin"));
+                let body = response.body_str().expect("Response should have a body");
                 assert!(body.contains("https://evil.com"));
+
+                // The rejected origin is never stashed, so the response phase
+                // doesn't grant it either.
+                let response = cors.on_response(response);
+                assert!(!response.headers.contains_key("access-control-allow-origin"));
             }
             Action::Continue(_) => panic!("Expected ShortCircuit, got Continue"),
         }
@@ -5,8 +5,10 @@
 //! - Path rewriting
 //! - Header forwarding
 //! - Configurable timeouts
+//! - A consecutive-failure circuit breaker
 
-use barbacane_plugin_sdk::http;
+use barbacane_plugin_sdk::clock;
+use barbacane_plugin_sdk::http::{self, is_hop_by_hop, CircuitBreaker};
 use barbacane_plugin_sdk::prelude::*;
 use serde::Deserialize;
 use std::collections::BTreeMap;
@@ -27,6 +29,11 @@ pub struct HttpUpstreamDispatcher {
     /// Request timeout in seconds (default: 30).
     #[serde(default = "default_timeout")]
     timeout: f64,
+
+    /// Fast-fail with 503 after `failure_threshold` consecutive connection
+    /// failures or 5xx responses, for `open_seconds`. Default: disabled.
+    #[serde(default)]
+    circuit_breaker: CircuitBreaker,
 }
 
 fn default_timeout() -> f64 {
@@ -46,34 +53,14 @@ impl HttpUpstreamDispatcher {
             path_params,
         } = req;
 
-        // Build the upstream path
-        let upstream_path = match &self.path {
-            Some(template) => self.substitute_path_params(template, &path_params),
-            None => path,
-        };
-
-        // Construct the full URL with query string
-        let base_url = if self.url.ends_with('/') || upstream_path.starts_with('/') {
-            format!("{}{}", self.url.trim_end_matches('/'), upstream_path)
-        } else {
-            format!("{}{}", self.url, upstream_path)
-        };
-
-        let full_url = match &query {
-            Some(qs) if !qs.is_empty() => format!("{}?{}", base_url, qs),
-            _ => base_url,
-        };
+        let full_url = self.upstream_url(path, query.as_deref(), &path_params);
 
         // Build headers to send to upstream
         let mut headers: BTreeMap<String, String> = BTreeMap::new();
 
         // Forward incoming headers (filter hop-by-hop headers)
         for (key, value) in &req_headers {
-            let key_lower = key.to_lowercase();
-            if !matches!(
-                key_lower.as_str(),
-                "connection" | "keep-alive" | "transfer-encoding" | "te" | "trailer" | "upgrade"
-            ) {
+            if !is_hop_by_hop(key) {
                 headers.insert(key.clone(), value.clone());
             }
         }
@@ -100,7 +87,19 @@ impl HttpUpstreamDispatcher {
             timeout_ms: Some(timeout_ms),
         };
 
-        let http_response = match http::call(&http_request, body.as_deref()) {
+        let now = clock::now();
+        if !self.circuit_breaker.allow(now) {
+            return self.error_response(
+                503,
+                "Service Unavailable",
+                "upstream circuit open",
+                "too many consecutive upstream failures",
+            );
+        }
+        let result = http::call(&http_request, body.as_deref());
+        self.circuit_breaker.record(&result, now);
+
+        let http_response = match result {
             Ok(resp) => resp,
             Err(http::HttpError::Unreachable) | Err(http::HttpError::Unsupported) => {
                 return self.error_response(
@@ -129,16 +128,11 @@ impl HttpUpstreamDispatcher {
         };
 
         // Build the response, filtering hop-by-hop headers
-        let mut response_headers: BTreeMap<String, String> = BTreeMap::new();
-        for (key, value) in http_response.headers {
-            let key_lower = key.to_lowercase();
-            if !matches!(
-                key_lower.as_str(),
-                "connection" | "keep-alive" | "transfer-encoding" | "te" | "trailer" | "upgrade"
-            ) {
-                response_headers.insert(key, value);
-            }
-        }
+        let response_headers: BTreeMap<String, String> = http_response
+            .headers
+            .into_iter()
+            .filter(|(key, _)| !is_hop_by_hop(key))
+            .collect();
 
         Response {
             status: http_response.status,
@@ -147,6 +141,32 @@ impl HttpUpstreamDispatcher {
         }
     }
 
+    /// Full upstream URL: `url` followed by the `path` template with
+    /// parameters substituted (replacing the request path) or, without a
+    /// template, the original request path appended; then the query string.
+    fn upstream_url(
+        &self,
+        request_path: String,
+        query: Option<&str>,
+        path_params: &BTreeMap<String, String>,
+    ) -> String {
+        let upstream_path = match &self.path {
+            Some(template) => self.substitute_path_params(template, path_params),
+            None => request_path,
+        };
+
+        let base_url = if self.url.ends_with('/') || upstream_path.starts_with('/') {
+            format!("{}{}", self.url.trim_end_matches('/'), upstream_path)
+        } else {
+            format!("{}{}", self.url, upstream_path)
+        };
+
+        match query {
+            Some(qs) if !qs.is_empty() => format!("{}?{}", base_url, qs),
+            _ => base_url,
+        }
+    }
+
     /// Substitute path parameters in the template.
     fn substitute_path_params(&self, template: &str, params: &BTreeMap<String, String>) -> String {
         let mut result = template.to_string();
@@ -202,6 +222,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let mut params = BTreeMap::new();
@@ -217,6 +238,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let mut params = BTreeMap::new();
@@ -235,6 +257,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let mut params = BTreeMap::new();
@@ -250,6 +273,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let params = BTreeMap::new();
@@ -258,12 +282,135 @@ mod tests {
         assert_eq!(result, "/users");
     }
 
+    #[test]
+    fn test_upstream_url_appends_request_path() {
+        let dispatcher = HttpUpstreamDispatcher {
+            url: "https://api.example.com/".to_string(),
+            path: None,
+            timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
+        };
+
+        let url =
+            dispatcher.upstream_url("/users/123".to_string(), Some("page=2"), &BTreeMap::new());
+        assert_eq!(url, "https://api.example.com/users/123?page=2");
+    }
+
+    #[test]
+    fn test_upstream_url_replaces_path_with_template() {
+        let dispatcher = HttpUpstreamDispatcher {
+            url: "https://api.example.com".to_string(),
+            path: Some("/v2/accounts/{id}".to_string()),
+            timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
+        };
+
+        let mut params = BTreeMap::new();
+        params.insert("id".to_string(), "123".to_string());
+
+        let url = dispatcher.upstream_url("/users/123".to_string(), Some(""), &params);
+        assert_eq!(url, "https://api.example.com/v2/accounts/123");
+    }
+
+    #[test]
+    fn test_dispatch_filters_hop_by_hop_headers() {
+        use barbacane_plugin_sdk::mock_host::MockHost;
+
+        let host = MockHost::install();
+        let mut upstream_headers = BTreeMap::new();
+        upstream_headers.insert("Connection".to_string(), "close".to_string());
+        upstream_headers.insert("transfer-encoding".to_string(), "chunked".to_string());
+        upstream_headers.insert("content-type".to_string(), "text/plain".to_string());
+        host.on_http(
+            "GET",
+            "http://example.com/items",
+            http::HttpResponse {
+                status: 200,
+                headers: upstream_headers,
+                body: Some(b"ok".to_vec()),
+            },
+        );
+
+        let mut dispatcher = HttpUpstreamDispatcher {
+            url: "http://example.com".to_string(),
+            path: None,
+            timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
+        };
+        let mut headers = BTreeMap::new();
+        headers.insert("Keep-Alive".to_string(), "timeout=5".to_string());
+        headers.insert("x-trace".to_string(), "abc".to_string());
+        let req = make_request("GET", "/items", headers, None, None, BTreeMap::new());
+
+        let response = dispatcher.dispatch(req);
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.headers.keys().collect::<Vec<_>>(),
+            vec!["content-type"]
+        );
+
+        let sent = &host.http_calls()[0].request.headers;
+        assert!(!sent.contains_key("Keep-Alive"));
+        assert_eq!(sent.get("x-trace").map(String::as_str), Some("abc"));
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_and_closes_after_probe() {
+        use barbacane_plugin_sdk::mock_host::MockHost;
+
+        let host = MockHost::install();
+        host.set_time(1_000);
+        let mut dispatcher = HttpUpstreamDispatcher {
+            url: "http://example.com".to_string(),
+            path: None,
+            timeout: 30.0,
+            circuit_breaker: CircuitBreaker::new(2, 10),
+        };
+        let get = |dispatcher: &mut HttpUpstreamDispatcher| {
+            let req = make_request(
+                "GET",
+                "/items",
+                BTreeMap::new(),
+                None,
+                None,
+                BTreeMap::new(),
+            );
+            dispatcher.dispatch(req)
+        };
+
+        // Unscripted upstream is unreachable: two failures open the circuit.
+        assert_eq!(get(&mut dispatcher).status, 502);
+        assert_eq!(get(&mut dispatcher).status, 502);
+        let response = get(&mut dispatcher);
+        assert_eq!(response.status, 503);
+        let body: serde_json::Value =
+            serde_json::from_slice(response.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["type"], "urn:barbacane:error:circuit-open");
+        assert_eq!(host.http_calls().len(), 2);
+
+        // Once open_seconds pass, a successful probe closes the circuit.
+        host.on_http(
+            "GET",
+            "http://example.com/items",
+            http::HttpResponse {
+                status: 200,
+                headers: BTreeMap::new(),
+                body: None,
+            },
+        )
+        .set_time(1_010);
+        assert_eq!(get(&mut dispatcher).status, 200);
+        assert_eq!(get(&mut dispatcher).status, 200);
+        assert_eq!(host.http_calls().len(), 4);
+    }
+
     #[test]
     fn test_error_response_502() {
         let dispatcher = HttpUpstreamDispatcher {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let response =
@@ -288,6 +435,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let response = dispatcher.error_response(
@@ -309,6 +457,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let response =
@@ -326,6 +475,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let response = dispatcher.error_response(
@@ -378,6 +528,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let req = make_request("GET", "/test", BTreeMap::new(), None, None, BTreeMap::new());
@@ -391,6 +542,7 @@ mod tests {
             url: "http://example.com".to_string(),
             path: None,
             timeout: 30.0,
+            circuit_breaker: CircuitBreaker::default(),
         };
 
         let body = br#"{"name":"test"}"#.to_vec();
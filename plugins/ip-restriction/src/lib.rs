@@ -3,6 +3,7 @@
 //! Allows or denies requests based on client IP address or CIDR ranges.
 //! Supports both allowlist and denylist modes.
 
+use barbacane_plugin_sdk::net;
 use barbacane_plugin_sdk::prelude::*;
 use serde::Deserialize;
 use std::net::IpAddr;
@@ -84,7 +85,7 @@ impl IpRestriction {
     /// Check if an IP matches any entry in a list (single IPs and CIDR ranges,
     /// IPv4 and IPv6).
     fn is_ip_in_list(&self, client_ip: IpAddr, list: &[String]) -> bool {
-        list.iter().any(|entry| ip_matches(client_ip, entry))
+        list.iter().any(|entry| net::addr_in_cidr(client_ip, entry))
     }
 
     /// Generate 403 Forbidden response.
@@ -100,60 +101,6 @@ impl IpRestriction {
     }
 }
 
-/// Whether `client` matches a list entry: either a single IP (`10.0.0.5`,
-/// `2001:db8::1`) or a CIDR range (`10.0.0.0/8`, `2001:db8::/32`). IPv4 and IPv6
-/// are both supported; a family mismatch never matches.
-fn ip_matches(client: IpAddr, entry: &str) -> bool {
-    match entry.split_once('/') {
-        Some((network, prefix)) => match (
-            network.trim().parse::<IpAddr>(),
-            prefix.trim().parse::<u8>(),
-        ) {
-            (Ok(network), Ok(prefix)) => ip_in_cidr(client, network, prefix),
-            _ => false,
-        },
-        None => entry
-            .trim()
-            .parse::<IpAddr>()
-            .map(|e| e == client)
-            .unwrap_or(false),
-    }
-}
-
-/// Check if an IP address is within a CIDR range (IPv4 or IPv6).
-fn ip_in_cidr(ip: IpAddr, network: IpAddr, prefix_len: u8) -> bool {
-    match (ip, network) {
-        (IpAddr::V4(ip), IpAddr::V4(net)) => {
-            bits_match(&ip.octets(), &net.octets(), prefix_len, 32)
-        }
-        (IpAddr::V6(ip), IpAddr::V6(net)) => {
-            bits_match(&ip.octets(), &net.octets(), prefix_len, 128)
-        }
-        // Mismatched families (e.g. IPv4 client vs IPv6 range) never match.
-        _ => false,
-    }
-}
-
-/// Compare the first `prefix_len` bits of two address byte arrays.
-fn bits_match(a: &[u8], b: &[u8], prefix_len: u8, max_bits: u8) -> bool {
-    if prefix_len > max_bits {
-        return false;
-    }
-    let mut remaining = prefix_len as usize;
-    for (x, y) in a.iter().zip(b.iter()) {
-        if remaining == 0 {
-            break;
-        }
-        let take = remaining.min(8);
-        let mask: u8 = if take == 8 { 0xFF } else { !0u8 << (8 - take) };
-        if (x & mask) != (y & mask) {
-            return false;
-        }
-        remaining -= take;
-    }
-    true
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,45 +126,6 @@ mod tests {
         }
     }
 
-    fn ip(s: &str) -> IpAddr {
-        s.parse().unwrap()
-    }
-
-    #[test]
-    fn matches_exact_ipv4_and_ipv6() {
-        assert!(ip_matches(ip("192.168.1.100"), "192.168.1.100"));
-        assert!(!ip_matches(ip("192.168.1.101"), "192.168.1.100"));
-        assert!(ip_matches(ip("2001:db8::1"), "2001:db8::1"));
-        assert!(!ip_matches(ip("2001:db8::2"), "2001:db8::1"));
-    }
-
-    #[test]
-    fn matches_cidr_ipv4_and_ipv6() {
-        assert!(ip_matches(ip("10.1.2.3"), "10.0.0.0/8"));
-        assert!(!ip_matches(ip("11.0.0.1"), "10.0.0.0/8"));
-        assert!(ip_matches(ip("192.168.1.50"), "192.168.1.0/24"));
-        assert!(!ip_matches(ip("192.168.2.50"), "192.168.1.0/24"));
-        assert!(ip_matches(ip("2001:db8::abcd"), "2001:db8::/32"));
-        assert!(!ip_matches(ip("2001:db9::1"), "2001:db8::/32"));
-    }
-
-    #[test]
-    fn cidr_prefix_zero_matches_all() {
-        assert!(ip_matches(ip("1.2.3.4"), "0.0.0.0/0"));
-    }
-
-    #[test]
-    fn family_mismatch_never_matches() {
-        assert!(!ip_matches(ip("10.0.0.1"), "2001:db8::/32"));
-        assert!(!ip_matches(ip("2001:db8::1"), "10.0.0.0/8"));
-    }
-
-    #[test]
-    fn invalid_entry_never_matches() {
-        assert!(!ip_matches(ip("10.0.0.1"), "not-an-ip"));
-        assert!(!ip_matches(ip("10.0.0.1"), "10.0.0.0/99"));
-    }
-
     #[test]
     fn allowed_ip_continues() {
         let mut p = test_plugin();
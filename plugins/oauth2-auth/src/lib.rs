@@ -3,11 +3,21 @@
 //! Validates Bearer tokens via RFC 7662 token introspection and rejects
 //! unauthenticated requests with 401 Unauthorized or 403 Forbidden.
 
+use barbacane_plugin_sdk::clock::{BoxedClock, Clock};
 use barbacane_plugin_sdk::http::{call, HttpError, HttpRequest};
+use barbacane_plugin_sdk::jwt::{self, TokenSource, TokenSourceError};
+use barbacane_plugin_sdk::log;
 use barbacane_plugin_sdk::prelude::*;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 
+/// Upper bound on cached introspection results per plugin instance.
+const MAX_CACHE_ENTRIES: usize = 1024;
+
+/// Longest time an inactive token's result is cached.
+const NEGATIVE_CACHE_TTL_SECONDS: u64 = 10;
+
 /// OAuth2 token introspection middleware configuration.
 #[barbacane_middleware]
 #[derive(Deserialize)]
@@ -25,15 +35,65 @@ pub struct OAuth2Auth {
     #[serde(default)]
     required_scopes: Option<String>,
 
+    /// Compare scopes case-insensitively (for providers that emit scopes
+    /// with different casing than configured).
+    #[serde(default)]
+    scopes_case_insensitive: bool,
+
     /// Expected audience (`aud`). If set, the introspection response's `aud` must
     /// contain this value; otherwise tokens minted for any audience at this
     /// authorization server are accepted (confused-deputy risk on shared IdPs).
     #[serde(default)]
     audience: Option<String>,
 
+    /// Accepted `token_type` (e.g. "Bearer"), compared case-insensitively.
+    /// If set, active tokens of another type, or without a type, are rejected.
+    #[serde(default)]
+    require_token_type: Option<String>,
+
     /// Request timeout in seconds for introspection call.
     #[serde(default = "default_timeout")]
     timeout: f64,
+
+    /// Where to look for the token, tried in order. Empty (the default)
+    /// means `Authorization: Bearer <token>`.
+    #[serde(default)]
+    token_sources: Vec<TokenSource>,
+
+    /// Let requests without a token through, marked `x-auth-anonymous: true`,
+    /// instead of rejecting them. A token that is present must still be valid.
+    #[serde(default)]
+    optional: bool,
+
+    /// Log every allow/deny decision as a JSON line (see
+    /// `barbacane_plugin_sdk::log::access_allowed`). Off by default.
+    #[serde(default)]
+    audit_log: bool,
+
+    /// Prefix of the injected identity headers (`<prefix>sub`,
+    /// `<prefix>consumer`, ...), e.g. `x-consumer-`. Default: `x-auth-`.
+    #[serde(default)]
+    claim_header_prefix: Option<String>,
+
+    /// Cache introspection results for up to this many seconds, capped by the
+    /// token's `exp`. 0 (default) disables caching, so every request is
+    /// introspected and revocation takes effect immediately.
+    #[serde(default)]
+    cache_ttl_seconds: u64,
+
+    /// Introspection results keyed by SHA-256 of the token.
+    #[serde(skip)]
+    cache: BTreeMap<[u8; 32], CachedIntrospection>,
+
+    /// Time source for cache expiry.
+    #[serde(skip)]
+    clock: BoxedClock,
+}
+
+/// A cached introspection result and the Unix time it stops being used.
+struct CachedIntrospection {
+    response: IntrospectionResponse,
+    expires_at: u64,
 }
 
 fn default_timeout() -> f64 {
@@ -59,7 +119,7 @@ fn warn_once_no_audience() {
 fn warn_once_no_audience() {}
 
 /// RFC 7662 Token Introspection Response.
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 struct IntrospectionResponse {
     /// REQUIRED. Whether the token is active.
     active: bool,
@@ -118,6 +178,7 @@ enum OAuth2Error {
     TokenInactive,
     InsufficientScope,
     InvalidAudience,
+    WrongTokenType,
 }
 
 impl OAuth2Error {
@@ -129,6 +190,7 @@ impl OAuth2Error {
             OAuth2Error::TokenInactive => "invalid_token",
             OAuth2Error::InsufficientScope => "insufficient_scope",
             OAuth2Error::InvalidAudience => "invalid_token",
+            OAuth2Error::WrongTokenType => "invalid_token",
         }
     }
 
@@ -140,6 +202,7 @@ impl OAuth2Error {
             OAuth2Error::TokenInactive => "Token is not active".to_string(),
             OAuth2Error::InsufficientScope => "Token does not have required scopes".to_string(),
             OAuth2Error::InvalidAudience => "Token audience mismatch".to_string(),
+            OAuth2Error::WrongTokenType => "Token type not accepted".to_string(),
         }
     }
 
@@ -153,41 +216,60 @@ impl OAuth2Error {
 
 impl OAuth2Auth {
     /// Handle incoming request - validate OAuth2 token via introspection.
-    pub fn on_request(&mut self, req: Request) -> Action<Request> {
+    pub fn on_request(&mut self, mut req: Request) -> Action<Request> {
+        // Only identity headers set below may reach the upstream. Headers
+        // that are configured token sources stay readable.
+        let keep: Vec<&str> = self
+            .token_sources
+            .iter()
+            .filter_map(TokenSource::header_name)
+            .collect();
+        jwt::strip_claim_headers(&mut req, self.claim_header_prefix.as_deref(), &keep);
+
         match self.validate_request(&req) {
             Ok(introspection) => {
+                if self.audit_log {
+                    log::access_allowed(
+                        "oauth2-auth",
+                        introspection
+                            .sub
+                            .as_deref()
+                            .or(introspection.username.as_deref()),
+                    );
+                }
+
                 // Add auth context to request headers for downstream use
                 let mut modified_req = req;
 
                 if let Some(sub) = &introspection.sub {
                     modified_req
                         .headers
-                        .insert("x-auth-sub".to_string(), sub.clone());
+                        .insert(self.claim_header("sub"), sub.clone());
                 }
 
                 if let Some(scope) = &introspection.scope {
                     modified_req
                         .headers
-                        .insert("x-auth-scope".to_string(), scope.clone());
+                        .insert(self.claim_header("scope"), scope.clone());
                     // Convert space-separated scopes to comma-separated groups
                     let groups = scope.split_whitespace().collect::<Vec<_>>().join(",");
                     if !groups.is_empty() {
                         modified_req
                             .headers
-                            .insert("x-auth-consumer-groups".to_string(), groups);
+                            .insert(self.claim_header("consumer-groups"), groups);
                     }
                 }
 
                 if let Some(client_id) = &introspection.client_id {
                     modified_req
                         .headers
-                        .insert("x-auth-client-id".to_string(), client_id.clone());
+                        .insert(self.claim_header("client-id"), client_id.clone());
                 }
 
                 if let Some(username) = &introspection.username {
                     modified_req
                         .headers
-                        .insert("x-auth-username".to_string(), username.clone());
+                        .insert(self.claim_header("username"), username.clone());
                 }
 
                 // Standard consumer header: sub takes precedence, then username
@@ -198,19 +280,35 @@ impl OAuth2Auth {
                 if let Some(consumer_id) = consumer {
                     modified_req
                         .headers
-                        .insert("x-auth-consumer".to_string(), consumer_id.clone());
+                        .insert(self.claim_header("consumer"), consumer_id.clone());
                 }
 
                 // Serialize full introspection response for downstream
                 if let Ok(claims_json) = serde_json::to_string(&introspection) {
                     modified_req
                         .headers
-                        .insert("x-auth-claims".to_string(), claims_json);
+                        .insert(self.claim_header("claims"), claims_json);
                 }
 
                 Action::Continue(modified_req)
             }
-            Err(e) => Action::ShortCircuit(self.error_response(&e)),
+            Err(OAuth2Error::MissingToken) if self.optional => {
+                let mut anonymous_req = req;
+                if self.audit_log {
+                    log::access_allowed("oauth2-auth", None);
+                }
+                jwt::mark_anonymous_with_prefix(
+                    &mut anonymous_req,
+                    self.claim_header_prefix.as_deref(),
+                );
+                Action::Continue(anonymous_req)
+            }
+            Err(e) => {
+                if self.audit_log {
+                    log::access_denied("oauth2-auth", e.as_str());
+                }
+                Action::ShortCircuit(self.error_response(&e))
+            }
         }
     }
 
@@ -219,19 +317,36 @@ impl OAuth2Auth {
         resp
     }
 
+    /// Name of the injected identity header `name` under `claim_header_prefix`.
+    fn claim_header(&self, name: &str) -> String {
+        jwt::claim_header(self.claim_header_prefix.as_deref(), name)
+    }
+
     /// Validate the OAuth2 token via introspection.
-    fn validate_request(&self, req: &Request) -> Result<IntrospectionResponse, OAuth2Error> {
+    fn validate_request(&mut self, req: &Request) -> Result<IntrospectionResponse, OAuth2Error> {
         // Extract Bearer token
         let token = self.extract_token(req)?;
 
-        // Call introspection endpoint
-        let introspection = self.introspect_token(&token)?;
+        // Call introspection endpoint, unless a cached result is still fresh
+        let introspection = self.cached_introspect(&token)?;
 
         // Check if token is active
         if !introspection.active {
             return Err(OAuth2Error::TokenInactive);
         }
 
+        // Check token type (e.g. reject refresh tokens where only access
+        // tokens are expected)
+        if let Some(required) = &self.require_token_type {
+            let matches = introspection
+                .token_type
+                .as_deref()
+                .is_some_and(|t| t.eq_ignore_ascii_case(required));
+            if !matches {
+                return Err(OAuth2Error::WrongTokenType);
+            }
+        }
+
         // Check required scopes
         if let Some(required) = &self.required_scopes {
             self.check_scopes(&introspection, required)?;
@@ -266,17 +381,75 @@ impl OAuth2Auth {
         }
     }
 
-    /// Extract Bearer token from Authorization header.
+    /// Extract the token from the first matching `token_sources` entry.
     fn extract_token(&self, req: &Request) -> Result<String, OAuth2Error> {
-        let auth_header = req
-            .headers
-            .get("authorization")
-            .or_else(|| req.headers.get("Authorization"))
-            .ok_or(OAuth2Error::MissingToken)?;
+        jwt::extract_token(req, &self.token_sources).map_err(|e| match e {
+            TokenSourceError::Missing => OAuth2Error::MissingToken,
+            TokenSourceError::Malformed => OAuth2Error::InvalidAuthHeader,
+        })
+    }
+
+    /// Introspect `token`, serving and recording results in the cache when
+    /// `cache_ttl_seconds` is set. Failed introspection calls are never cached.
+    fn cached_introspect(&mut self, token: &str) -> Result<IntrospectionResponse, OAuth2Error> {
+        if self.cache_ttl_seconds == 0 {
+            return self.introspect_token(token);
+        }
+
+        let key: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        let now = self.clock.now();
+        if let Some(entry) = self.cache.get(&key) {
+            if now < entry.expires_at {
+                return Ok(entry.response.clone());
+            }
+            self.cache.remove(&key);
+        }
 
-        barbacane_plugin_sdk::jwt::bearer_token(auth_header)
-            .map(|token| token.to_string())
-            .ok_or(OAuth2Error::InvalidAuthHeader)
+        let introspection = self.introspect_token(token)?;
+        let expires_at = if introspection.active {
+            let ttl_expiry = now.saturating_add(self.cache_ttl_seconds);
+            introspection
+                .exp
+                .map_or(ttl_expiry, |exp| exp.min(ttl_expiry))
+        } else {
+            now.saturating_add(self.cache_ttl_seconds.min(NEGATIVE_CACHE_TTL_SECONDS))
+        };
+        if expires_at > now {
+            self.insert_cached(key, introspection.clone(), expires_at, now);
+        }
+
+        Ok(introspection)
+    }
+
+    /// Insert a cache entry, dropping expired entries and then the entry
+    /// closest to expiry when the cache is full.
+    fn insert_cached(
+        &mut self,
+        key: [u8; 32],
+        response: IntrospectionResponse,
+        expires_at: u64,
+        now: u64,
+    ) {
+        if self.cache.len() >= MAX_CACHE_ENTRIES {
+            self.cache.retain(|_, entry| entry.expires_at > now);
+        }
+        if self.cache.len() >= MAX_CACHE_ENTRIES {
+            if let Some(oldest) = self
+                .cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(k, _)| *k)
+            {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.cache.insert(
+            key,
+            CachedIntrospection {
+                response,
+                expires_at,
+            },
+        );
     }
 
     /// Call the introspection endpoint to validate the token.
@@ -331,15 +504,17 @@ impl OAuth2Auth {
         introspection: &IntrospectionResponse,
         required: &str,
     ) -> Result<(), OAuth2Error> {
-        let token_scopes: Vec<&str> = introspection
+        let token_scopes: Vec<String> = introspection
             .scope
-            .as_ref()
-            .map(|s| s.split_whitespace().collect())
+            .as_deref()
+            .map(|s| {
+                s.split_whitespace()
+                    .map(|s| self.normalize_scope(s))
+                    .collect()
+            })
             .unwrap_or_default();
 
-        let required_scopes: Vec<&str> = required.split_whitespace().collect();
-
-        for scope in required_scopes {
+        for scope in required.split_whitespace().map(|s| self.normalize_scope(s)) {
             if !token_scopes.contains(&scope) {
                 return Err(OAuth2Error::InsufficientScope);
             }
@@ -348,6 +523,15 @@ impl OAuth2Auth {
         Ok(())
     }
 
+    /// Lowercase a scope when `scopes_case_insensitive` is set.
+    fn normalize_scope(&self, scope: &str) -> String {
+        if self.scopes_case_insensitive {
+            scope.to_lowercase()
+        } else {
+            scope.to_string()
+        }
+    }
+
     /// Generate error response.
     fn error_response(&self, error: &OAuth2Error) -> Response {
         let status = error.status_code();
@@ -430,6 +614,7 @@ fn url_encode(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use barbacane_plugin_sdk::mock_host::MockHost;
 
     fn create_test_config() -> OAuth2Auth {
         OAuth2Auth {
@@ -437,8 +622,17 @@ mod tests {
             client_id: "test_client".to_string(),
             client_secret: "test_secret".to_string(),
             required_scopes: None,
+            scopes_case_insensitive: false,
             audience: None,
+            require_token_type: None,
             timeout: 5.0,
+            token_sources: Vec::new(),
+            optional: false,
+            audit_log: false,
+            claim_header_prefix: None,
+            cache_ttl_seconds: 0,
+            cache: BTreeMap::new(),
+            clock: BoxedClock::default(),
         }
     }
 
@@ -484,6 +678,86 @@ mod tests {
         assert!(config.check_audience(&introspection(true, None)).is_err());
     }
 
+    #[test]
+    fn required_token_type_is_enforced() {
+        let host = MockHost::install();
+        let mut config = OAuth2Auth {
+            require_token_type: Some("Bearer".to_string()),
+            ..create_test_config()
+        };
+        let introspected = |body: serde_json::Value| {
+            Ok(barbacane_plugin_sdk::http::HttpResponse {
+                status: 200,
+                headers: BTreeMap::new(),
+                body: Some(body.to_string().into_bytes()),
+            })
+        };
+        host.on_http_once(
+            "POST",
+            ENDPOINT,
+            introspected(serde_json::json!({"active": true, "token_type": "bearer"})),
+        )
+        .on_http_once(
+            "POST",
+            ENDPOINT,
+            introspected(serde_json::json!({"active": true, "token_type": "refresh_token"})),
+        )
+        .on_http_once(
+            "POST",
+            ENDPOINT,
+            introspected(serde_json::json!({"active": true})),
+        );
+
+        let req = create_request_with_auth("Bearer t");
+        assert!(config.validate_request(&req).is_ok());
+        assert!(matches!(
+            config.validate_request(&req),
+            Err(OAuth2Error::WrongTokenType)
+        ));
+        match config.on_request(req) {
+            Action::ShortCircuit(resp) => {
+                assert_eq!(resp.status, 401);
+                assert!(resp.headers["www-authenticate"].contains("error=\"invalid_token\""));
+            }
+            Action::Continue(_) => panic!("Expected a token without a type to be rejected"),
+        }
+    }
+
+    #[test]
+    fn audience_mismatch_rejects_request_with_invalid_token() {
+        let host = MockHost::install();
+        let mut config = OAuth2Auth {
+            audience: Some("my-api".to_string()),
+            ..create_test_config()
+        };
+        let introspected = |aud: serde_json::Value| {
+            let body = serde_json::json!({"active": true, "sub": "alice", "aud": aud});
+            Ok(barbacane_plugin_sdk::http::HttpResponse {
+                status: 200,
+                headers: BTreeMap::new(),
+                body: Some(body.to_string().into_bytes()),
+            })
+        };
+        host.on_http_once(
+            "POST",
+            ENDPOINT,
+            introspected(serde_json::json!(["billing", "my-api"])),
+        )
+        .on_http_once("POST", ENDPOINT, introspected(serde_json::json!("billing")));
+
+        assert!(matches!(
+            config.on_request(create_request_with_auth("Bearer t1")),
+            Action::Continue(_)
+        ));
+        match config.on_request(create_request_with_auth("Bearer t2")) {
+            Action::ShortCircuit(resp) => {
+                assert_eq!(resp.status, 401);
+                assert!(resp.headers["www-authenticate"].contains("error=\"invalid_token\""));
+            }
+            Action::Continue(_) => panic!("Expected audience mismatch to be rejected"),
+        }
+    }
+
     fn create_request_with_auth(auth_value: &str) -> Request {
         let mut headers = BTreeMap::new();
         headers.insert("authorization".to_string(), auth_value.to_string());
@@ -580,6 +854,74 @@ mod tests {
         assert_eq!(result.unwrap(), "cap_token");
     }
 
+    fn request_with(headers: &[(&str, &str)], query: Option<&str>) -> Request {
+        Request {
+            query: query.map(str::to_string),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..create_request_with_auth("")
+        }
+    }
+
+    #[test]
+    fn test_extract_token_from_query_param() {
+        let config = OAuth2Auth {
+            token_sources: vec![TokenSource::Query {
+                name: "access_token".to_string(),
+            }],
+            ..create_test_config()
+        };
+        let req = request_with(&[], Some("access_token=opaque%2Dtoken"));
+        assert_eq!(config.extract_token(&req).unwrap(), "opaque-token");
+    }
+
+    #[test]
+    fn test_extract_token_from_custom_header_without_scheme() {
+        let config = OAuth2Auth {
+            token_sources: vec![TokenSource::Header {
+                name: "X-Auth-Token".to_string(),
+                scheme: None,
+            }],
+            ..create_test_config()
+        };
+        let req = request_with(&[("x-auth-token", "opaque_token")], None);
+        assert_eq!(config.extract_token(&req).unwrap(), "opaque_token");
+
+        // The default Authorization source is replaced, not kept.
+        let req = create_request_with_auth("Bearer opaque_token");
+        assert!(matches!(
+            config.extract_token(&req),
+            Err(OAuth2Error::MissingToken)
+        ));
+    }
+
+    #[test]
+    fn test_extract_token_sources_precedence() {
+        let config: OAuth2Auth = serde_json::from_value(serde_json::json!({
+            "introspection_endpoint": "https://auth.example.com/introspect",
+            "client_id": "c",
+            "client_secret": "s",
+            "token_sources": [
+                {"header": {"name": "x-auth-token"}},
+                {"header": {"name": "authorization", "scheme": "Bearer"}}
+            ]
+        }))
+        .unwrap();
+        let both = request_with(
+            &[
+                ("x-auth-token", "custom_token"),
+                ("authorization", "Bearer bearer_token"),
+            ],
+            None,
+        );
+        assert_eq!(config.extract_token(&both).unwrap(), "custom_token");
+
+        let bearer_only = create_request_with_auth("Bearer bearer_token");
+        assert_eq!(config.extract_token(&bearer_only).unwrap(), "bearer_token");
+    }
+
     #[test]
     fn test_check_scopes_all_present() {
         let config = create_test_config();
@@ -676,6 +1018,43 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    fn introspection_with_scope(scope: &str) -> IntrospectionResponse {
+        IntrospectionResponse {
+            active: true,
+            scope: Some(scope.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_scopes_exact_match_case_sensitive() {
+        let config = create_test_config();
+        let introspection = introspection_with_scope("Read Write");
+        assert!(config.check_scopes(&introspection, "Read Write").is_ok());
+    }
+
+    #[test]
+    fn test_check_scopes_case_differs_rejected_by_default() {
+        let config = create_test_config();
+        let introspection = introspection_with_scope("READ write");
+        assert!(matches!(
+            config.check_scopes(&introspection, "read write"),
+            Err(OAuth2Error::InsufficientScope)
+        ));
+    }
+
+    #[test]
+    fn test_check_scopes_case_insensitive() {
+        let mut config = create_test_config();
+        config.scopes_case_insensitive = true;
+        let introspection = introspection_with_scope("READ Write");
+        assert!(config.check_scopes(&introspection, "read WRITE").is_ok());
+        assert!(matches!(
+            config.check_scopes(&introspection, "admin"),
+            Err(OAuth2Error::InsufficientScope)
+        ));
+    }
+
     #[test]
     fn test_error_response_401() {
         let config = create_test_config();
@@ -955,6 +1334,308 @@ mod tests {
         assert_eq!(consumer.unwrap(), "alice");
     }
 
+    const ENDPOINT: &str = "https://auth.example.com/introspect";
+
+    fn caching_config(host: &MockHost, now: u64) -> OAuth2Auth {
+        host.set_time(now);
+        OAuth2Auth {
+            cache_ttl_seconds: 60,
+            ..create_test_config()
+        }
+    }
+
+    #[test]
+    fn cache_hit_skips_second_introspection() {
+        let host = MockHost::install();
+        host.on_http_json(
+            "POST",
+            ENDPOINT,
+            200,
+            &serde_json::json!({"active": true, "sub": "alice", "exp": 5000}),
+        );
+        let mut config = caching_config(&host, 1000);
+        let req = create_request_with_auth("Bearer token-a");
+
+        for _ in 0..3 {
+            let sub = config.validate_request(&req).unwrap().sub;
+            assert_eq!(sub.as_deref(), Some("alice"));
+        }
+        assert_eq!(host.http_calls().len(), 1);
+
+        // A different token is introspected on its own.
+        config
+            .validate_request(&create_request_with_auth("Bearer token-b"))
+            .unwrap();
+        assert_eq!(host.http_calls().len(), 2);
+    }
+
+    #[test]
+    fn cache_expiry_forces_reintrospection() {
+        let host = MockHost::install();
+        host.on_http_json(
+            "POST",
+            ENDPOINT,
+            200,
+            &serde_json::json!({"active": true, "exp": 1030}),
+        );
+        let mut config = caching_config(&host, 1000);
+        let req = create_request_with_auth("Bearer token");
+
+        config.validate_request(&req).unwrap();
+        host.set_time(1029);
+        config.validate_request(&req).unwrap();
+        assert_eq!(host.http_calls().len(), 1);
+
+        // The token's exp (1030) is sooner than the 60s TTL.
+        host.set_time(1030);
+        config.validate_request(&req).unwrap();
+        assert_eq!(host.http_calls().len(), 2);
+    }
+
+    #[test]
+    fn cache_ttl_bounds_tokens_without_exp() {
+        let host = MockHost::install();
+        host.on_http_json("POST", ENDPOINT, 200, &serde_json::json!({"active": true}));
+        let mut config = caching_config(&host, 2000);
+        let req = create_request_with_auth("Bearer token");
+
+        config.validate_request(&req).unwrap();
+        host.set_time(2059);
+        config.validate_request(&req).unwrap();
+        assert_eq!(host.http_calls().len(), 1);
+
+        host.set_time(2060);
+        config.validate_request(&req).unwrap();
+        assert_eq!(host.http_calls().len(), 2);
+    }
+
+    #[test]
+    fn inactive_tokens_are_cached_briefly() {
+        let host = MockHost::install();
+        host.on_http_json("POST", ENDPOINT, 200, &serde_json::json!({"active": false}));
+        let mut config = caching_config(&host, 1000);
+        let req = create_request_with_auth("Bearer revoked");
+
+        assert!(matches!(
+            config.validate_request(&req),
+            Err(OAuth2Error::TokenInactive)
+        ));
+        host.set_time(1000 + NEGATIVE_CACHE_TTL_SECONDS - 1);
+        assert!(matches!(
+            config.validate_request(&req),
+            Err(OAuth2Error::TokenInactive)
+        ));
+        assert_eq!(host.http_calls().len(), 1);
+
+        host.set_time(1000 + NEGATIVE_CACHE_TTL_SECONDS);
+        assert!(config.validate_request(&req).is_err());
+        assert_eq!(host.http_calls().len(), 2);
+    }
+
+    #[test]
+    fn caching_disabled_by_default() {
+        let host = MockHost::install();
+        host.on_http_json("POST", ENDPOINT, 200, &serde_json::json!({"active": true}));
+        let mut config = create_test_config();
+        let req = create_request_with_auth("Bearer token");
+
+        config.validate_request(&req).unwrap();
+        config.validate_request(&req).unwrap();
+        assert_eq!(host.http_calls().len(), 2);
+        assert!(config.cache.is_empty());
+    }
+
+    #[test]
+    fn failed_introspection_is_not_cached() {
+        let host = MockHost::install();
+        host.on_http_json("POST", ENDPOINT, 503, &serde_json::json!({}));
+        let mut config = caching_config(&host, 1000);
+        let req = create_request_with_auth("Bearer token");
+
+        assert!(config.validate_request(&req).is_err());
+        assert!(config.validate_request(&req).is_err());
+        assert_eq!(host.http_calls().len(), 2);
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let host = MockHost::install();
+        host.on_http_json("POST", ENDPOINT, 200, &serde_json::json!({"active": true}));
+        let mut config = caching_config(&host, 1000);
+
+        for i in 0..MAX_CACHE_ENTRIES + 5 {
+            config
+                .validate_request(&create_request_with_auth(&format!("Bearer t{i}")))
+                .unwrap();
+        }
+        assert_eq!(config.cache.len(), MAX_CACHE_ENTRIES);
+    }
+
+    #[test]
+    fn optional_missing_token_continues_anonymously() {
+        let host = MockHost::install();
+        let mut config = OAuth2Auth {
+            optional: true,
+            ..create_test_config()
+        };
+        let mut req = create_request_with_auth("");
+        req.headers.clear();
+        req.headers
+            .insert("x-auth-client-id".to_string(), "spoofed".to_string());
+        match config.on_request(req) {
+            Action::Continue(r) => {
+                assert_eq!(r.headers.get("x-auth-anonymous").unwrap(), "true");
+                assert!(!r.headers.contains_key("x-auth-client-id"));
+            }
+            Action::ShortCircuit(_) => panic!("Expected anonymous request to continue"),
+        }
+        assert!(host.http_calls().is_empty());
+    }
+
+    #[test]
+    fn optional_valid_token_populates_headers() {
+        let host = MockHost::install();
+        host.on_http_json(
+            "POST",
+            ENDPOINT,
+            200,
+            &serde_json::json!({"active": true, "sub": "alice"}),
+        );
+        let mut config = OAuth2Auth {
+            optional: true,
+            ..create_test_config()
+        };
+        match config.on_request(create_request_with_auth("Bearer good")) {
+            Action::Continue(r) => {
+                assert_eq!(r.headers.get("x-auth-consumer").unwrap(), "alice");
+                assert!(!r.headers.contains_key("x-auth-anonymous"));
+            }
+            Action::ShortCircuit(_) => panic!("Expected request to be allowed"),
+        }
+    }
+
+    #[test]
+    fn custom_claim_header_prefix() {
+        let host = MockHost::install();
+        host.on_http_json(
+            "POST",
+            ENDPOINT,
+            200,
+            &serde_json::json!({
+                "active": true,
+                "sub": "alice",
+                "scope": "read write",
+                "client_id": "app",
+                "username": "alice@example.com",
+            }),
+        );
+        let mut config = OAuth2Auth {
+            claim_header_prefix: Some("x-consumer-".to_string()),
+            ..create_test_config()
+        };
+        match config.on_request(create_request_with_auth("Bearer good")) {
+            Action::Continue(r) => {
+                let injected: Vec<&str> = r
+                    .headers
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|name| name.starts_with("x-"))
+                    .collect();
+                assert_eq!(
+                    injected,
+                    [
+                        "x-consumer-claims",
+                        "x-consumer-client-id",
+                        "x-consumer-consumer",
+                        "x-consumer-consumer-groups",
+                        "x-consumer-scope",
+                        "x-consumer-sub",
+                        "x-consumer-username",
+                    ]
+                );
+                assert_eq!(r.headers["x-consumer-consumer-groups"], "read,write");
+            }
+            Action::ShortCircuit(_) => panic!("Expected request to be allowed"),
+        }
+    }
+
+    #[test]
+    fn forged_auth_headers_stripped_when_claims_missing() {
+        let host = MockHost::install();
+        host.on_http_json(
+            "POST",
+            ENDPOINT,
+            200,
+            &serde_json::json!({"active": true, "sub": "alice"}),
+        );
+        let mut config = create_test_config();
+        let mut req = create_request_with_auth("Bearer good");
+        req.headers
+            .insert("x-auth-username".to_string(), "admin".to_string());
+        req.headers
+            .insert("X-Auth-Scope".to_string(), "admin".to_string());
+        match config.on_request(req) {
+            Action::Continue(r) => {
+                assert_eq!(r.headers.get("x-auth-sub").unwrap(), "alice");
+                assert!(!r.headers.contains_key("x-auth-username"));
+                assert!(!r.headers.contains_key("X-Auth-Scope"));
+            }
+            Action::ShortCircuit(_) => panic!("Expected request to be allowed"),
+        }
+    }
+
+    #[test]
+    fn optional_invalid_token_still_rejected() {
+        let host = MockHost::install();
+        host.on_http_json("POST", ENDPOINT, 200, &serde_json::json!({"active": false}));
+        let mut config = OAuth2Auth {
+            optional: true,
+            ..create_test_config()
+        };
+        for auth in ["Bearer revoked", "Basic dXNlcjpwYXNz"] {
+            match config.on_request(create_request_with_auth(auth)) {
+                Action::ShortCircuit(resp) => assert_eq!(resp.status, 401),
+                Action::Continue(_) => panic!("Expected invalid token to be rejected"),
+            }
+        }
+    }
+
+    #[test]
+    fn audit_log_records_allow_and_deny() {
+        let host = MockHost::install();
+        host.on_http_json(
+            "POST",
+            ENDPOINT,
+            200,
+            &serde_json::json!({"active": true, "username": "alice", "scope": "read"}),
+        );
+        let mut config = OAuth2Auth {
+            audit_log: true,
+            claim_header_prefix: None,
+            required_scopes: Some("write".to_string()),
+            ..create_test_config()
+        };
+        config.on_request(create_request_with_auth("Bearer token"));
+        config.required_scopes = None;
+        config.on_request(create_request_with_auth("Bearer token"));
+
+        let lines: Vec<serde_json::Value> = host
+            .logs()
+            .iter()
+            .map(|(level, line)| {
+                assert_eq!(*level, 1);
+                serde_json::from_str(line).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                serde_json::json!({"plugin": "oauth2-auth", "decision": "deny", "reason": "insufficient_scope", "sub": null}),
+                serde_json::json!({"plugin": "oauth2-auth", "decision": "allow", "sub": "alice"}),
+            ]
+        );
+    }
+
     #[test]
     fn consumer_groups_from_scope() {
         let scope = "read write admin";
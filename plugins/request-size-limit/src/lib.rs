@@ -1,7 +1,9 @@
 //! Request size limit middleware plugin for Barbacane API gateway.
 //!
 //! Rejects requests that exceed a configurable size limit.
-//! Checks both Content-Length header and actual body size.
+//! Checks both Content-Length header and actual body size, and optionally the
+//! header count, per-header size and URI length — the same limits the gateway
+//! applies globally (`RequestLimits`), tightened per route.
 
 use barbacane_plugin_sdk::prelude::*;
 use serde::Deserialize;
@@ -15,6 +17,18 @@ pub struct RequestSizeLimit {
     #[serde(default = "default_max_bytes")]
     max_bytes: u64,
 
+    /// Maximum number of request headers. Unset: only the gateway limit applies.
+    #[serde(default)]
+    max_headers: Option<usize>,
+
+    /// Maximum size of a single header (name + value bytes).
+    #[serde(default)]
+    max_header_size: Option<usize>,
+
+    /// Maximum URI length (path plus query string).
+    #[serde(default)]
+    max_uri_length: Option<usize>,
+
     /// Whether to check Content-Length header for early rejection.
     /// Default: true
     #[serde(default = "default_check_content_length")]
@@ -29,15 +43,73 @@ fn default_check_content_length() -> bool {
     true
 }
 
+/// A violated limit, mirroring the gateway's `ValidationError2` size variants.
+#[derive(Debug, PartialEq)]
+enum LimitError {
+    BodyTooLarge {
+        size: u64,
+    },
+    TooManyHeaders {
+        count: usize,
+        limit: usize,
+    },
+    HeaderTooLarge {
+        name: String,
+        size: usize,
+        limit: usize,
+    },
+    UriTooLong {
+        length: usize,
+        limit: usize,
+    },
+}
+
 impl RequestSizeLimit {
     /// Handle incoming request - check size limits.
     pub fn on_request(&mut self, req: Request) -> Action<Request> {
+        match self.validate_all(&req) {
+            Ok(()) => Action::Continue(req),
+            Err(e) => Action::ShortCircuit(self.limit_response(&e)),
+        }
+    }
+
+    /// Check the URI, then headers, then the body; the first violation wins.
+    fn validate_all(&self, req: &Request) -> Result<(), LimitError> {
+        if let Some(limit) = self.max_uri_length {
+            let length = req.path.len() + req.query.as_ref().map_or(0, |q| q.len() + 1);
+            if length > limit {
+                return Err(LimitError::UriTooLong { length, limit });
+            }
+        }
+
+        if let Some(limit) = self.max_headers {
+            if req.headers.len() > limit {
+                return Err(LimitError::TooManyHeaders {
+                    count: req.headers.len(),
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = self.max_header_size {
+            for (name, value) in &req.headers {
+                let size = name.len() + value.len();
+                if size > limit {
+                    return Err(LimitError::HeaderTooLarge {
+                        name: name.clone(),
+                        size,
+                        limit,
+                    });
+                }
+            }
+        }
+
         // Check Content-Length header first for early rejection
         if self.check_content_length {
             if let Some(content_length) = req.headers.get("content-length") {
                 if let Ok(size) = content_length.parse::<u64>() {
                     if size > self.max_bytes {
-                        return Action::ShortCircuit(self.payload_too_large_response(size));
+                        return Err(LimitError::BodyTooLarge { size });
                     }
                 }
             }
@@ -47,11 +119,11 @@ impl RequestSizeLimit {
         if let Some(body) = &req.body {
             let size = body.len() as u64;
             if size > self.max_bytes {
-                return Action::ShortCircuit(self.payload_too_large_response(size));
+                return Err(LimitError::BodyTooLarge { size });
             }
         }
 
-        Action::Continue(req)
+        Ok(())
     }
 
     /// Pass through responses unchanged.
@@ -72,6 +144,43 @@ impl RequestSizeLimit {
         ))
         .into_response()
     }
+
+    /// Problem response for a violated limit: 413 body, 431 headers, 414 URI.
+    fn limit_response(&self, error: &LimitError) -> Response {
+        let (status, error_type, title, detail) = match error {
+            LimitError::BodyTooLarge { size } => return self.payload_too_large_response(*size),
+            LimitError::TooManyHeaders { count, limit } => (
+                431,
+                "urn:barbacane:error:request-header-fields-too-large",
+                "Request Header Fields Too Large",
+                format!(
+                    "Request has {} headers, exceeding the limit of {}.",
+                    count, limit
+                ),
+            ),
+            LimitError::HeaderTooLarge { name, size, limit } => (
+                431,
+                "urn:barbacane:error:request-header-fields-too-large",
+                "Request Header Fields Too Large",
+                format!(
+                    "Header '{}' is {} bytes, exceeding the limit of {} bytes.",
+                    name, size, limit
+                ),
+            ),
+            LimitError::UriTooLong { length, limit } => (
+                414,
+                "urn:barbacane:error:uri-too-long",
+                "URI Too Long",
+                format!(
+                    "Request URI is {} characters, exceeding the limit of {}.",
+                    length, limit
+                ),
+            ),
+        };
+        ProblemDetails::new(status, error_type, title)
+            .detail(detail)
+            .into_response()
+    }
 }
 
 #[cfg(test)]
@@ -186,6 +295,9 @@ mod tests {
         let plugin: RequestSizeLimit = serde_json::from_value(serde_json::json!({})).unwrap();
         assert_eq!(plugin.max_bytes, 1_048_576);
         assert!(plugin.check_content_length);
+        assert_eq!(plugin.max_headers, None);
+        assert_eq!(plugin.max_header_size, None);
+        assert_eq!(plugin.max_uri_length, None);
     }
 
     #[test]
@@ -199,6 +311,78 @@ mod tests {
         assert!(body["detail"].as_str().unwrap().contains("1024"));
     }
 
+    fn limits_plugin() -> RequestSizeLimit {
+        serde_json::from_value(serde_json::json!({
+            "max_bytes": 1024,
+            "max_headers": 3,
+            "max_header_size": 64,
+            "max_uri_length": 32
+        }))
+        .unwrap()
+    }
+
+    fn problem(action: Action<Request>) -> (u16, serde_json::Value) {
+        match action {
+            Action::ShortCircuit(r) => {
+                (r.status, serde_json::from_slice(&r.body.unwrap()).unwrap())
+            }
+            Action::Continue(_) => panic!("expected ShortCircuit"),
+        }
+    }
+
+    #[test]
+    fn test_body_too_large_maps_to_413() {
+        let mut plugin = limits_plugin();
+        let (status, body) = problem(plugin.on_request(request_with_body(&"x".repeat(1025))));
+        assert_eq!(status, 413);
+        assert_eq!(body["type"], "urn:barbacane:error:payload-too-large");
+    }
+
+    #[test]
+    fn test_too_many_headers_maps_to_431() {
+        let mut plugin = limits_plugin();
+        let mut req = request_with_body("hi");
+        for name in ["a", "b", "c"] {
+            req.headers.insert(format!("x-{}", name), "1".to_string());
+        }
+        let (status, body) = problem(plugin.on_request(req));
+        assert_eq!(status, 431);
+        assert_eq!(
+            body["type"],
+            "urn:barbacane:error:request-header-fields-too-large"
+        );
+        assert!(body["detail"].as_str().unwrap().contains("4 headers"));
+    }
+
+    #[test]
+    fn test_header_too_large_maps_to_431() {
+        let mut plugin = limits_plugin();
+        let mut req = request_with_body("hi");
+        req.headers.insert("cookie".to_string(), "c".repeat(100));
+        let (status, body) = problem(plugin.on_request(req));
+        assert_eq!(status, 431);
+        assert!(body["detail"].as_str().unwrap().contains("'cookie'"));
+    }
+
+    #[test]
+    fn test_uri_too_long_maps_to_414() {
+        let mut plugin = limits_plugin();
+        let mut req = request_with_body("hi");
+        req.path = "/search".to_string();
+        req.query = Some(format!("q={}", "x".repeat(30)));
+        let (status, body) = problem(plugin.on_request(req));
+        assert_eq!(status, 414);
+        assert_eq!(body["type"], "urn:barbacane:error:uri-too-long");
+    }
+
+    #[test]
+    fn test_within_all_limits_passes() {
+        let mut plugin = limits_plugin();
+        let mut req = request_with_body("hi");
+        req.query = Some("page=2".to_string());
+        assert!(matches!(plugin.on_request(req), Action::Continue(_)));
+    }
+
     #[test]
     fn test_on_response_passthrough() {
         let mut plugin = test_plugin();
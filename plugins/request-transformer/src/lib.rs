@@ -1,21 +1,31 @@
 //! Request transformer middleware plugin for Barbacane API gateway.
 //!
 //! Provides declarative request transformations for:
-//! - Headers (add, set, remove, rename)
+//! - Headers (add, set, append, remove, rename)
 //! - Query parameters (add, remove, rename)
 //! - Path rewriting (strip_prefix, add_prefix, regex replace)
-//! - JSON body (add, remove, rename using JSON Pointer — RFC 6901)
+//! - JSON body (add, remove, rename, copy using JSON Pointer — RFC 6901)
+//! - JSON body via a JSON Patch document (RFC 6902), replacing the pointer ops
+//! - YAML bodies, edited through the same JSON value representation
+//! - `X-Forwarded-For` / `-Proto` / `-Host` injection
+//!
+//! A `when` condition (methods, header values, path regex) gates the whole
+//! transformation.
 //!
 //! Supports variable interpolation: `$client_ip`, `$path.<name>`, `$header.<name>`,
-//! `$query.<name>`, `context:<key>`
+//! `$query.<name>`, `context:<key>`, embedded in larger strings as
+//! `${client_ip}`, `${path.<name>}`, etc.
 
 use barbacane_plugin_sdk::log::log as log_message;
 use barbacane_plugin_sdk::prelude::*;
-use form_urlencoded::{parse as parse_urlencoded, Serializer};
+use barbacane_plugin_sdk::transform::{self, BodyFormat, Patch};
+use form_urlencoded::{byte_serialize, parse as parse_urlencoded};
 use jsonptr::{Assign, Delete, Pointer};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use regex::Regex;
 use serde::Deserialize;
 use serde_json::Value;
+use std::cell::OnceCell;
 use std::collections::BTreeMap;
 
 // ---------------------------------------------------------------------------
@@ -31,11 +41,45 @@ struct HeaderConfig {
     #[serde(default)]
     set: BTreeMap<String, String>,
 
+    /// Append to an existing header as `existing, new`; add it if absent.
+    #[serde(default)]
+    append: BTreeMap<String, String>,
+
+    /// Header names, or `~`-prefixed regexes matched against lowercased names.
     #[serde(default)]
     remove: Vec<String>,
 
     #[serde(default)]
     rename: BTreeMap<String, String>,
+
+    /// `~` entries of `remove`, compiled on first use.
+    #[serde(skip)]
+    remove_patterns: OnceCell<Vec<Regex>>,
+}
+
+impl HeaderConfig {
+    /// Compiled `~pattern` entries from `remove`. Invalid patterns are logged
+    /// and skipped.
+    fn remove_patterns(&self) -> &[Regex] {
+        self.remove_patterns.get_or_init(|| {
+            self.remove
+                .iter()
+                .filter_map(|entry| {
+                    let pattern = entry.strip_prefix('~')?;
+                    match Regex::new(pattern) {
+                        Ok(re) => Some(re),
+                        Err(e) => {
+                            log_message(
+                                0,
+                                &format!("Invalid header remove pattern '{}': {}", pattern, e),
+                            );
+                            None
+                        }
+                    }
+                })
+                .collect()
+        })
+    }
 }
 
 /// Query string transformation configuration.
@@ -49,6 +93,11 @@ struct QueryConfig {
 
     #[serde(default)]
     rename: BTreeMap<String, String>,
+
+    /// Parameters declared with OpenAPI `allowReserved: true`: their values
+    /// keep reserved characters unencoded instead of being form-encoded.
+    #[serde(default)]
+    allow_reserved: Vec<String>,
 }
 
 /// Path rewriting configuration.
@@ -56,7 +105,24 @@ struct QueryConfig {
 struct PathConfig {
     strip_prefix: Option<String>,
     add_prefix: Option<String>,
-    replace: Option<PathReplaceConfig>,
+    replace: Option<PathReplace>,
+}
+
+/// Path regex replacement: a single rule, or a chain applied in order.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum PathReplace {
+    Single(PathReplaceConfig),
+    Chain(Vec<PathReplaceConfig>),
+}
+
+impl PathReplace {
+    fn rules(&self) -> &[PathReplaceConfig] {
+        match self {
+            PathReplace::Single(rule) => std::slice::from_ref(rule),
+            PathReplace::Chain(rules) => rules,
+        }
+    }
 }
 
 /// Path regex replace configuration.
@@ -77,6 +143,89 @@ struct BodyConfig {
 
     #[serde(default)]
     rename: BTreeMap<String, String>,
+
+    /// Source pointer → destination pointer; the source is left in place.
+    #[serde(default)]
+    copy: BTreeMap<String, String>,
+}
+
+/// `X-Forwarded-*` header injection; each flag enables one header.
+#[derive(Debug, Clone, Deserialize, Default)]
+struct ForwardedConfig {
+    /// Append the client IP to `X-Forwarded-For`.
+    #[serde(default, rename = "for")]
+    for_: bool,
+
+    /// Set `X-Forwarded-Proto` unless an earlier proxy already did.
+    #[serde(default)]
+    proto: bool,
+
+    /// Set `X-Forwarded-Host` from `Host` unless an earlier proxy already did.
+    #[serde(default)]
+    host: bool,
+}
+
+/// Condition gating every transformation. All configured criteria must match.
+#[derive(Debug, Clone, Deserialize, Default)]
+struct WhenConfig {
+    /// HTTP methods (case-insensitive); empty matches any method.
+    #[serde(default)]
+    methods: Vec<String>,
+
+    /// Header name → exact value; every listed header must be present.
+    #[serde(default)]
+    header_equals: BTreeMap<String, String>,
+
+    /// Regex matched against the request path.
+    #[serde(default)]
+    path_matches: Option<String>,
+
+    /// `path_matches`, compiled on first use; `None` if absent or invalid.
+    #[serde(skip)]
+    path_regex: OnceCell<Option<Regex>>,
+}
+
+impl WhenConfig {
+    /// Whether `req` satisfies the condition. An invalid `path_matches`
+    /// pattern is logged and never matches.
+    fn matches(&self, req: &Request) -> bool {
+        if !self.methods.is_empty()
+            && !self
+                .methods
+                .iter()
+                .any(|m| m.eq_ignore_ascii_case(&req.method))
+        {
+            return false;
+        }
+
+        let headers_match = self.header_equals.iter().all(|(name, expected)| {
+            req.headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .is_some_and(|(_, value)| value == expected)
+        });
+        if !headers_match {
+            return false;
+        }
+
+        match &self.path_matches {
+            Some(pattern) => self
+                .path_regex
+                .get_or_init(|| match Regex::new(pattern) {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        log_message(
+                            0,
+                            &format!("Invalid when.path_matches pattern '{}': {}", pattern, e),
+                        );
+                        None
+                    }
+                })
+                .as_ref()
+                .is_some_and(|re| re.is_match(&req.path)),
+            None => true,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -87,6 +236,10 @@ struct BodyConfig {
 #[barbacane_middleware]
 #[derive(Deserialize)]
 pub struct RequestTransformer {
+    /// Apply the transformations only to requests matching this condition.
+    #[serde(default)]
+    when: Option<WhenConfig>,
+
     #[serde(default)]
     headers: Option<HeaderConfig>,
 
@@ -99,9 +252,32 @@ pub struct RequestTransformer {
     #[serde(default)]
     body: Option<BodyConfig>,
 
-    /// Compiled regex for path replacement (lazy-initialized on first request).
+    /// Format of bodies edited by `body` and `json_patch`. Unset, it is
+    /// detected from the `content-type` header (YAML types, else JSON).
+    #[serde(default)]
+    body_format: Option<BodyFormat>,
+
+    #[serde(default)]
+    forwarded: Option<ForwardedConfig>,
+
+    /// RFC 6902 patch document applied to JSON bodies. Takes precedence
+    /// over `body`.
+    #[serde(default)]
+    json_patch: Option<Vec<Value>>,
+
+    /// Skip body transforms (`body` and `json_patch`) for bodies larger than
+    /// this many bytes instead of parsing them. Unset means no limit.
+    #[serde(default)]
+    max_body_bytes: Option<usize>,
+
+    /// `json_patch`, parsed on first use; `None` if the document is invalid.
+    #[serde(skip)]
+    compiled_patch: OnceCell<Option<Patch>>,
+
+    /// Compiled regexes for path replacement, one per rule (lazy-initialized
+    /// on first request).
     #[serde(skip)]
-    compiled_replace: Option<Regex>,
+    compiled_replace: Option<Vec<Regex>>,
 }
 
 impl RequestTransformer {
@@ -110,30 +286,43 @@ impl RequestTransformer {
     /// Transformations are applied in this order:
     /// 1. Path (affects routing, must be first)
     /// 2. Headers
-    /// 3. Query parameters
-    /// 4. Body
+    /// 3. `X-Forwarded-*` headers
+    /// 4. Query parameters
+    /// 5. Body (`json_patch`, or else `body`); `content-length` is dropped
+    ///    when the body changes
     ///
     /// Variable interpolation always reads from the **original** request so that
-    /// earlier transforms don't affect later variable resolution.
+    /// earlier transforms don't affect later variable resolution. The `when`
+    /// condition is evaluated against the same snapshot; when it doesn't match,
+    /// the request passes through untouched.
     pub fn on_request(&mut self, mut req: Request) -> Action<Request> {
         // Snapshot the original request for interpolation — all variable
         // references ($query.*, $header.*, etc.) resolve against this snapshot
         // so that transforms don't interfere with each other.
         let original = req.clone();
 
-        // Lazy-compile the path regex on first invocation
+        if let Some(when) = &self.when {
+            if !when.matches(&original) {
+                return Action::Continue(req);
+            }
+        }
+
+        // Lazy-compile the path regexes on first invocation. An invalid
+        // pattern disables the whole chain rather than applying part of it.
         if self.compiled_replace.is_none() {
             if let Some(path_config) = &self.path {
-                if let Some(replace_config) = &path_config.replace {
-                    match Regex::new(&replace_config.pattern) {
-                        Ok(re) => self.compiled_replace = Some(re),
-                        Err(e) => {
+                if let Some(replace) = &path_config.replace {
+                    let compiled: Result<Vec<Regex>, _> = replace
+                        .rules()
+                        .iter()
+                        .map(|rule| Regex::new(&rule.pattern).map_err(|e| (rule, e)))
+                        .collect();
+                    match compiled {
+                        Ok(regexes) => self.compiled_replace = Some(regexes),
+                        Err((rule, e)) => {
                             log_message(
                                 0,
-                                &format!(
-                                    "Invalid regex pattern '{}': {}",
-                                    replace_config.pattern, e
-                                ),
+                                &format!("Invalid regex pattern '{}': {}", rule.pattern, e),
                             );
                         }
                     }
@@ -142,24 +331,58 @@ impl RequestTransformer {
         }
 
         if let Some(path_config) = &self.path {
-            req.path = transform_path(&req.path, path_config, self.compiled_replace.as_ref());
+            req.path = transform_path(&req.path, path_config, self.compiled_replace.as_deref());
         }
 
         if let Some(header_config) = &self.headers {
             transform_headers(&mut req.headers, header_config, &original);
         }
 
+        if let Some(forwarded_config) = &self.forwarded {
+            apply_forwarded(&mut req.headers, forwarded_config, &original);
+        }
+
         if let Some(query_config) = &self.querystring {
             req.query = transform_query(&req.query, query_config, &original);
         }
 
-        if let Some(body_config) = &self.body {
-            req.body = transform_body(&req.body, body_config, &original);
+        let transforms_body = self.json_patch.is_some() || self.body.is_some();
+        if transforms_body
+            && transform::body_within_limit(self.max_body_bytes, req.body.as_deref(), "request")
+        {
+            let format = self
+                .body_format
+                .unwrap_or_else(|| BodyFormat::detect(&original.headers));
+            let edited = if self.json_patch.is_some() {
+                self.compiled_patch()
+                    .map(|patch| transform::apply_json_patch(&req.body, patch, format))
+            } else {
+                self.body
+                    .as_ref()
+                    .map(|config| transform_body(&req.body, config, &original, format))
+            };
+            if let Some(edited) = edited.filter(|edited| *edited != req.body) {
+                transform::remove_content_length(&mut req.headers);
+                req.body = edited;
+            }
         }
 
         Action::Continue(req)
     }
 
+    /// The parsed `json_patch` document. An invalid document is logged once
+    /// and leaves bodies unchanged.
+    fn compiled_patch(&self) -> Option<&Patch> {
+        self.compiled_patch
+            .get_or_init(|| {
+                let ops = Value::Array(self.json_patch.clone()?);
+                serde_json::from_value(ops)
+                    .map_err(|e| log_message(0, &format!("Invalid JSON Patch document: {}", e)))
+                    .ok()
+            })
+            .as_ref()
+    }
+
     /// Pass through responses unchanged (request-transformer only).
     pub fn on_response(&mut self, resp: Response) -> Response {
         resp
@@ -172,35 +395,79 @@ impl RequestTransformer {
 
 /// Interpolate a value template with request data.
 ///
+/// A template containing `${...}` fragments has each fragment resolved in
+/// place (`"v=${query.page};ip=${client_ip}"`); unknown variables become empty
+/// and an unterminated `${` is kept literally. Otherwise the whole template is
+/// a single variable (`$client_ip`, `$path.id`, `context:auth.sub`) or a literal.
+///
 /// Returns the resolved value, or an empty string if the variable cannot be resolved.
 fn interpolate_value(template: &str, req: &Request) -> String {
-    if template == "$client_ip" {
-        return req.client_ip.clone();
+    if template.contains("${") {
+        return interpolate_fragments(template, req);
     }
 
-    if let Some(param_name) = template.strip_prefix("$path.") {
-        return req.path_params.get(param_name).cloned().unwrap_or_default();
+    if template.starts_with("context:") {
+        return resolve_variable(template, req).unwrap_or_default();
     }
 
-    if let Some(header_name) = template.strip_prefix("$header.") {
-        return req
-            .headers
-            .get(header_name)
-            .or_else(|| req.headers.get(&header_name.to_lowercase()))
-            .cloned()
-            .unwrap_or_default();
+    template
+        .strip_prefix('$')
+        .and_then(|name| resolve_variable(name, req))
+        // Literal value (no variable prefix)
+        .unwrap_or_else(|| template.to_string())
+}
+
+/// Replace every `${name}` fragment in `template` with its resolved value.
+fn interpolate_fragments(template: &str, req: &Request) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let fragment = &rest[start + 2..];
+        let Some(end) = fragment.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        out.push_str(&resolve_variable(&fragment[..end], req).unwrap_or_default());
+        rest = &fragment[end + 1..];
     }
 
-    if let Some(query_name) = template.strip_prefix("$query.") {
-        return extract_query_param(&req.query, query_name);
+    out.push_str(rest);
+    out
+}
+
+/// Resolve a variable name (without its `$` or `${}` wrapper). Returns `None`
+/// for an unknown kind of variable, and an empty string for a known kind whose
+/// value is absent.
+fn resolve_variable(name: &str, req: &Request) -> Option<String> {
+    if name == "client_ip" {
+        return Some(req.client_ip.clone());
+    }
+
+    if let Some(param_name) = name.strip_prefix("path.") {
+        return Some(req.path_params.get(param_name).cloned().unwrap_or_default());
     }
 
-    if let Some(context_key) = template.strip_prefix("context:") {
-        return context_get(context_key).unwrap_or_default();
+    if let Some(header_name) = name.strip_prefix("header.") {
+        return Some(
+            req.headers
+                .get(header_name)
+                .or_else(|| req.headers.get(&header_name.to_lowercase()))
+                .cloned()
+                .unwrap_or_default(),
+        );
+    }
+
+    if let Some(query_name) = name.strip_prefix("query.") {
+        return Some(extract_query_param(&req.query, query_name));
     }
 
-    // Literal value (no variable prefix)
-    template.to_string()
+    if let Some(context_key) = name.strip_prefix("context:") {
+        return Some(context_get(context_key).unwrap_or_default());
+    }
+
+    None
 }
 
 /// Extract a single query parameter value from a query string.
@@ -223,14 +490,24 @@ fn extract_query_param(query: &Option<String>, param_name: &str) -> String {
 // Header transformations
 // ---------------------------------------------------------------------------
 
-/// Transform request headers. Order: remove → rename → set → add.
+/// Transform request headers. Order: remove → rename → set → append → add.
 fn transform_headers(
     headers: &mut BTreeMap<String, String>,
     config: &HeaderConfig,
     original: &Request,
 ) {
     for header_name in &config.remove {
-        headers.remove(&header_name.to_lowercase());
+        if !header_name.starts_with('~') {
+            headers.remove(&header_name.to_lowercase());
+        }
+    }
+
+    let patterns = config.remove_patterns();
+    if !patterns.is_empty() {
+        headers.retain(|name, _| {
+            let name = name.to_lowercase();
+            !patterns.iter().any(|re| re.is_match(&name))
+        });
     }
 
     for (old_name, new_name) in &config.rename {
@@ -245,6 +522,17 @@ fn transform_headers(
             .or_insert_with(|| interpolate_value(value_template, original));
     }
 
+    for (header_name, value_template) in &config.append {
+        let value = interpolate_value(value_template, original);
+        headers
+            .entry(header_name.to_lowercase())
+            .and_modify(|existing| {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            })
+            .or_insert_with(|| value.clone());
+    }
+
     for (header_name, value_template) in &config.add {
         headers.insert(
             header_name.to_lowercase(),
@@ -253,6 +541,45 @@ fn transform_headers(
     }
 }
 
+/// Inject `X-Forwarded-*` headers.
+///
+/// `X-Forwarded-For` extends whatever chain is left after the header
+/// transforms, so `headers.remove: [x-forwarded-for]` starts a fresh chain
+/// for untrusted clients. `X-Forwarded-Proto` and `X-Forwarded-Host` always
+/// replace incoming values so clients cannot spoof them; the scheme comes from
+/// the host's `request.scheme` context key.
+fn apply_forwarded(
+    headers: &mut BTreeMap<String, String>,
+    config: &ForwardedConfig,
+    original: &Request,
+) {
+    if config.for_ {
+        headers
+            .entry("x-forwarded-for".to_string())
+            .and_modify(|chain| {
+                chain.push_str(", ");
+                chain.push_str(&original.client_ip);
+            })
+            .or_insert_with(|| original.client_ip.clone());
+    }
+
+    if config.proto {
+        let scheme = context_get("request.scheme").unwrap_or_else(|| "http".to_string());
+        headers.insert("x-forwarded-proto".to_string(), scheme);
+    }
+
+    if config.host {
+        match original.headers.get("host") {
+            Some(host) => {
+                headers.insert("x-forwarded-host".to_string(), host.clone());
+            }
+            None => {
+                headers.remove("x-forwarded-host");
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Query string transformations
 // ---------------------------------------------------------------------------
@@ -263,7 +590,7 @@ fn transform_query(
     config: &QueryConfig,
     original: &Request,
 ) -> Option<String> {
-    let mut params = parse_query_params(query);
+    let mut params = parse_query_params(query, &config.allow_reserved);
 
     for param_name in &config.remove {
         params.retain(|(k, _)| k != param_name);
@@ -283,29 +610,69 @@ fn transform_query(
         params.push((param_name.clone(), value));
     }
 
-    build_query_string(params)
+    build_query_string(params, &config.allow_reserved)
 }
 
-fn parse_query_params(query: &Option<String>) -> Vec<(String, String)> {
+/// Characters still percent-encoded in `allowReserved` values: everything
+/// outside RFC 3986 unreserved + reserved, plus `%` (so literal percent signs
+/// survive), `&` (pair separator) and `#` (would end the query).
+const ALLOW_RESERVED_ENCODE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'<')
+    .add(b'>')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+fn parse_query_params(query: &Option<String>, allow_reserved: &[String]) -> Vec<(String, String)> {
     match query {
-        Some(q) if !q.is_empty() => parse_urlencoded(q.as_bytes())
-            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        Some(q) if !q.is_empty() => q
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter_map(|pair| {
+                let (key, value) = parse_urlencoded(pair.as_bytes()).next()?;
+                let key = key.into_owned();
+                // `+` is a literal plus in an allowReserved value, so only
+                // `%XX` escapes are decoded.
+                let value = if allow_reserved.contains(&key) {
+                    let raw = pair.split_once('=').map_or("", |(_, v)| v);
+                    percent_decode_str(raw).decode_utf8_lossy().into_owned()
+                } else {
+                    value.into_owned()
+                };
+                Some((key, value))
+            })
             .collect(),
         _ => Vec::new(),
     }
 }
 
-fn build_query_string(params: Vec<(String, String)>) -> Option<String> {
+fn build_query_string(params: Vec<(String, String)>, allow_reserved: &[String]) -> Option<String> {
     if params.is_empty() {
         return None;
     }
 
-    let mut serializer = Serializer::new(String::new());
-    for (key, value) in params {
-        serializer.append_pair(&key, &value);
-    }
+    let pairs: Vec<String> = params
+        .iter()
+        .map(|(key, value)| {
+            let encoded_value: String = if allow_reserved.contains(key) {
+                utf8_percent_encode(value, ALLOW_RESERVED_ENCODE).collect()
+            } else {
+                byte_serialize(value.as_bytes()).collect()
+            };
+            let encoded_key: String = byte_serialize(key.as_bytes()).collect();
+            format!("{}={}", encoded_key, encoded_value)
+        })
+        .collect();
 
-    Some(serializer.finish())
+    Some(pairs.join("&"))
 }
 
 // ---------------------------------------------------------------------------
@@ -313,7 +680,7 @@ fn build_query_string(params: Vec<(String, String)>) -> Option<String> {
 // ---------------------------------------------------------------------------
 
 /// Transform path. Order: strip_prefix → add_prefix → regex replace.
-fn transform_path(path: &str, config: &PathConfig, compiled_re: Option<&Regex>) -> String {
+fn transform_path(path: &str, config: &PathConfig, compiled_re: Option<&[Regex]>) -> String {
     let mut result = path.to_string();
 
     if let Some(prefix) = &config.strip_prefix {
@@ -342,11 +709,10 @@ fn transform_path(path: &str, config: &PathConfig, compiled_re: Option<&Regex>)
         result = format!("{}{}", normalized_prefix, normalized_path);
     }
 
-    if let Some(re) = compiled_re {
-        if let Some(replace_config) = &config.replace {
-            result = re
-                .replace_all(&result, &replace_config.replacement)
-                .to_string();
+    // Each replacement runs against the output of the previous one.
+    if let (Some(regexes), Some(replace)) = (compiled_re, &config.replace) {
+        for (re, rule) in regexes.iter().zip(replace.rules()) {
+            result = re.replace_all(&result, &rule.replacement).to_string();
         }
     }
 
@@ -357,25 +723,29 @@ fn transform_path(path: &str, config: &PathConfig, compiled_re: Option<&Regex>)
 // Body transformations (JSON Pointer — RFC 6901)
 // ---------------------------------------------------------------------------
 
-/// Transform JSON body. Order: remove → rename → add.
+/// Transform JSON body. Order: remove → rename → copy → add.
 ///
 /// Non-JSON bodies are returned unchanged.
 fn transform_body(
     body: &Option<Vec<u8>>,
     config: &BodyConfig,
     original: &Request,
+    format: BodyFormat,
 ) -> Option<Vec<u8>> {
     let body_bytes = match body {
         Some(b) if !b.is_empty() => b,
         _ => return body.clone(),
     };
 
-    let mut json: Value = match serde_json::from_slice(body_bytes) {
-        Ok(v) => v,
-        Err(_) => {
-            log_message(1, "Body is not valid JSON, skipping body transformations");
-            return body.clone();
-        }
+    let Some(mut json) = format.parse(body_bytes) else {
+        log_message(
+            1,
+            &format!(
+                "Body is not valid {}, skipping body transformations",
+                format.name()
+            ),
+        );
+        return body.clone();
     };
 
     for pointer_str in &config.remove {
@@ -433,6 +803,35 @@ fn transform_body(
         }
     }
 
+    for (source_str, dest_str) in &config.copy {
+        if let Err(e) = Pointer::parse(source_str) {
+            log_message(1, &format!("Invalid copy source '{}': {}", source_str, e));
+            continue;
+        }
+        let dest_ptr = match Pointer::parse(dest_str) {
+            Ok(ptr) => ptr,
+            Err(e) => {
+                log_message(
+                    1,
+                    &format!("Invalid copy destination '{}': {}", dest_str, e),
+                );
+                continue;
+            }
+        };
+
+        let Some(value) = json.pointer(source_str).cloned() else {
+            log_message(1, &format!("Copy source '{}' not found", source_str));
+            continue;
+        };
+
+        if let Err(e) = json.assign(dest_ptr, value) {
+            log_message(
+                1,
+                &format!("Failed to copy '{}' to '{}': {}", source_str, dest_str, e),
+            );
+        }
+    }
+
     for (pointer_str, value_template) in &config.add {
         let interpolated = interpolate_value(value_template, original);
         // When the value was interpolated from a variable ($query.page → "2"),
@@ -456,12 +855,16 @@ fn transform_body(
         }
     }
 
-    match serde_json::to_vec(&json) {
+    match format.serialize(&json) {
         Ok(v) => Some(v),
         Err(e) => {
             log_message(
                 0,
-                &format!("Failed to serialize JSON after transformation: {}", e),
+                &format!(
+                    "Failed to serialize {} after transformation: {}",
+                    format.name(),
+                    e
+                ),
             );
             body.clone()
         }
@@ -631,6 +1034,37 @@ mod tests {
         assert_eq!(interpolate_value("context:missing", &req), "");
     }
 
+    #[test]
+    fn test_interpolate_embedded_fragments() {
+        let req = create_test_request();
+        assert_eq!(
+            interpolate_value("v=${query.page};ip=${client_ip}", &req),
+            "v=2;ip=192.168.1.1"
+        );
+        assert_eq!(
+            interpolate_value("/users/${path.id}/${header.host}", &req),
+            "/users/123/api.example.com"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_unknown_fragment_is_empty() {
+        let req = create_test_request();
+        assert_eq!(interpolate_value("a${nope}b${query.missing}c", &req), "abc");
+        assert_eq!(interpolate_value("x=${path.id", &req), "x=${path.id");
+    }
+
+    #[test]
+    fn test_interpolate_literal_without_fragments() {
+        let req = create_test_request();
+        assert_eq!(
+            interpolate_value("/users/$path.id", &req),
+            "/users/$path.id"
+        );
+        assert_eq!(interpolate_value("$5 off {promo}", &req), "$5 off {promo}");
+        assert_eq!(interpolate_value("$unknown", &req), "$unknown");
+    }
+
     // -- Header transformation tests ----------------------------------------
 
     #[test]
@@ -685,6 +1119,80 @@ mod tests {
         assert_eq!(headers.get("host"), Some(&"api.example.com".to_string()));
     }
 
+    #[test]
+    fn test_headers_append_to_existing() {
+        let req = create_test_request();
+        let mut headers = req.headers.clone();
+        headers.insert("via".to_string(), "1.1 upstream-proxy".to_string());
+
+        let mut config = HeaderConfig::default();
+        config
+            .append
+            .insert("Via".to_string(), "1.1 barbacane".to_string());
+
+        transform_headers(&mut headers, &config, &req);
+
+        assert_eq!(
+            headers.get("via"),
+            Some(&"1.1 upstream-proxy, 1.1 barbacane".to_string())
+        );
+    }
+
+    #[test]
+    fn test_headers_append_to_missing() {
+        let req = create_test_request();
+        let mut headers = req.headers.clone();
+
+        let mut config = HeaderConfig::default();
+        config
+            .append
+            .insert("via".to_string(), "1.1 barbacane".to_string());
+
+        transform_headers(&mut headers, &config, &req);
+
+        assert_eq!(headers.get("via"), Some(&"1.1 barbacane".to_string()));
+    }
+
+    #[test]
+    fn test_headers_append_interpolated() {
+        let req = create_test_request();
+        let mut headers = req.headers.clone();
+        headers.insert("x-forwarded-for".to_string(), "10.0.0.1".to_string());
+
+        let mut config = HeaderConfig::default();
+        config
+            .append
+            .insert("x-forwarded-for".to_string(), "$client_ip".to_string());
+
+        transform_headers(&mut headers, &config, &req);
+
+        assert_eq!(
+            headers.get("x-forwarded-for"),
+            Some(&"10.0.0.1, 192.168.1.1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_headers_append_runs_after_set_and_before_add() {
+        let req = create_test_request();
+        let mut headers = req.headers.clone();
+
+        let mut config = HeaderConfig::default();
+        config.set.insert("x-a".to_string(), "set".to_string());
+        config
+            .append
+            .insert("x-a".to_string(), "appended".to_string());
+        config
+            .append
+            .insert("x-b".to_string(), "appended".to_string());
+        config.add.insert("x-b".to_string(), "added".to_string());
+
+        transform_headers(&mut headers, &config, &req);
+
+        assert_eq!(headers.get("x-a"), Some(&"set, appended".to_string()));
+        assert_eq!(headers.get("x-b"), Some(&"added".to_string()));
+    }
+
     #[test]
     fn test_headers_remove() {
         let req = create_test_request();
@@ -701,6 +1209,41 @@ mod tests {
         assert_eq!(headers.get("host"), None);
     }
 
+    #[test]
+    fn test_headers_remove_pattern() {
+        let req = create_test_request();
+        let mut headers = req.headers.clone();
+        headers.insert("x-internal-token".to_string(), "secret".to_string());
+        headers.insert("x-internal-user".to_string(), "alice".to_string());
+        headers.insert("x-external".to_string(), "keep".to_string());
+
+        let mut config = HeaderConfig::default();
+        config.remove.push("~^x-internal-".to_string());
+
+        transform_headers(&mut headers, &config, &req);
+
+        assert_eq!(headers.get("x-internal-token"), None);
+        assert_eq!(headers.get("x-internal-user"), None);
+        assert_eq!(headers.get("x-external"), Some(&"keep".to_string()));
+        assert_eq!(headers.get("host"), Some(&"api.example.com".to_string()));
+    }
+
+    #[test]
+    fn test_headers_remove_invalid_pattern_skipped() {
+        let req = create_test_request();
+        let mut headers = req.headers.clone();
+        headers.insert("x-internal-token".to_string(), "secret".to_string());
+
+        let mut config = HeaderConfig::default();
+        config.remove.push("~[unclosed".to_string());
+        config.remove.push("host".to_string());
+
+        transform_headers(&mut headers, &config, &req);
+
+        assert_eq!(headers.get("x-internal-token"), Some(&"secret".to_string()));
+        assert_eq!(headers.get("host"), None);
+    }
+
     #[test]
     fn test_headers_rename() {
         let req = create_test_request();
@@ -770,6 +1313,100 @@ mod tests {
         assert_eq!(headers.get("x-add"), Some(&"add-value".to_string()));
     }
 
+    // -- Forwarded header tests ---------------------------------------------
+
+    fn forwarded_plugin() -> RequestTransformer {
+        serde_json::from_str(r#"{"forwarded": {"for": true, "proto": true, "host": true}}"#)
+            .expect("valid json")
+    }
+
+    #[test]
+    fn test_forwarded_first_hop() {
+        mock_host::reset();
+        mock_host::context_set("request.scheme", "https");
+        let mut plugin = forwarded_plugin();
+
+        match plugin.on_request(create_test_request()) {
+            Action::Continue(modified) => {
+                assert_eq!(
+                    modified.headers.get("x-forwarded-for"),
+                    Some(&"192.168.1.1".to_string())
+                );
+                assert_eq!(
+                    modified.headers.get("x-forwarded-proto"),
+                    Some(&"https".to_string())
+                );
+                assert_eq!(
+                    modified.headers.get("x-forwarded-host"),
+                    Some(&"api.example.com".to_string())
+                );
+            }
+            _ => panic!("Expected Action::Continue"),
+        }
+    }
+
+    #[test]
+    fn test_forwarded_overwrites_client_proto_and_host() {
+        mock_host::reset();
+        mock_host::context_set("request.scheme", "http");
+        let mut plugin = forwarded_plugin();
+        let mut req = create_test_request();
+        req.headers.insert(
+            "x-forwarded-for".to_string(),
+            "203.0.113.7, 10.0.0.2".to_string(),
+        );
+        req.headers
+            .insert("x-forwarded-proto".to_string(), "https".to_string());
+        req.headers.insert(
+            "x-forwarded-host".to_string(),
+            "evil.example.com".to_string(),
+        );
+
+        match plugin.on_request(req) {
+            Action::Continue(modified) => {
+                assert_eq!(
+                    modified.headers.get("x-forwarded-for"),
+                    Some(&"203.0.113.7, 10.0.0.2, 192.168.1.1".to_string())
+                );
+                assert_eq!(
+                    modified.headers.get("x-forwarded-proto"),
+                    Some(&"http".to_string())
+                );
+                assert_eq!(
+                    modified.headers.get("x-forwarded-host"),
+                    Some(&"api.example.com".to_string())
+                );
+            }
+            _ => panic!("Expected Action::Continue"),
+        }
+    }
+
+    #[test]
+    fn test_forwarded_removed_chain_starts_fresh() {
+        let mut plugin: RequestTransformer = serde_json::from_str(
+            r#"{
+                "headers": {"remove": ["x-forwarded-for"]},
+                "forwarded": {"for": true}
+            }"#,
+        )
+        .expect("valid json");
+        let mut req = create_test_request();
+        req.headers
+            .insert("x-forwarded-for".to_string(), "6.6.6.6".to_string());
+
+        match plugin.on_request(req) {
+            Action::Continue(modified) => {
+                assert_eq!(
+                    modified.headers.get("x-forwarded-for"),
+                    Some(&"192.168.1.1".to_string())
+                );
+                assert_eq!(modified.headers.get("x-forwarded-proto"), None);
+                assert_eq!(modified.headers.get("x-forwarded-host"), None);
+            }
+            _ => panic!("Expected Action::Continue"),
+        }
+    }
+
     // -- Query string transformation tests ----------------------------------
 
     #[test]
@@ -928,6 +1565,48 @@ mod tests {
         assert!(result_str.contains("special"));
     }
 
+    #[test]
+    fn test_query_allow_reserved_preserves_reserved_chars() {
+        let req = create_test_request();
+        let query = Some("path=docs/a+b:c&other=x".to_string());
+
+        let mut config = QueryConfig::default();
+        config.allow_reserved.push("path".to_string());
+        config.add.insert("filter".to_string(), "a/b".to_string());
+
+        let result = transform_query(&query, &config, &req);
+        let result_str = result.expect("should have query string");
+
+        assert!(result_str.starts_with("path=docs/a+b:c&other=x"));
+        // Added params that aren't allowReserved are still encoded.
+        assert!(result_str.contains("filter=a%2Fb"));
+    }
+
+    #[test]
+    fn test_query_allow_reserved_still_encodes_structural_chars() {
+        let req = create_test_request();
+
+        let mut config = QueryConfig::default();
+        config.allow_reserved.push("path".to_string());
+        config
+            .add
+            .insert("path".to_string(), "a/b&c#d 100%".to_string());
+
+        let result = transform_query(&None, &config, &req);
+        assert_eq!(result, Some("path=a/b%26c%23d%20100%25".to_string()));
+    }
+
+    #[test]
+    fn test_query_default_encodes_reserved_chars() {
+        let req = create_test_request();
+        let query = Some("path=docs/a:b".to_string());
+
+        let config = QueryConfig::default();
+
+        let result = transform_query(&query, &config, &req);
+        assert_eq!(result, Some("path=docs%2Fa%3Ab".to_string()));
+    }
+
     // -- Path transformation tests ------------------------------------------
 
     #[test]
@@ -999,24 +1678,24 @@ mod tests {
         let config = PathConfig {
             strip_prefix: None,
             add_prefix: None,
-            replace: Some(PathReplaceConfig {
+            replace: Some(PathReplace::Single(PathReplaceConfig {
                 pattern: r"/v\d+/".to_string(),
                 replacement: "/".to_string(),
-            }),
+            })),
         };
-        let re =
-            Regex::new(&config.replace.as_ref().expect("set above").pattern).expect("valid regex");
+        let re = Regex::new(&config.replace.as_ref().expect("set above").rules()[0].pattern)
+            .expect("valid regex");
 
         assert_eq!(
-            transform_path("/api/v1/users", &config, Some(&re)),
+            transform_path("/api/v1/users", &config, Some(std::slice::from_ref(&re))),
             "/api/users"
         );
         assert_eq!(
-            transform_path("/api/v2/orders", &config, Some(&re)),
+            transform_path("/api/v2/orders", &config, Some(std::slice::from_ref(&re))),
             "/api/orders"
         );
         assert_eq!(
-            transform_path("/api/users", &config, Some(&re)),
+            transform_path("/api/users", &config, Some(std::slice::from_ref(&re))),
             "/api/users"
         );
     }
@@ -1026,20 +1705,20 @@ mod tests {
         let config = PathConfig {
             strip_prefix: None,
             add_prefix: None,
-            replace: Some(PathReplaceConfig {
+            replace: Some(PathReplace::Single(PathReplaceConfig {
                 pattern: r"/users/(\d+)".to_string(),
                 replacement: "/user/$1/profile".to_string(),
-            }),
+            })),
         };
-        let re =
-            Regex::new(&config.replace.as_ref().expect("set above").pattern).expect("valid regex");
+        let re = Regex::new(&config.replace.as_ref().expect("set above").rules()[0].pattern)
+            .expect("valid regex");
 
         assert_eq!(
-            transform_path("/users/123", &config, Some(&re)),
+            transform_path("/users/123", &config, Some(std::slice::from_ref(&re))),
             "/user/123/profile"
         );
         assert_eq!(
-            transform_path("/users/456/data", &config, Some(&re)),
+            transform_path("/users/456/data", &config, Some(std::slice::from_ref(&re))),
             "/user/456/profile/data"
         );
     }
@@ -1049,20 +1728,52 @@ mod tests {
         let config = PathConfig {
             strip_prefix: Some("/api".to_string()),
             add_prefix: Some("/internal".to_string()),
-            replace: Some(PathReplaceConfig {
+            replace: Some(PathReplace::Single(PathReplaceConfig {
                 pattern: r"/v\d+".to_string(),
                 replacement: "".to_string(),
-            }),
+            })),
         };
-        let re =
-            Regex::new(&config.replace.as_ref().expect("set above").pattern).expect("valid regex");
+        let re = Regex::new(&config.replace.as_ref().expect("set above").rules()[0].pattern)
+            .expect("valid regex");
 
         assert_eq!(
-            transform_path("/api/v2/users", &config, Some(&re)),
+            transform_path("/api/v2/users", &config, Some(std::slice::from_ref(&re))),
             "/internal/users"
         );
     }
 
+    #[test]
+    fn test_path_regex_replace_chain_applies_in_order() {
+        let config: PathConfig = serde_json::from_str(
+            r#"{"replace": [
+                {"pattern": "^/v\\d+", "replacement": ""},
+                {"pattern": "/{2,}", "replacement": "/"},
+                {"pattern": "/legacy/(\\w+)", "replacement": "/$1"}
+            ]}"#,
+        )
+        .expect("valid json");
+        let regexes: Vec<Regex> = config
+            .replace
+            .as_ref()
+            .expect("set above")
+            .rules()
+            .iter()
+            .map(|rule| Regex::new(&rule.pattern).expect("valid regex"))
+            .collect();
+
+        // Each rule sees the previous rule's output: stripping `/v1` exposes
+        // the double slash that the second rule collapses, which in turn lets
+        // the third rule match `/legacy/...`.
+        assert_eq!(
+            transform_path("/v1//legacy/orders", &config, Some(&regexes)),
+            "/orders"
+        );
+        assert_eq!(
+            transform_path("/v1//legacy/orders", &config, Some(&regexes[..1])),
+            "//legacy/orders"
+        );
+    }
+
     #[test]
     fn test_path_empty() {
         let config = PathConfig {
@@ -1112,7 +1823,7 @@ mod tests {
             .add
             .insert("/gateway".to_string(), "barbacane".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1133,7 +1844,7 @@ mod tests {
             .add
             .insert("/metadata/version".to_string(), "1.0".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1158,7 +1869,7 @@ mod tests {
             .add
             .insert("/page".to_string(), "$query.page".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1176,7 +1887,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.remove.push("/password".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1193,7 +1904,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.remove.push("/metadata/internal".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1212,7 +1923,7 @@ mod tests {
             .rename
             .insert("/userName".to_string(), "/user_name".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1232,7 +1943,7 @@ mod tests {
             "/metadata/newName".to_string(),
         );
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1250,7 +1961,7 @@ mod tests {
             .rename
             .insert("/field".to_string(), "/field".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1269,7 +1980,7 @@ mod tests {
             .rename
             .insert("/items/0/oldKey".to_string(), "/items/0/newKey".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1294,7 +2005,7 @@ mod tests {
             .insert("/toOverwrite".to_string(), "new".to_string());
         config.add.insert("/added".to_string(), "value".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1312,7 +2023,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.add.insert("/field".to_string(), "value".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         assert_eq!(result, Some(b"not json".to_vec()));
     }
 
@@ -1324,7 +2035,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.add.insert("/field".to_string(), "value".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         assert_eq!(result, None);
     }
 
@@ -1336,7 +2047,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.remove.push("/nonexistent/deeply/nested".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         assert!(result.is_some());
     }
 
@@ -1350,7 +2061,7 @@ mod tests {
             .add
             .insert("/items/0/gateway".to_string(), "barbacane".to_string());
 
-        let result = transform_body(&body, &config, &req);
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -1358,6 +2069,445 @@ mod tests {
         assert_eq!(json["items"][1].get("gateway"), None);
     }
 
+    #[test]
+    fn test_body_copy_scalar() {
+        let req = create_post_request();
+        let body = Some(br#"{"user":"john","age":30}"#.to_vec());
+
+        let mut config = BodyConfig::default();
+        config
+            .copy
+            .insert("/user".to_string(), "/username".to_string());
+        config
+            .copy
+            .insert("/age".to_string(), "/meta/age".to_string());
+
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
+        let json: Value =
+            serde_json::from_slice(&result.expect("should have body")).expect("valid json");
+
+        assert_eq!(json["user"], "john");
+        assert_eq!(json["username"], "john");
+        assert_eq!(json["age"], 30);
+        assert_eq!(json["meta"]["age"], 30);
+    }
+
+    #[test]
+    fn test_body_copy_nested_object() {
+        let req = create_post_request();
+        let body =
+            Some(br#"{"address":{"city":"Paris","zip":"75001"},"items":[{"id":1}]}"#.to_vec());
+
+        let mut config = BodyConfig::default();
+        config
+            .copy
+            .insert("/address".to_string(), "/billing/address".to_string());
+        config
+            .copy
+            .insert("/items/0".to_string(), "/items/-".to_string());
+
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
+        let json: Value =
+            serde_json::from_slice(&result.expect("should have body")).expect("valid json");
+
+        assert_eq!(json["address"]["city"], "Paris");
+        assert_eq!(json["billing"]["address"]["city"], "Paris");
+        assert_eq!(json["billing"]["address"]["zip"], "75001");
+        assert_eq!(json["items"], serde_json::json!([{"id": 1}, {"id": 1}]));
+    }
+
+    #[test]
+    fn test_body_copy_missing_source_is_noop() {
+        let req = create_post_request();
+        let body = Some(br#"{"user":"john"}"#.to_vec());
+
+        let mut config = BodyConfig::default();
+        config
+            .copy
+            .insert("/missing".to_string(), "/dest".to_string());
+        config
+            .copy
+            .insert("no-leading-slash".to_string(), "/dest2".to_string());
+
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
+        let json: Value =
+            serde_json::from_slice(&result.expect("should have body")).expect("valid json");
+
+        assert_eq!(json, serde_json::json!({"user": "john"}));
+    }
+
+    #[test]
+    fn test_body_copy_runs_after_rename_before_add() {
+        let req = create_post_request();
+        let body = Some(br#"{"userName":"john"}"#.to_vec());
+
+        let mut config = BodyConfig::default();
+        config
+            .rename
+            .insert("/userName".to_string(), "/user_name".to_string());
+        config
+            .copy
+            .insert("/user_name".to_string(), "/display".to_string());
+        config
+            .add
+            .insert("/display".to_string(), "overridden".to_string());
+
+        let result = transform_body(&body, &config, &req, BodyFormat::Json);
+        let json: Value =
+            serde_json::from_slice(&result.expect("should have body")).expect("valid json");
+
+        assert_eq!(json["user_name"], "john");
+        assert_eq!(json["display"], "overridden");
+    }
+
+    // -- Conditional (when) tests ------------------------------------------
+
+    fn when_plugin(when: &str) -> RequestTransformer {
+        serde_json::from_str(&format!(
+            r#"{{"when": {}, "headers": {{"add": {{"x-transformed": "yes"}}}}}}"#,
+            when
+        ))
+        .expect("valid json")
+    }
+
+    fn transformed(plugin: &mut RequestTransformer, req: Request) -> bool {
+        match plugin.on_request(req) {
+            Action::Continue(modified) => modified.headers.contains_key("x-transformed"),
+            _ => panic!("Expected Action::Continue"),
+        }
+    }
+
+    #[test]
+    fn test_when_matching_method() {
+        let mut plugin = when_plugin(r#"{"methods": ["post", "PUT"]}"#);
+
+        let mut post = create_test_request();
+        post.method = "POST".to_string();
+        assert!(transformed(&mut plugin, post));
+
+        // create_test_request is a GET.
+        assert!(!transformed(&mut plugin, create_test_request()));
+    }
+
+    #[test]
+    fn test_when_non_matching_header_skips_all_transforms() {
+        let mut plugin: RequestTransformer = serde_json::from_str(
+            r#"{
+                "when": {"header_equals": {"X-Tenant": "acme"}},
+                "headers": {"add": {"x-transformed": "yes"}},
+                "path": {"add_prefix": "/acme"}
+            }"#,
+        )
+        .expect("valid json");
+
+        let mut other = create_test_request();
+        other
+            .headers
+            .insert("x-tenant".to_string(), "globex".to_string());
+        match plugin.on_request(other.clone()) {
+            Action::Continue(modified) => {
+                assert_eq!(modified.path, other.path);
+                assert_eq!(modified.headers, other.headers);
+            }
+            _ => panic!("Expected Action::Continue"),
+        }
+
+        // A missing header doesn't match either.
+        assert!(!transformed(&mut plugin, create_test_request()));
+
+        let mut acme = create_test_request();
+        acme.headers
+            .insert("x-tenant".to_string(), "acme".to_string());
+        match plugin.on_request(acme) {
+            Action::Continue(modified) => assert_eq!(modified.path, "/acme/users/123"),
+            _ => panic!("Expected Action::Continue"),
+        }
+    }
+
+    #[test]
+    fn test_when_path_regex_gate() {
+        let mut plugin = when_plugin(r#"{"path_matches": "^/users/\\d+$"}"#);
+        assert!(transformed(&mut plugin, create_test_request()));
+
+        let mut other = create_test_request();
+        other.path = "/users/123/orders".to_string();
+        assert!(!transformed(&mut plugin, other));
+    }
+
+    #[test]
+    fn test_when_evaluates_original_request() {
+        // The path rewrite would make the gate fail if it were evaluated on
+        // mutated state.
+        let mut plugin: RequestTransformer = serde_json::from_str(
+            r#"{
+                "when": {"path_matches": "^/api/"},
+                "path": {"strip_prefix": "/api"},
+                "headers": {"add": {"x-transformed": "yes"}}
+            }"#,
+        )
+        .expect("valid json");
+        let mut req = create_test_request();
+        req.path = "/api/users".to_string();
+
+        match plugin.on_request(req) {
+            Action::Continue(modified) => {
+                assert_eq!(modified.path, "/users");
+                assert!(modified.headers.contains_key("x-transformed"));
+            }
+            _ => panic!("Expected Action::Continue"),
+        }
+    }
+
+    #[test]
+    fn test_when_invalid_path_regex_never_matches() {
+        let mut plugin = when_plugin(r#"{"path_matches": "(unclosed"}"#);
+        assert!(!transformed(&mut plugin, create_test_request()));
+    }
+
+    // -- content-length tests -----------------------------------------------
+
+    #[test]
+    fn test_body_edit_drops_content_length() {
+        let mut plugin: RequestTransformer = serde_json::from_value(serde_json::json!({
+            "body": { "remove": ["/password"] }
+        }))
+        .unwrap();
+
+        let mut req = create_test_request();
+        let body = br#"{"user":"john","password":"secret"}"#.to_vec();
+        req.headers
+            .insert("Content-Length".to_string(), body.len().to_string());
+        req.body = Some(body);
+
+        let result = match plugin.on_request(req) {
+            Action::Continue(r) => r,
+            _ => panic!("expected Continue"),
+        };
+        assert_eq!(
+            result.body.as_deref(),
+            Some(br#"{"user":"john"}"#.as_slice())
+        );
+        assert!(!result
+            .headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case("content-length")));
+    }
+
+    #[test]
+    fn test_content_length_kept_without_body_change() {
+        // A body transform that doesn't match anything leaves the body as-is.
+        let mut plugin: RequestTransformer = serde_json::from_value(serde_json::json!({
+            "headers": { "add": { "x-seen": "1" } },
+            "body": { "remove": ["/missing"] }
+        }))
+        .unwrap();
+
+        let mut req = create_test_request();
+        req.headers
+            .insert("content-length".to_string(), "15".to_string());
+        req.body = Some(br#"{"user":"john"}"#.to_vec());
+
+        let result = match plugin.on_request(req) {
+            Action::Continue(r) => r,
+            _ => panic!("expected Continue"),
+        };
+        assert_eq!(
+            result.headers.get("content-length").map(String::as_str),
+            Some("15")
+        );
+    }
+
+    // -- JSON Patch tests ---------------------------------------------------
+
+    fn patch_request(patch: &str, body: &str) -> Option<Value> {
+        let mut plugin: RequestTransformer =
+            serde_json::from_str(&format!(r#"{{"json_patch": {}}}"#, patch)).expect("valid json");
+        let mut req = create_test_request();
+        req.body = Some(body.as_bytes().to_vec());
+
+        match plugin.on_request(req) {
+            Action::Continue(modified) => modified
+                .body
+                .map(|b| serde_json::from_slice(&b).expect("json body")),
+            _ => panic!("Expected Action::Continue"),
+        }
+    }
+
+    #[test]
+    fn test_json_patch_add_remove_replace() {
+        let body = patch_request(
+            r#"[
+                {"op": "add", "path": "/tags/-", "value": "new"},
+                {"op": "remove", "path": "/password"},
+                {"op": "replace", "path": "/name", "value": "Jane"}
+            ]"#,
+            r#"{"name": "John", "password": "secret", "tags": ["a"]}"#,
+        );
+        assert_eq!(
+            body,
+            Some(serde_json::json!({"name": "Jane", "tags": ["a", "new"]}))
+        );
+    }
+
+    #[test]
+    fn test_json_patch_failed_test_leaves_body_unchanged() {
+        let body = patch_request(
+            r#"[
+                {"op": "replace", "path": "/name", "value": "Jane"},
+                {"op": "test", "path": "/role", "value": "admin"},
+                {"op": "remove", "path": "/role"}
+            ]"#,
+            r#"{"name": "John", "role": "user"}"#,
+        );
+        assert_eq!(
+            body,
+            Some(serde_json::json!({"name": "John", "role": "user"}))
+        );
+    }
+
+    #[test]
+    fn test_json_patch_takes_precedence_over_body() {
+        let body = patch_request(
+            r#"[{"op": "move", "from": "/userName", "path": "/user_name"}], "body": {"add": {"/extra": "x"}}"#,
+            r#"{"userName": "john"}"#,
+        );
+        assert_eq!(body, Some(serde_json::json!({"user_name": "john"})));
+    }
+
+    #[test]
+    fn test_json_patch_invalid_document_leaves_body_unchanged() {
+        let body = patch_request(
+            r#"[{"op": "frobnicate", "path": "/name"}]"#,
+            r#"{"name": "John"}"#,
+        );
+        assert_eq!(body, Some(serde_json::json!({"name": "John"})));
+    }
+
+    // -- max_body_bytes tests -----------------------------------------------
+
+    fn limited_plugin(max: usize) -> RequestTransformer {
+        serde_json::from_value(serde_json::json!({
+            "max_body_bytes": max,
+            "body": {"add": {"/added": "yes"}}
+        }))
+        .expect("valid json")
+    }
+
+    #[test]
+    fn test_max_body_bytes_under_limit_transforms() {
+        let body = br#"{"name":"John"}"#;
+        let mut plugin = limited_plugin(body.len());
+        let mut req = create_test_request();
+        req.body = Some(body.to_vec());
+
+        let Action::Continue(modified) = plugin.on_request(req) else {
+            panic!("Expected Action::Continue");
+        };
+        let json: Value = serde_json::from_slice(&modified.body.expect("body")).expect("json");
+        assert_eq!(json, serde_json::json!({"name": "John", "added": "yes"}));
+    }
+
+    #[test]
+    fn test_max_body_bytes_over_limit_passes_through() {
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        let body = br#"{"name":"John"}"#;
+        let mut plugin = limited_plugin(body.len() - 1);
+        let mut req = create_test_request();
+        req.body = Some(body.to_vec());
+
+        let Action::Continue(modified) = plugin.on_request(req) else {
+            panic!("Expected Action::Continue");
+        };
+        assert_eq!(modified.body.as_deref(), Some(&body[..]));
+        let logs = host.logs();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0, 1);
+        assert!(logs[0].1.contains("max_body_bytes"), "{}", logs[0].1);
+    }
+
+    // -- body_format tests --------------------------------------------------
+
+    fn yaml_plugin(config: Value) -> RequestTransformer {
+        serde_json::from_value(config).expect("valid json")
+    }
+
+    fn yaml_request(content_type: &str, body: &str) -> Request {
+        let mut req = create_test_request();
+        req.headers
+            .insert("content-type".to_string(), content_type.to_string());
+        req.body = Some(body.as_bytes().to_vec());
+        req
+    }
+
+    #[test]
+    fn test_yaml_body_detected_from_content_type() {
+        let mut plugin = yaml_plugin(serde_json::json!({
+            "body": {
+                "add": {"/user/role": "admin"},
+                "remove": ["/user/password"],
+                "rename": {"/user/name": "/user/full_name"}
+            }
+        }));
+        let req = yaml_request(
+            "application/yaml; charset=utf-8",
+            "user:\n  name: John\n  password: secret\n",
+        );
+
+        let Action::Continue(modified) = plugin.on_request(req) else {
+            panic!("Expected Action::Continue");
+        };
+        let body = modified.body.expect("body");
+        assert!(serde_json::from_slice::<Value>(&body).is_err());
+        let yaml: Value = serde_yaml::from_slice(&body).expect("yaml");
+        assert_eq!(
+            yaml,
+            serde_json::json!({"user": {"full_name": "John", "role": "admin"}})
+        );
+    }
+
+    #[test]
+    fn test_yaml_body_format_from_config() {
+        let mut plugin = yaml_plugin(serde_json::json!({
+            "body_format": "yaml",
+            "json_patch": [{"op": "replace", "path": "/user/name", "value": "Jane"}]
+        }));
+        let req = yaml_request("text/plain", "user:\n  name: John\n");
+
+        let Action::Continue(modified) = plugin.on_request(req) else {
+            panic!("Expected Action::Continue");
+        };
+        let yaml: Value = serde_yaml::from_slice(&modified.body.expect("body")).expect("yaml");
+        assert_eq!(yaml, serde_json::json!({"user": {"name": "Jane"}}));
+    }
+
+    #[test]
+    fn test_non_json_non_yaml_body_passes_through() {
+        let mut plugin = yaml_plugin(serde_json::json!({
+            "body": {"add": {"/added": "yes"}}
+        }));
+        let req = yaml_request("application/yaml", "{ not: [valid");
+
+        let Action::Continue(modified) = plugin.on_request(req) else {
+            panic!("Expected Action::Continue");
+        };
+        assert_eq!(modified.body.as_deref(), Some(&b"{ not: [valid"[..]));
+    }
+
+    #[test]
+    fn test_body_format_detect() {
+        let headers = |ct: &str| BTreeMap::from([("Content-Type".to_string(), ct.to_string())]);
+        assert_eq!(BodyFormat::detect(&headers("text/yaml")), BodyFormat::Yaml);
+        assert_eq!(
+            BodyFormat::detect(&headers("application/vnd.api+yaml")),
+            BodyFormat::Yaml
+        );
+        assert_eq!(
+            BodyFormat::detect(&headers("application/json")),
+            BodyFormat::Json
+        );
+        assert_eq!(BodyFormat::detect(&BTreeMap::new()), BodyFormat::Json);
+    }
+
     // -- to_json_value tests ------------------------------------------------
 
     #[test]
@@ -1391,11 +2541,13 @@ mod tests {
         // Verify that body interpolation reads from the original request,
         // not the mutated one (query-to-body use case).
         let mut plugin = RequestTransformer {
+            when: None,
             headers: None,
             querystring: Some(QueryConfig {
                 add: BTreeMap::new(),
                 remove: vec!["userId".to_string()],
                 rename: BTreeMap::new(),
+                allow_reserved: vec![],
             }),
             path: None,
             body: Some(BodyConfig {
@@ -1406,7 +2558,13 @@ mod tests {
                 },
                 remove: vec![],
                 rename: BTreeMap::new(),
+                copy: BTreeMap::new(),
             }),
+            forwarded: None,
+            json_patch: None,
+            max_body_bytes: None,
+            body_format: None,
+            compiled_patch: OnceCell::new(),
             compiled_replace: None,
         };
 
@@ -1439,17 +2597,23 @@ mod tests {
     #[test]
     fn test_on_request_lazy_regex_compilation() {
         let mut plugin = RequestTransformer {
+            when: None,
             headers: None,
             querystring: None,
             path: Some(PathConfig {
                 strip_prefix: None,
                 add_prefix: None,
-                replace: Some(PathReplaceConfig {
+                replace: Some(PathReplace::Single(PathReplaceConfig {
                     pattern: r"/v\d+".to_string(),
                     replacement: "".to_string(),
-                }),
+                })),
             }),
             body: None,
+            forwarded: None,
+            json_patch: None,
+            max_body_bytes: None,
+            body_format: None,
+            compiled_patch: OnceCell::new(),
             compiled_replace: None,
         };
 
@@ -1476,6 +2640,57 @@ mod tests {
         assert!(plugin.compiled_replace.is_some());
     }
 
+    #[test]
+    fn test_config_deserialization_path_replace_forms() {
+        let single: RequestTransformer = serde_json::from_str(
+            r#"{"path": {"replace": {"pattern": "/v1", "replacement": "/v2"}}}"#,
+        )
+        .expect("valid json");
+        let replace = single.path.and_then(|p| p.replace).expect("set above");
+        assert!(matches!(replace, PathReplace::Single(_)));
+        assert_eq!(replace.rules().len(), 1);
+        assert_eq!(replace.rules()[0].replacement, "/v2");
+
+        let chain: RequestTransformer = serde_json::from_str(
+            r#"{"path": {"replace": [
+                {"pattern": "/v1", "replacement": "/v2"},
+                {"pattern": "//", "replacement": "/"}
+            ]}}"#,
+        )
+        .expect("valid json");
+        let replace = chain.path.and_then(|p| p.replace).expect("set above");
+        assert!(matches!(replace, PathReplace::Chain(_)));
+        let patterns: Vec<&str> = replace.rules().iter().map(|r| r.pattern.as_str()).collect();
+        assert_eq!(patterns, vec!["/v1", "//"]);
+    }
+
+    #[test]
+    fn test_on_request_path_replace_chain() {
+        let mut plugin: RequestTransformer = serde_json::from_str(
+            r#"{"path": {"replace": [
+                {"pattern": "^/api/v\\d+", "replacement": "/api"},
+                {"pattern": "/users/(\\d+)", "replacement": "/accounts/$1"}
+            ]}}"#,
+        )
+        .expect("valid json");
+
+        let req = Request {
+            method: "GET".to_string(),
+            path: "/api/v3/users/42".to_string(),
+            query: None,
+            headers: BTreeMap::new(),
+            body: None,
+            client_ip: "127.0.0.1".to_string(),
+            path_params: BTreeMap::new(),
+        };
+
+        match plugin.on_request(req) {
+            Action::Continue(modified) => assert_eq!(modified.path, "/api/accounts/42"),
+            _ => panic!("Expected Action::Continue"),
+        }
+        assert_eq!(plugin.compiled_replace.as_ref().map(Vec::len), Some(2));
+    }
+
     #[test]
     fn test_config_deserialization_defaults() {
         let config: RequestTransformer = serde_json::from_str("{}").expect("valid json");
@@ -1483,6 +2698,9 @@ mod tests {
         assert!(config.querystring.is_none());
         assert!(config.path.is_none());
         assert!(config.body.is_none());
+        assert!(config.forwarded.is_none());
+        assert!(config.json_patch.is_none());
+        assert!(config.when.is_none());
     }
 
     #[test]
@@ -1491,6 +2709,7 @@ mod tests {
             "headers": {
                 "add": {"x-gateway": "barbacane"},
                 "set": {"x-default": "value"},
+                "append": {"via": "1.1 barbacane"},
                 "remove": ["authorization"],
                 "rename": {"x-old": "x-new"}
             },
@@ -1519,6 +2738,7 @@ mod tests {
         let h = config.headers.expect("set above");
         assert_eq!(h.add.get("x-gateway"), Some(&"barbacane".to_string()));
         assert_eq!(h.set.get("x-default"), Some(&"value".to_string()));
+        assert_eq!(h.append.get("via"), Some(&"1.1 barbacane".to_string()));
         assert_eq!(h.remove, vec!["authorization"]);
         assert_eq!(h.rename.get("x-old"), Some(&"x-new".to_string()));
     }
@@ -1,15 +1,29 @@
 //! Response transformer middleware plugin for Barbacane API gateway.
 //!
 //! Provides declarative response transformations for:
-//! - Status code mapping (e.g., 200 → 201, 400 → 403)
+//! - Status code mapping (e.g., 200 → 201, 400 → 403, or a whole class: 2xx → 200)
 //! - Headers (add, set, remove, rename)
 //! - JSON body (add, remove, rename using JSON Pointer — RFC 6901)
+//! - JSON body via a JSON Patch document (RFC 6902), replacing the pointer ops
+//! - Whole-body replacement (`set_body`), which the body edits then apply to
+//! - YAML bodies, edited through the same JSON value representation
+//!
+//! `status_range` (`"200"`, `"4xx"`, `"500-599"`) scopes the header and body
+//! transformations to matching upstream status codes. `only_when_accept`
+//! scopes the body transformations to requests whose `Accept` header names
+//! one of the configured media types.
+//!
+//! Body `add` values support variable interpolation against the upstream
+//! response: `$status`, `$header.<name>`.
 
 use barbacane_plugin_sdk::log::log as log_message;
 use barbacane_plugin_sdk::prelude::*;
+use barbacane_plugin_sdk::transform::{self, BodyFormat, Patch};
 use jsonptr::{Assign, Delete, Pointer};
+use regex::Regex;
 use serde::Deserialize;
 use serde_json::Value;
+use std::cell::OnceCell;
 use std::collections::BTreeMap;
 
 // ---------------------------------------------------------------------------
@@ -25,11 +39,41 @@ struct HeaderConfig {
     #[serde(default)]
     set: BTreeMap<String, String>,
 
+    /// Header names, or `~`-prefixed regexes matched against lowercased names.
     #[serde(default)]
     remove: Vec<String>,
 
     #[serde(default)]
     rename: BTreeMap<String, String>,
+
+    /// `~` entries of `remove`, compiled on first use.
+    #[serde(skip)]
+    remove_patterns: OnceCell<Vec<Regex>>,
+}
+
+impl HeaderConfig {
+    /// Compiled `~pattern` entries from `remove`. Invalid patterns are logged
+    /// and skipped.
+    fn remove_patterns(&self) -> &[Regex] {
+        self.remove_patterns.get_or_init(|| {
+            self.remove
+                .iter()
+                .filter_map(|entry| {
+                    let pattern = entry.strip_prefix('~')?;
+                    match Regex::new(pattern) {
+                        Ok(re) => Some(re),
+                        Err(e) => {
+                            log_message(
+                                0,
+                                &format!("Invalid header remove pattern '{}': {}", pattern, e),
+                            );
+                            None
+                        }
+                    }
+                })
+                .collect()
+        })
+    }
 }
 
 /// Body transformation configuration (JSON Pointer).
@@ -45,6 +89,73 @@ struct BodyConfig {
     rename: BTreeMap<String, String>,
 }
 
+/// Upstream status codes a `status_range` selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusMatcher {
+    /// `"404"`
+    Exact(u16),
+    /// `"4xx"`: the hundred's digit.
+    Class(u16),
+    /// `"500-599"`, inclusive.
+    Range(u16, u16),
+}
+
+impl StatusMatcher {
+    /// Parse `"200"`, `"4xx"` (case-insensitive) or `"500-599"`.
+    fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim().to_ascii_lowercase();
+        if let Some((low, high)) = spec.split_once('-') {
+            let low = low.trim().parse().ok()?;
+            let high = high.trim().parse().ok()?;
+            return (low <= high).then_some(StatusMatcher::Range(low, high));
+        }
+        if let Some(class) = spec.strip_suffix("xx") {
+            return match class.parse() {
+                Ok(digit @ 1..=9) => Some(StatusMatcher::Class(digit)),
+                _ => None,
+            };
+        }
+        spec.parse().ok().map(StatusMatcher::Exact)
+    }
+
+    fn matches(self, status: u16) -> bool {
+        match self {
+            StatusMatcher::Exact(code) => status == code,
+            StatusMatcher::Class(digit) => status / 100 == digit,
+            StatusMatcher::Range(low, high) => (low..=high).contains(&status),
+        }
+    }
+}
+
+/// The `status` table with its keys parsed: exact codes (`"404"`) and
+/// classes (`"4xx"`). Exact entries win over class entries.
+#[derive(Debug, Default)]
+struct StatusMapping {
+    exact: BTreeMap<u16, u16>,
+    /// Keyed by the hundred's digit.
+    class: BTreeMap<u16, u16>,
+}
+
+impl StatusMapping {
+    /// Parse the configured table. Keys that are neither a code nor a class
+    /// (including ranges, which `status_range` accepts) are logged and ignored.
+    fn new(mapping: &BTreeMap<String, u16>) -> Self {
+        let mut parsed = Self::default();
+        for (key, &to) in mapping {
+            match StatusMatcher::parse(key) {
+                Some(StatusMatcher::Exact(code)) => {
+                    parsed.exact.insert(code, to);
+                }
+                Some(StatusMatcher::Class(digit)) => {
+                    parsed.class.insert(digit, to);
+                }
+                _ => log_message(0, &format!("Invalid status mapping key '{}'", key)),
+            }
+        }
+        parsed
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Plugin struct
 // ---------------------------------------------------------------------------
@@ -57,16 +168,67 @@ pub struct ResponseTransformer {
     #[serde(default)]
     status: Option<BTreeMap<String, u16>>,
 
+    /// `status`, parsed on first use.
+    #[serde(skip)]
+    status_mapping: OnceCell<StatusMapping>,
+
+    /// Apply header and body transformations only to upstream statuses in
+    /// this range (`"200"`, `"4xx"`, `"500-599"`).
+    #[serde(default)]
+    status_range: Option<String>,
+
+    /// `status_range`, parsed on first use; `None` if it is invalid.
+    #[serde(skip)]
+    status_matcher: OnceCell<Option<StatusMatcher>>,
+
     #[serde(default)]
     headers: Option<HeaderConfig>,
 
     #[serde(default)]
     body: Option<BodyConfig>,
+
+    /// JSON document that replaces the response body before `json_patch` or
+    /// `body` edits run.
+    #[serde(default)]
+    set_body: Option<Value>,
+
+    /// Format of bodies edited by `body` and `json_patch`. Unset, it is
+    /// detected from the `content-type` header (YAML types, else JSON).
+    #[serde(default)]
+    body_format: Option<BodyFormat>,
+
+    /// RFC 6902 patch document applied to JSON bodies. Takes precedence
+    /// over `body`.
+    #[serde(default)]
+    json_patch: Option<Vec<Value>>,
+
+    /// Skip body transforms (`body` and `json_patch`) for bodies larger than
+    /// this many bytes instead of parsing them. Unset means no limit.
+    #[serde(default)]
+    max_body_bytes: Option<usize>,
+
+    /// `json_patch`, parsed on first use; `None` if the document is invalid.
+    #[serde(skip)]
+    compiled_patch: OnceCell<Option<Patch>>,
+
+    /// Apply body transforms (`set_body`, `json_patch`, `body`) only when the
+    /// request's `Accept` header names one of these media types.
+    #[serde(default)]
+    only_when_accept: Option<Vec<String>>,
+
+    /// `Accept` header of the request being handled, captured in
+    /// `on_request` and overwritten for every request.
+    #[serde(skip)]
+    request_accept: Option<String>,
 }
 
 impl ResponseTransformer {
-    /// Pass through requests unchanged (response-transformer only).
+    /// Pass through requests unchanged, remembering their `Accept` header
+    /// for `only_when_accept`.
     pub fn on_request(&mut self, req: Request) -> Action<Request> {
+        // Always overwrite, so a request without `Accept` never inherits the
+        // previous request's value.
+        self.request_accept = req.headers.get("accept").cloned();
         Action::Continue(req)
     }
 
@@ -75,34 +237,215 @@ impl ResponseTransformer {
     /// Transformations are applied in this order:
     /// 1. Status code mapping
     /// 2. Headers
-    /// 3. Body
+    /// 3. Body replacement (`set_body`)
+    /// 4. Body edits (`json_patch`, or else `body`); `content-length` is
+    ///    dropped when the body changes
+    ///
+    /// Variable interpolation reads from the **upstream** response, so status
+    /// mapping and header changes don't affect resolved values. `status_range`
+    /// is also checked against the upstream status and gates steps 2 to 4;
+    /// `only_when_accept` additionally gates steps 3 and 4.
     pub fn on_response(&mut self, mut resp: Response) -> Response {
+        // Snapshot status and headers for interpolation; the body is never
+        // referenced by variables, so it isn't copied.
+        let original = Response {
+            status: resp.status,
+            headers: resp.headers.clone(),
+            body: None,
+        };
+
         if let Some(status_map) = &self.status {
-            resp.status = transform_status(resp.status, status_map);
+            let mapping = self
+                .status_mapping
+                .get_or_init(|| StatusMapping::new(status_map));
+            resp.status = transform_status(resp.status, mapping);
+        }
+
+        if !self.status_in_range(original.status) {
+            return resp;
         }
 
         if let Some(header_config) = &self.headers {
             transform_headers(&mut resp.headers, header_config);
         }
 
-        if let Some(body_config) = &self.body {
-            resp.body = transform_body(&resp.body, body_config);
+        if !self.accept_matches() {
+            return resp;
+        }
+
+        // The replacement is always JSON, and not subject to `max_body_bytes`
+        // since the upstream body is discarded unparsed.
+        let replaced = match &self.set_body {
+            Some(replacement) => {
+                resp.body = Some(replacement.to_string().into_bytes());
+                ensure_json_content_type(&mut resp.headers);
+                true
+            }
+            None => false,
+        };
+        let mut body_changed = replaced;
+
+        let transforms_body = self.json_patch.is_some() || self.body.is_some();
+        if transforms_body
+            && (replaced
+                || transform::body_within_limit(
+                    self.max_body_bytes,
+                    resp.body.as_deref(),
+                    "response",
+                ))
+        {
+            let format = match self.body_format {
+                _ if replaced => BodyFormat::Json,
+                Some(format) => format,
+                None => BodyFormat::detect(&original.headers),
+            };
+            let edited = if self.json_patch.is_some() {
+                self.compiled_patch()
+                    .map(|patch| transform::apply_json_patch(&resp.body, patch, format))
+            } else {
+                self.body
+                    .as_ref()
+                    .map(|config| transform_body(&resp.body, config, &original, format))
+            };
+            if let Some(edited) = edited.filter(|edited| *edited != resp.body) {
+                resp.body = edited;
+                body_changed = true;
+            }
+        }
+
+        if body_changed {
+            transform::remove_content_length(&mut resp.headers);
         }
 
         resp
     }
+
+    /// Whether `status` falls in the configured `status_range`. Without one
+    /// every status matches; an invalid range is logged once and matches none.
+    fn status_in_range(&self, status: u16) -> bool {
+        let Some(range) = &self.status_range else {
+            return true;
+        };
+        self.status_matcher
+            .get_or_init(|| {
+                let matcher = StatusMatcher::parse(range);
+                if matcher.is_none() {
+                    log_message(0, &format!("Invalid status_range '{}'", range));
+                }
+                matcher
+            })
+            .is_some_and(|matcher| matcher.matches(status))
+    }
+
+    /// Whether the request's `Accept` header allows body transforms. Without
+    /// `only_when_accept` every request does; with it, the header must list a
+    /// configured media type explicitly (wildcard ranges like `*/*` don't
+    /// count, and neither do ranges with `q=0`).
+    fn accept_matches(&self) -> bool {
+        let Some(wanted) = &self.only_when_accept else {
+            return true;
+        };
+        let Some(accept) = &self.request_accept else {
+            return false;
+        };
+        accept.split(',').any(|range| {
+            let mut parts = range.split(';');
+            let media_type = parts.next().unwrap_or("").trim();
+            let refused = parts.any(|param| {
+                param
+                    .split_once('=')
+                    .is_some_and(|(k, v)| k.trim() == "q" && v.trim().parse() == Ok(0.0))
+            });
+            !refused
+                && !media_type.contains('*')
+                && wanted.iter().any(|w| media_type_matches(w, media_type))
+        })
+    }
+
+    /// The parsed `json_patch` document. An invalid document is logged once
+    /// and leaves bodies unchanged.
+    fn compiled_patch(&self) -> Option<&Patch> {
+        self.compiled_patch
+            .get_or_init(|| {
+                let ops = Value::Array(self.json_patch.clone()?);
+                serde_json::from_value(ops)
+                    .map_err(|e| log_message(0, &format!("Invalid JSON Patch document: {}", e)))
+                    .ok()
+            })
+            .as_ref()
+    }
+}
+
+/// Whether `media_type` (from `Accept`) matches the configured `pattern`,
+/// case-insensitively. The pattern may be `type/*` or `*/*`; parameters on
+/// either side are ignored.
+fn media_type_matches(pattern: &str, media_type: &str) -> bool {
+    let pattern = pattern.split(';').next().unwrap_or("").trim();
+    let Some((p_type, p_sub)) = pattern.split_once('/') else {
+        return false;
+    };
+    let Some((m_type, m_sub)) = media_type.split_once('/') else {
+        return false;
+    };
+    (p_type == "*" || p_type.eq_ignore_ascii_case(m_type))
+        && (p_sub == "*" || p_sub.eq_ignore_ascii_case(m_sub))
 }
 
 // ---------------------------------------------------------------------------
-// Status code mapping
+// Variable interpolation
 // ---------------------------------------------------------------------------
 
-/// Map a status code using the configured mapping table.
+/// Interpolate a value template with response data.
 ///
-/// Keys are stringified status codes (from JSON/YAML deserialization).
-/// Unmapped codes pass through unchanged.
-fn transform_status(status: u16, mapping: &BTreeMap<String, u16>) -> u16 {
-    mapping.get(&status.to_string()).copied().unwrap_or(status)
+/// Supported variables:
+/// - `$status` — upstream status code
+/// - `$header.<name>` — upstream response header (case-insensitive)
+///
+/// Returns the resolved value, or an empty string if the variable cannot be
+/// resolved. Non-variable templates are returned as-is.
+fn interpolate_response_value(template: &str, resp: &Response) -> String {
+    if template == "$status" {
+        return resp.status.to_string();
+    }
+
+    if let Some(header_name) = template.strip_prefix("$header.") {
+        return resp
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(header_name))
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+    }
+
+    template.to_string()
+}
+
+/// Check if a template string contains a variable reference.
+fn is_variable(template: &str) -> bool {
+    template.starts_with('$')
+}
+
+/// Parse a string as a JSON value, falling back to a JSON string.
+///
+/// This preserves numeric/boolean types when interpolating variable values
+/// into JSON bodies (e.g. `$status` = `"404"` becomes the JSON number `404`).
+fn to_json_value(s: &str) -> Value {
+    serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// Status code mapping
+// ---------------------------------------------------------------------------
+
+/// Map a status code using the configured mapping table: an exact entry
+/// first, then the code's class. Unmapped codes pass through unchanged.
+fn transform_status(status: u16, mapping: &StatusMapping) -> u16 {
+    mapping
+        .exact
+        .get(&status)
+        .or_else(|| mapping.class.get(&(status / 100)))
+        .copied()
+        .unwrap_or(status)
 }
 
 // ---------------------------------------------------------------------------
@@ -122,7 +465,17 @@ fn transform_headers(headers: &mut BTreeMap<String, String>, config: &HeaderConf
         .collect();
 
     for header_name in &config.remove {
-        headers.remove(&header_name.to_lowercase());
+        if !header_name.starts_with('~') {
+            headers.remove(&header_name.to_lowercase());
+        }
+    }
+
+    let patterns = config.remove_patterns();
+    if !patterns.is_empty() {
+        headers.retain(|name, _| {
+            let name = name.to_lowercase();
+            !patterns.iter().any(|re| re.is_match(&name))
+        });
     }
 
     for (old_name, new_name) in &config.rename {
@@ -146,21 +499,47 @@ fn transform_headers(headers: &mut BTreeMap<String, String>, config: &HeaderConf
 // Body transformations (JSON Pointer — RFC 6901)
 // ---------------------------------------------------------------------------
 
+/// Set `content-type: application/json` unless the response already declares
+/// a JSON type (`application/json`, `application/problem+json`, ...).
+fn ensure_json_content_type(headers: &mut BTreeMap<String, String>) {
+    let is_json = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .and_then(|(_, value)| value.split(';').next())
+        .map(|mime| {
+            let mime = mime.trim().to_ascii_lowercase();
+            mime == "application/json" || mime.ends_with("+json")
+        })
+        .unwrap_or(false);
+    if !is_json {
+        headers.retain(|name, _| !name.eq_ignore_ascii_case("content-type"));
+        headers.insert("content-type".to_string(), "application/json".to_string());
+    }
+}
+
 /// Transform JSON body. Order: remove → rename → add.
 ///
 /// Non-JSON bodies are returned unchanged.
-fn transform_body(body: &Option<Vec<u8>>, config: &BodyConfig) -> Option<Vec<u8>> {
+fn transform_body(
+    body: &Option<Vec<u8>>,
+    config: &BodyConfig,
+    original: &Response,
+    format: BodyFormat,
+) -> Option<Vec<u8>> {
     let body_bytes = match body {
         Some(b) if !b.is_empty() => b,
         _ => return body.clone(),
     };
 
-    let mut json: Value = match serde_json::from_slice(body_bytes) {
-        Ok(v) => v,
-        Err(_) => {
-            log_message(1, "Body is not valid JSON, skipping body transformations");
-            return body.clone();
-        }
+    let Some(mut json) = format.parse(body_bytes) else {
+        log_message(
+            1,
+            &format!(
+                "Body is not valid {}, skipping body transformations",
+                format.name()
+            ),
+        );
+        return body.clone();
     };
 
     for pointer_str in &config.remove {
@@ -218,8 +597,15 @@ fn transform_body(body: &Option<Vec<u8>>, config: &BodyConfig) -> Option<Vec<u8>
         }
     }
 
-    for (pointer_str, value_str) in &config.add {
-        let json_value = Value::String(value_str.clone());
+    for (pointer_str, value_template) in &config.add {
+        let interpolated = interpolate_response_value(value_template, original);
+        // Variables keep their JSON type ($status → number); literal config
+        // values stay strings.
+        let json_value = if is_variable(value_template) {
+            to_json_value(&interpolated)
+        } else {
+            Value::String(interpolated)
+        };
 
         match Pointer::parse(pointer_str) {
             Ok(ptr) => {
@@ -233,12 +619,16 @@ fn transform_body(body: &Option<Vec<u8>>, config: &BodyConfig) -> Option<Vec<u8>
         }
     }
 
-    match serde_json::to_vec(&json) {
+    match format.serialize(&json) {
         Ok(v) => Some(v),
         Err(e) => {
             log_message(
                 0,
-                &format!("Failed to serialize JSON after transformation: {}", e),
+                &format!(
+                    "Failed to serialize {} after transformation: {}",
+                    format.name(),
+                    e
+                ),
             );
             body.clone()
         }
@@ -273,7 +663,7 @@ mod tests {
         let mut mapping = BTreeMap::new();
         mapping.insert("200".to_string(), 201);
 
-        assert_eq!(transform_status(200, &mapping), 201);
+        assert_eq!(transform_status(200, &StatusMapping::new(&mapping)), 201);
     }
 
     #[test]
@@ -281,7 +671,7 @@ mod tests {
         let mut mapping = BTreeMap::new();
         mapping.insert("200".to_string(), 201);
 
-        assert_eq!(transform_status(404, &mapping), 404);
+        assert_eq!(transform_status(404, &StatusMapping::new(&mapping)), 404);
     }
 
     #[test]
@@ -291,17 +681,65 @@ mod tests {
         mapping.insert("400".to_string(), 403);
         mapping.insert("500".to_string(), 503);
 
-        assert_eq!(transform_status(200, &mapping), 201);
-        assert_eq!(transform_status(400, &mapping), 403);
-        assert_eq!(transform_status(500, &mapping), 503);
-        assert_eq!(transform_status(302, &mapping), 302);
+        assert_eq!(transform_status(200, &StatusMapping::new(&mapping)), 201);
+        assert_eq!(transform_status(400, &StatusMapping::new(&mapping)), 403);
+        assert_eq!(transform_status(500, &StatusMapping::new(&mapping)), 503);
+        assert_eq!(transform_status(302, &StatusMapping::new(&mapping)), 302);
     }
 
     #[test]
     fn test_status_mapping_empty() {
         let mapping = BTreeMap::new();
 
-        assert_eq!(transform_status(200, &mapping), 200);
+        assert_eq!(transform_status(200, &StatusMapping::new(&mapping)), 200);
+    }
+
+    #[test]
+    fn test_status_mapping_exact_wins_over_class() {
+        let mut mapping = BTreeMap::new();
+        mapping.insert("2xx".to_string(), 200);
+        mapping.insert("201".to_string(), 202);
+        let mapping = StatusMapping::new(&mapping);
+
+        assert_eq!(transform_status(201, &mapping), 202);
+        assert_eq!(transform_status(204, &mapping), 200);
+    }
+
+    #[test]
+    fn test_status_mapping_class_only() {
+        let mut mapping = BTreeMap::new();
+        mapping.insert("5XX".to_string(), 503);
+        let mapping = StatusMapping::new(&mapping);
+
+        assert_eq!(transform_status(500, &mapping), 503);
+        assert_eq!(transform_status(504, &mapping), 503);
+        assert_eq!(transform_status(404, &mapping), 404);
+    }
+
+    #[test]
+    fn test_status_mapping_invalid_keys_ignored() {
+        let mut mapping = BTreeMap::new();
+        mapping.insert("500-599".to_string(), 503);
+        mapping.insert("nope".to_string(), 200);
+        let mapping = StatusMapping::new(&mapping);
+
+        assert_eq!(transform_status(502, &mapping), 502);
+    }
+
+    #[test]
+    fn test_on_response_class_status_mapping() {
+        let mut plugin: ResponseTransformer = serde_json::from_value(serde_json::json!({
+            "status": { "2xx": 200, "201": 201 }
+        }))
+        .unwrap();
+
+        let mut resp = create_test_response();
+        resp.status = 204;
+        assert_eq!(plugin.on_response(resp).status, 200);
+
+        let mut resp = create_test_response();
+        resp.status = 201;
+        assert_eq!(plugin.on_response(resp).status, 201);
     }
 
     // -- Header transformation tests ----------------------------------------
@@ -389,6 +827,42 @@ mod tests {
         assert_eq!(headers.get("x-powered-by"), None);
     }
 
+    #[test]
+    fn test_headers_remove_pattern() {
+        let mut headers = BTreeMap::new();
+        headers.insert("X-Internal-Trace".to_string(), "abc".to_string());
+        headers.insert("x-internal-node".to_string(), "n1".to_string());
+        headers.insert("x-public".to_string(), "keep".to_string());
+        headers.insert("server".to_string(), "nginx".to_string());
+
+        let mut config = HeaderConfig::default();
+        config.remove.push("~^x-internal-".to_string());
+        config.remove.push("server".to_string());
+
+        transform_headers(&mut headers, &config);
+
+        assert_eq!(headers.get("x-internal-trace"), None);
+        assert_eq!(headers.get("x-internal-node"), None);
+        assert_eq!(headers.get("server"), None);
+        assert_eq!(headers.get("x-public"), Some(&"keep".to_string()));
+    }
+
+    #[test]
+    fn test_headers_remove_invalid_pattern_skipped() {
+        let mut headers = BTreeMap::new();
+        headers.insert("x-internal-node".to_string(), "n1".to_string());
+        headers.insert("server".to_string(), "nginx".to_string());
+
+        let mut config = HeaderConfig::default();
+        config.remove.push("~x-internal-(".to_string());
+        config.remove.push("server".to_string());
+
+        transform_headers(&mut headers, &config);
+
+        assert_eq!(headers.get("x-internal-node"), Some(&"n1".to_string()));
+        assert_eq!(headers.get("server"), None);
+    }
+
     #[test]
     fn test_headers_rename() {
         let resp = create_test_response();
@@ -500,7 +974,7 @@ mod tests {
             .add
             .insert("/gateway".to_string(), "barbacane".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -520,7 +994,7 @@ mod tests {
             .add
             .insert("/metadata/version".to_string(), "1.0".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -536,7 +1010,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.add.insert("/status".to_string(), "new".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -550,7 +1024,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.remove.push("/password".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -566,7 +1040,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.remove.push("/metadata/internal".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -584,7 +1058,7 @@ mod tests {
             .rename
             .insert("/userName".to_string(), "/user_name".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -603,7 +1077,7 @@ mod tests {
             "/metadata/newName".to_string(),
         );
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -625,7 +1099,7 @@ mod tests {
             .insert("/toOverwrite".to_string(), "new".to_string());
         config.add.insert("/added".to_string(), "value".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -642,7 +1116,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.add.insert("/field".to_string(), "value".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         assert_eq!(result, Some(b"not json".to_vec()));
     }
 
@@ -653,7 +1127,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.add.insert("/field".to_string(), "value".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         assert_eq!(result, None);
     }
 
@@ -664,7 +1138,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.add.insert("/field".to_string(), "value".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         assert_eq!(result, Some(Vec::new()));
     }
 
@@ -675,7 +1149,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.remove.push("/nonexistent/deeply/nested".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -691,7 +1165,7 @@ mod tests {
             .add
             .insert("/items/0/gateway".to_string(), "barbacane".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -708,7 +1182,7 @@ mod tests {
             .rename
             .insert("/field".to_string(), "/field".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -726,7 +1200,7 @@ mod tests {
             .rename
             .insert("/items/0/oldKey".to_string(), "/items/0/newKey".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -743,7 +1217,7 @@ mod tests {
         let mut config = BodyConfig::default();
         config.remove.push("/items/0/secret".to_string());
 
-        let result = transform_body(&body, &config);
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
         let json: Value =
             serde_json::from_slice(&result.expect("should have body")).expect("valid json");
 
@@ -753,11 +1227,553 @@ mod tests {
         assert_eq!(json["items"][1]["secret"], "y");
     }
 
+    #[test]
+    fn test_body_add_status_variable() {
+        let mut resp = create_test_response();
+        resp.status = 404;
+        let body = Some(br#"{"error":"not found"}"#.to_vec());
+
+        let mut config = BodyConfig::default();
+        config
+            .add
+            .insert("/upstream_status".to_string(), "$status".to_string());
+
+        let result = transform_body(&body, &config, &resp, BodyFormat::Json);
+        let json: Value =
+            serde_json::from_slice(&result.expect("should have body")).expect("valid json");
+
+        assert_eq!(json["upstream_status"], 404);
+        assert!(json["upstream_status"].is_number());
+    }
+
+    #[test]
+    fn test_body_add_header_variable() {
+        let body = Some(br#"{"user":"john"}"#.to_vec());
+
+        let mut config = BodyConfig::default();
+        config
+            .add
+            .insert("/meta/server".to_string(), "$header.Server".to_string());
+        config
+            .add
+            .insert("/meta/literal".to_string(), "200".to_string());
+
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
+        let json: Value =
+            serde_json::from_slice(&result.expect("should have body")).expect("valid json");
+
+        assert_eq!(json["meta"]["server"], "upstream/1.0");
+        // Literal values stay strings even when they look numeric.
+        assert_eq!(json["meta"]["literal"], "200");
+    }
+
+    #[test]
+    fn test_body_add_missing_header_is_empty_string() {
+        let body = Some(br#"{"user":"john"}"#.to_vec());
+
+        let mut config = BodyConfig::default();
+        config
+            .add
+            .insert("/trace".to_string(), "$header.x-trace-id".to_string());
+
+        let result = transform_body(&body, &config, &create_test_response(), BodyFormat::Json);
+        let json: Value =
+            serde_json::from_slice(&result.expect("should have body")).expect("valid json");
+
+        assert_eq!(json["trace"], "");
+    }
+
+    #[test]
+    fn test_interpolate_response_value() {
+        let resp = create_test_response();
+        assert_eq!(interpolate_response_value("$status", &resp), "200");
+        assert_eq!(
+            interpolate_response_value("$header.x-powered-by", &resp),
+            "Express"
+        );
+        assert_eq!(interpolate_response_value("$header.missing", &resp), "");
+        assert_eq!(interpolate_response_value("literal", &resp), "literal");
+    }
+
+    // -- Status range tests -------------------------------------------------
+
+    #[test]
+    fn test_status_matcher_parse() {
+        assert_eq!(StatusMatcher::parse("200"), Some(StatusMatcher::Exact(200)));
+        assert_eq!(StatusMatcher::parse("4xx"), Some(StatusMatcher::Class(4)));
+        assert_eq!(StatusMatcher::parse("5XX"), Some(StatusMatcher::Class(5)));
+        assert_eq!(StatusMatcher::parse("2xX"), Some(StatusMatcher::Class(2)));
+        assert_eq!(
+            StatusMatcher::parse("500-599"),
+            Some(StatusMatcher::Range(500, 599))
+        );
+        assert_eq!(StatusMatcher::parse("599-500"), None);
+        assert_eq!(StatusMatcher::parse("xx"), None);
+        assert_eq!(StatusMatcher::parse("error"), None);
+    }
+
+    fn scoped_plugin(status_range: &str) -> ResponseTransformer {
+        serde_json::from_str(&format!(
+            r#"{{
+                "status_range": "{}",
+                "headers": {{"add": {{"cache-control": "no-store"}}}},
+                "body": {{"add": {{"/scoped": "yes"}}}}
+            }}"#,
+            status_range
+        ))
+        .expect("valid json")
+    }
+
+    fn with_status(status: u16) -> Response {
+        let mut resp = create_test_response();
+        resp.status = status;
+        resp
+    }
+
+    #[test]
+    fn test_status_range_exact_code() {
+        let mut plugin = scoped_plugin("404");
+
+        let result = plugin.on_response(with_status(404));
+        assert_eq!(
+            result.headers.get("cache-control"),
+            Some(&"no-store".to_string())
+        );
+        let json: Value = serde_json::from_slice(result.body.as_ref().expect("should have body"))
+            .expect("valid json");
+        assert_eq!(json["scoped"], "yes");
+
+        let result = plugin.on_response(with_status(400));
+        assert_eq!(result.headers.get("cache-control"), None);
+    }
+
+    #[test]
+    fn test_status_range_class_match() {
+        let mut plugin = scoped_plugin("4xx");
+
+        for status in [400, 403, 499] {
+            let result = plugin.on_response(with_status(status));
+            assert_eq!(
+                result.headers.get("cache-control"),
+                Some(&"no-store".to_string()),
+                "status {}",
+                status
+            );
+        }
+        let result = plugin.on_response(with_status(500));
+        assert_eq!(result.headers.get("cache-control"), None);
+    }
+
+    #[test]
+    fn test_status_range_excludes_current_status() {
+        let mut plugin = scoped_plugin("500-599");
+        let upstream = create_test_response();
+
+        let result = plugin.on_response(upstream.clone());
+        assert_eq!(result.headers, upstream.headers);
+        assert_eq!(result.body, upstream.body);
+    }
+
+    #[test]
+    fn test_status_range_uses_upstream_status() {
+        let mut plugin: ResponseTransformer = serde_json::from_str(
+            r#"{
+                "status": {"500": 503},
+                "status_range": "500",
+                "headers": {"add": {"retry-after": "30"}}
+            }"#,
+        )
+        .expect("valid json");
+
+        let result = plugin.on_response(with_status(500));
+        assert_eq!(result.status, 503);
+        assert_eq!(result.headers.get("retry-after"), Some(&"30".to_string()));
+    }
+
+    #[test]
+    fn test_status_range_invalid_matches_nothing() {
+        let mut plugin = scoped_plugin("bogus");
+        let result = plugin.on_response(create_test_response());
+        assert_eq!(result.headers.get("cache-control"), None);
+    }
+
+    // -- max_body_bytes tests -----------------------------------------------
+
+    fn limited_plugin(max: usize) -> ResponseTransformer {
+        serde_json::from_value(serde_json::json!({
+            "max_body_bytes": max,
+            "body": {"remove": ["/age"]}
+        }))
+        .expect("valid json")
+    }
+
+    #[test]
+    fn test_max_body_bytes_under_limit_transforms() {
+        let upstream = create_test_response();
+        let mut plugin = limited_plugin(upstream.body.as_ref().expect("body").len());
+
+        let result = plugin.on_response(upstream);
+        let json: Value = serde_json::from_slice(&result.body.expect("body")).expect("json");
+        assert_eq!(json, serde_json::json!({"user": "john"}));
+    }
+
+    #[test]
+    fn test_max_body_bytes_over_limit_passes_through() {
+        let host = barbacane_plugin_sdk::mock_host::MockHost::install();
+        let upstream = create_test_response();
+        let mut plugin = limited_plugin(upstream.body.as_ref().expect("body").len() - 1);
+
+        let result = plugin.on_response(upstream.clone());
+        assert_eq!(result.body, upstream.body);
+        let logs = host.logs();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0, 1);
+        assert!(logs[0].1.contains("max_body_bytes"), "{}", logs[0].1);
+    }
+
+    // -- only_when_accept tests ---------------------------------------------
+
+    fn accept_plugin() -> ResponseTransformer {
+        serde_json::from_value(serde_json::json!({
+            "only_when_accept": ["application/vnd.acme.trimmed+json"],
+            "headers": {"add": {"x-transformed": "true"}},
+            "body": {"remove": ["/age"]}
+        }))
+        .expect("valid json")
+    }
+
+    fn request_with_accept(accept: Option<&str>) -> Request {
+        let mut headers = BTreeMap::new();
+        if let Some(accept) = accept {
+            headers.insert("accept".to_string(), accept.to_string());
+        }
+        Request {
+            method: "GET".to_string(),
+            path: "/users/1".to_string(),
+            query: None,
+            headers,
+            body: None,
+            client_ip: "127.0.0.1".to_string(),
+            path_params: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_only_when_accept_matching_applies_body_transforms() {
+        let mut plugin = accept_plugin();
+        plugin.on_request(request_with_accept(Some(
+            "text/html;q=0.9, Application/Vnd.Acme.Trimmed+JSON",
+        )));
+
+        let result = plugin.on_response(create_test_response());
+        let json: Value = serde_json::from_slice(&result.body.expect("body")).expect("json");
+        assert_eq!(json, serde_json::json!({"user": "john"}));
+    }
+
+    #[test]
+    fn test_only_when_accept_non_matching_leaves_body_untouched() {
+        let mut plugin = accept_plugin();
+        for accept in [
+            Some("application/json"),
+            Some("*/*"),
+            Some("application/vnd.acme.trimmed+json;q=0"),
+            None,
+        ] {
+            plugin.on_request(request_with_accept(accept));
+            let upstream = create_test_response();
+            let result = plugin.on_response(upstream.clone());
+            assert_eq!(result.body, upstream.body, "accept: {:?}", accept);
+            // Header transforms are not gated by Accept.
+            assert_eq!(result.headers.get("x-transformed").unwrap(), "true");
+        }
+    }
+
+    #[test]
+    fn test_only_when_accept_resets_between_requests() {
+        let mut plugin = accept_plugin();
+        plugin.on_request(request_with_accept(Some(
+            "application/vnd.acme.trimmed+json",
+        )));
+        plugin.on_response(create_test_response());
+
+        // The next request has no Accept header: it must not reuse the last one.
+        plugin.on_request(request_with_accept(None));
+        let upstream = create_test_response();
+        assert_eq!(plugin.on_response(upstream.clone()).body, upstream.body);
+    }
+
+    #[test]
+    fn test_media_type_matches_wildcard_pattern() {
+        assert!(media_type_matches("application/*", "application/json"));
+        assert!(media_type_matches("*/*", "text/plain"));
+        assert!(media_type_matches(
+            "application/json; charset=utf-8",
+            "application/json"
+        ));
+        assert!(!media_type_matches("application/json", "application/xml"));
+        assert!(!media_type_matches("json", "application/json"));
+    }
+
+    // -- body_format tests --------------------------------------------------
+
+    fn yaml_plugin(config: Value) -> ResponseTransformer {
+        serde_json::from_value(config).expect("valid json")
+    }
+
+    fn yaml_response(content_type: &str, body: &str) -> Response {
+        let mut resp = create_test_response();
+        resp.headers
+            .insert("content-type".to_string(), content_type.to_string());
+        resp.body = Some(body.as_bytes().to_vec());
+        resp
+    }
+
+    #[test]
+    fn test_yaml_body_detected_from_content_type() {
+        let mut plugin = yaml_plugin(serde_json::json!({
+            "body": {
+                "add": {"/data/meta/source": "gateway"},
+                "remove": ["/data/internal"],
+                "rename": {"/data/id": "/data/user_id"}
+            }
+        }));
+        let resp = yaml_response(
+            "application/x-yaml",
+            "data:\n  id: 42\n  internal: true\n  meta: {}\n",
+        );
+
+        let body = plugin.on_response(resp).body.expect("body");
+        assert!(serde_json::from_slice::<Value>(&body).is_err());
+        let yaml: Value = serde_yaml::from_slice(&body).expect("yaml");
+        assert_eq!(
+            yaml,
+            serde_json::json!({"data": {"user_id": 42, "meta": {"source": "gateway"}}})
+        );
+    }
+
+    #[test]
+    fn test_yaml_body_format_from_config() {
+        let mut plugin = yaml_plugin(serde_json::json!({
+            "body_format": "yaml",
+            "json_patch": [{"op": "remove", "path": "/data/secret"}]
+        }));
+        let resp = yaml_response("text/plain", "data:\n  name: John\n  secret: x\n");
+
+        let body = plugin.on_response(resp).body.expect("body");
+        let yaml: Value = serde_yaml::from_slice(&body).expect("yaml");
+        assert_eq!(yaml, serde_json::json!({"data": {"name": "John"}}));
+    }
+
+    #[test]
+    fn test_non_json_non_yaml_body_passes_through() {
+        let mut plugin = yaml_plugin(serde_json::json!({
+            "body": {"remove": ["/age"]}
+        }));
+        let resp = yaml_response("text/yaml", "{ not: [valid");
+
+        let result = plugin.on_response(resp);
+        assert_eq!(result.body.as_deref(), Some(&b"{ not: [valid"[..]));
+    }
+
+    // -- set_body tests -----------------------------------------------------
+
+    #[test]
+    fn test_set_body_replaces_non_json_body() {
+        let mut plugin: ResponseTransformer = serde_json::from_value(serde_json::json!({
+            "status_range": "5xx",
+            "set_body": { "error": "internal error" }
+        }))
+        .unwrap();
+
+        let mut headers = BTreeMap::new();
+        headers.insert("Content-Type".to_string(), "text/html".to_string());
+        let resp = Response {
+            status: 500,
+            headers,
+            body: Some(b"<html>stack trace</html>".to_vec()),
+        };
+
+        let result = plugin.on_response(resp);
+        assert_eq!(
+            result.body.as_deref(),
+            Some(br#"{"error":"internal error"}"#.as_slice())
+        );
+        assert_eq!(
+            result.headers.get("content-type").map(String::as_str),
+            Some("application/json")
+        );
+        assert!(!result.headers.contains_key("Content-Type"));
+    }
+
+    #[test]
+    fn test_set_body_then_body_edits() {
+        let mut plugin: ResponseTransformer = serde_json::from_value(serde_json::json!({
+            "set_body": { "error": "internal error", "trace": "x" },
+            "body": {
+                "add": { "/status": "$status" },
+                "remove": ["/trace"]
+            },
+            "max_body_bytes": 1
+        }))
+        .unwrap();
+
+        let mut resp = create_test_response();
+        resp.status = 503;
+        resp.headers.insert(
+            "content-type".to_string(),
+            "application/problem+json".to_string(),
+        );
+
+        let result = plugin.on_response(resp);
+        let body: Value = serde_json::from_slice(&result.body.unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "error": "internal error", "status": 503 })
+        );
+        // Already a JSON type, so the content type is kept.
+        assert_eq!(
+            result.headers.get("content-type").map(String::as_str),
+            Some("application/problem+json")
+        );
+    }
+
+    #[test]
+    fn test_without_set_body_body_is_untouched() {
+        let mut plugin: ResponseTransformer = serde_json::from_value(serde_json::json!({
+            "headers": { "add": { "x-seen": "1" } }
+        }))
+        .unwrap();
+
+        let resp = create_test_response();
+        let original = resp.body.clone();
+        let result = plugin.on_response(resp);
+        assert_eq!(result.body, original);
+        assert_eq!(
+            result.headers.get("content-type").map(String::as_str),
+            Some("application/json")
+        );
+    }
+
+    // -- content-length tests -----------------------------------------------
+
+    #[test]
+    fn test_body_edit_drops_content_length() {
+        let mut plugin: ResponseTransformer = serde_json::from_value(serde_json::json!({
+            "body": { "remove": ["/age"] }
+        }))
+        .unwrap();
+
+        let mut resp = create_test_response();
+        resp.headers
+            .insert("Content-Length".to_string(), "24".to_string());
+
+        let result = plugin.on_response(resp);
+        assert_eq!(
+            result.body.as_deref(),
+            Some(br#"{"user":"john"}"#.as_slice())
+        );
+        assert!(!result
+            .headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case("content-length")));
+    }
+
+    #[test]
+    fn test_set_body_drops_content_length() {
+        let mut plugin: ResponseTransformer = serde_json::from_value(serde_json::json!({
+            "set_body": { "error": "masked" }
+        }))
+        .unwrap();
+
+        let mut resp = create_test_response();
+        resp.headers
+            .insert("content-length".to_string(), "24".to_string());
+
+        let result = plugin.on_response(resp);
+        assert!(!result.headers.contains_key("content-length"));
+    }
+
+    #[test]
+    fn test_content_length_kept_without_body_transform() {
+        let mut plugin: ResponseTransformer = serde_json::from_value(serde_json::json!({
+            "headers": { "remove": ["server"] }
+        }))
+        .unwrap();
+
+        let mut resp = create_test_response();
+        resp.headers
+            .insert("content-length".to_string(), "24".to_string());
+
+        let result = plugin.on_response(resp);
+        assert_eq!(
+            result.headers.get("content-length").map(String::as_str),
+            Some("24")
+        );
+    }
+
+    // -- JSON Patch tests ---------------------------------------------------
+
+    fn patch_response(patch: &str, body: &str) -> Option<Value> {
+        let mut plugin: ResponseTransformer =
+            serde_json::from_str(&format!(r#"{{"json_patch": {}}}"#, patch)).expect("valid json");
+        let mut resp = create_test_response();
+        resp.body = Some(body.as_bytes().to_vec());
+
+        plugin
+            .on_response(resp)
+            .body
+            .map(|b| serde_json::from_slice(&b).expect("json body"))
+    }
+
+    #[test]
+    fn test_json_patch_add_remove_replace() {
+        let body = patch_response(
+            r#"[
+                {"op": "add", "path": "/meta", "value": {"gateway": "barbacane"}},
+                {"op": "remove", "path": "/internal"},
+                {"op": "replace", "path": "/age", "value": 31}
+            ]"#,
+            r#"{"user": "john", "age": 30, "internal": true}"#,
+        );
+        assert_eq!(
+            body,
+            Some(serde_json::json!({"user": "john", "age": 31, "meta": {"gateway": "barbacane"}}))
+        );
+    }
+
+    #[test]
+    fn test_json_patch_failed_test_leaves_body_unchanged() {
+        let body = patch_response(
+            r#"[
+                {"op": "test", "path": "/user", "value": "jane"},
+                {"op": "remove", "path": "/age"}
+            ]"#,
+            r#"{"user": "john", "age": 30}"#,
+        );
+        assert_eq!(body, Some(serde_json::json!({"user": "john", "age": 30})));
+    }
+
+    #[test]
+    fn test_json_patch_takes_precedence_over_body() {
+        let body = patch_response(
+            r#"[{"op": "copy", "from": "/user", "path": "/login"}], "body": {"remove": ["/user"]}"#,
+            r#"{"user": "john"}"#,
+        );
+        assert_eq!(
+            body,
+            Some(serde_json::json!({"user": "john", "login": "john"}))
+        );
+    }
+
     // -- Integration / full plugin tests ------------------------------------
 
     #[test]
     fn test_on_response_combined() {
         let mut plugin = ResponseTransformer {
+            status_range: None,
+            status_mapping: OnceCell::new(),
+            set_body: None,
+            status_matcher: OnceCell::new(),
             status: Some({
                 let mut m = BTreeMap::new();
                 m.insert("200".to_string(), 201);
@@ -772,6 +1788,7 @@ mod tests {
                 set: BTreeMap::new(),
                 remove: vec!["server".to_string()],
                 rename: BTreeMap::new(),
+                ..Default::default()
             }),
             body: Some(BodyConfig {
                 add: {
@@ -782,6 +1799,12 @@ mod tests {
                 remove: vec!["/internal".to_string()],
                 rename: BTreeMap::new(),
             }),
+            json_patch: None,
+            max_body_bytes: None,
+            body_format: None,
+            compiled_patch: OnceCell::new(),
+            only_when_accept: None,
+            request_accept: None,
         };
 
         let mut headers = BTreeMap::new();
@@ -810,12 +1833,43 @@ mod tests {
         assert_eq!(json.get("internal"), None);
     }
 
+    #[test]
+    fn test_on_response_interpolates_from_upstream_snapshot() {
+        let mut plugin: ResponseTransformer = serde_json::from_str(
+            r#"{
+                "status": {"200": 201},
+                "headers": {"remove": ["server"]},
+                "body": {"add": {"/upstream/status": "$status", "/upstream/server": "$header.server"}}
+            }"#,
+        )
+        .expect("valid json");
+
+        let result = plugin.on_response(create_test_response());
+
+        assert_eq!(result.status, 201);
+        assert_eq!(result.headers.get("server"), None);
+        let json: Value = serde_json::from_slice(result.body.as_ref().expect("should have body"))
+            .expect("valid json");
+        assert_eq!(json["upstream"]["status"], 200);
+        assert_eq!(json["upstream"]["server"], "upstream/1.0");
+    }
+
     #[test]
     fn test_on_response_empty_config() {
         let mut plugin = ResponseTransformer {
+            status_range: None,
+            status_mapping: OnceCell::new(),
+            set_body: None,
+            status_matcher: OnceCell::new(),
             status: None,
             headers: None,
             body: None,
+            json_patch: None,
+            max_body_bytes: None,
+            body_format: None,
+            compiled_patch: OnceCell::new(),
+            only_when_accept: None,
+            request_accept: None,
         };
 
         let resp = create_test_response();
@@ -833,6 +1887,10 @@ mod tests {
     #[test]
     fn test_on_request_passthrough() {
         let mut plugin = ResponseTransformer {
+            status_range: None,
+            status_mapping: OnceCell::new(),
+            set_body: None,
+            status_matcher: OnceCell::new(),
             status: Some({
                 let mut m = BTreeMap::new();
                 m.insert("200".to_string(), 201);
@@ -847,8 +1905,15 @@ mod tests {
                 set: BTreeMap::new(),
                 remove: vec![],
                 rename: BTreeMap::new(),
+                ..Default::default()
             }),
             body: None,
+            json_patch: None,
+            max_body_bytes: None,
+            body_format: None,
+            compiled_patch: OnceCell::new(),
+            only_when_accept: None,
+            request_accept: None,
         };
 
         let req = Request {
@@ -877,6 +1942,8 @@ mod tests {
         assert!(config.status.is_none());
         assert!(config.headers.is_none());
         assert!(config.body.is_none());
+        assert!(config.json_patch.is_none());
+        assert!(config.status_range.is_none());
     }
 
     #[test]
@@ -920,6 +1987,10 @@ mod tests {
     #[test]
     fn test_status_only_config() {
         let mut plugin = ResponseTransformer {
+            status_range: None,
+            status_mapping: OnceCell::new(),
+            set_body: None,
+            status_matcher: OnceCell::new(),
             status: Some({
                 let mut m = BTreeMap::new();
                 m.insert("200".to_string(), 201);
@@ -928,6 +1999,12 @@ mod tests {
             }),
             headers: None,
             body: None,
+            json_patch: None,
+            max_body_bytes: None,
+            body_format: None,
+            compiled_patch: OnceCell::new(),
+            only_when_accept: None,
+            request_accept: None,
         };
 
         let resp = Response {